@@ -0,0 +1,112 @@
+//! Compares seeding the key RNG on every attempt with the thread-local RNG
+//! `KeyVault::create_key` uses. The crate is a binary, so the claim loop is
+//! mirrored here instead of imported
+
+use std::{
+    cell::RefCell,
+    hint::black_box,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use criterion::{Criterion, criterion_group, criterion_main};
+use dashmap::{DashMap, mapref::entry::Entry};
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+
+thread_local! {
+    static RNG: RefCell<ChaCha8Rng> = RefCell::new(ChaCha8Rng::from_os_rng());
+}
+
+struct Vault {
+    active_keys: DashMap<(String, String), u64>,
+    prefix: Vec<String>,
+    suffix: Vec<String>,
+}
+
+impl Vault {
+    fn new(words: usize) -> Self {
+        Self {
+            active_keys: DashMap::new(),
+            prefix: (0..words).map(|i| format!("prefix{}", i)).collect(),
+            suffix: (0..words).map(|i| format!("suffix{}", i)).collect(),
+        }
+    }
+
+    fn try_claim(&self, key: (String, String)) -> Option<String> {
+        match self.active_keys.entry(key) {
+            Entry::Occupied(_) => None,
+            Entry::Vacant(entry) => {
+                let created_at = SystemTime::now()
+                    .duration_since(UNIX_EPOCH)
+                    .unwrap()
+                    .as_secs();
+                let key = entry.key();
+                let word = format!("{} {}", key.0, key.1);
+                entry.insert(created_at);
+                Some(word)
+            }
+        }
+    }
+
+    fn create_key(&self, random_idx: impl Fn(&Self) -> (usize, usize)) -> Option<String> {
+        for _ in 0..100 {
+            let (idx1, idx2) = random_idx(self);
+            let key = (self.prefix[idx1].clone(), self.suffix[idx2].clone());
+            if let Some(key) = self.try_claim(key) {
+                return Some(key);
+            }
+        }
+
+        None
+    }
+
+    fn release_key(&self, key_word: &str) {
+        let (prefix, suffix) = key_word.split_once(' ').unwrap();
+        self.active_keys
+            .remove(&(prefix.to_string(), suffix.to_string()));
+    }
+}
+
+fn seeded_per_call(vault: &Vault) -> (usize, usize) {
+    let mut rng = ChaCha8Rng::from_os_rng();
+    (
+        rng.random_range(0..vault.prefix.len()),
+        rng.random_range(0..vault.suffix.len()),
+    )
+}
+
+fn thread_local_rng(vault: &Vault) -> (usize, usize) {
+    RNG.with_borrow_mut(|rng| {
+        (
+            rng.random_range(0..vault.prefix.len()),
+            rng.random_range(0..vault.suffix.len()),
+        )
+    })
+}
+
+/// Half of the keys are held, so some attempts collide like they do in a
+/// busy vault
+fn create_key(c: &mut Criterion) {
+    let vault = Vault::new(100);
+    while vault.active_keys.len() < 5_000 {
+        vault.create_key(thread_local_rng);
+    }
+
+    let mut group = c.benchmark_group("create_key");
+    group.bench_function("seeded_per_call", |b| {
+        b.iter(|| {
+            let key = vault.create_key(seeded_per_call).unwrap();
+            vault.release_key(black_box(&key));
+        })
+    });
+    group.bench_function("thread_local", |b| {
+        b.iter(|| {
+            let key = vault.create_key(thread_local_rng).unwrap();
+            vault.release_key(black_box(&key));
+        })
+    });
+    group.finish();
+}
+
+criterion_group!(benches, create_key);
+criterion_main!(benches);
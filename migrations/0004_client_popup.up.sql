@@ -0,0 +1,10 @@
+-- Add up migration script here
+
+CREATE TABLE "client_popup" (
+    "id" INT PRIMARY KEY DEFAULT 1 CHECK ("id" = 1),
+    "heading" TEXT NOT NULL,
+    "paragraph" TEXT NOT NULL,
+    "active" BOOLEAN NOT NULL DEFAULT FALSE,
+    "active_from" TIMESTAMPTZ,
+    "active_until" TIMESTAMPTZ
+);
@@ -0,0 +1,12 @@
+-- Add down migration script here
+
+DROP INDEX IF EXISTS "idx_client_popup_priority";
+
+DELETE FROM "client_popup"
+WHERE "id" NOT IN (
+    SELECT "id" FROM "client_popup" ORDER BY "priority" LIMIT 1
+);
+
+ALTER TABLE "client_popup" DROP COLUMN "priority";
+ALTER TABLE "client_popup" DROP COLUMN "id";
+ALTER TABLE "client_popup" ADD COLUMN "id" INT PRIMARY KEY DEFAULT 1 CHECK ("id" = 1);
@@ -0,0 +1,9 @@
+-- Add up migration script here
+
+ALTER TABLE "client_popup" DROP COLUMN "id";
+
+ALTER TABLE "client_popup"
+ADD COLUMN "id" UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+ADD COLUMN "priority" SMALLINT NOT NULL DEFAULT 0;
+
+CREATE INDEX "idx_client_popup_priority" ON "client_popup" ("priority");
@@ -0,0 +1,3 @@
+-- Add down migration script here
+
+ALTER TABLE "integration" DROP COLUMN IF EXISTS "url";
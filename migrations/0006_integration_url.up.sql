@@ -0,0 +1,3 @@
+-- Add up migration script here
+
+ALTER TABLE "integration" ADD COLUMN "url" VARCHAR(255);
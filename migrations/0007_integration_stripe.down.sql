@@ -0,0 +1,15 @@
+-- Add down migration script here
+
+DELETE FROM "integration" WHERE "name" = 'stripe';
+
+ALTER TYPE "integration_name" RENAME TO "integration_name_old";
+
+CREATE TYPE "integration_name" AS ENUM (
+    'auth0',
+    'session'
+);
+
+ALTER TABLE "integration"
+    ALTER COLUMN "name" TYPE "integration_name" USING "name"::text::"integration_name";
+
+DROP TYPE "integration_name_old";
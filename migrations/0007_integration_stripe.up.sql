@@ -0,0 +1,3 @@
+-- Add up migration script here
+
+ALTER TYPE "integration_name" ADD VALUE IF NOT EXISTS 'stripe';
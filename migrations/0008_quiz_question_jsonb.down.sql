@@ -0,0 +1,14 @@
+-- Add down migration script here
+
+ALTER TABLE "quiz_game" ADD COLUMN "questions_text" TEXT[] NOT NULL DEFAULT '{}';
+
+UPDATE "quiz_game" quiz
+SET "questions_text" = COALESCE(ARRAY(
+    SELECT question.value->>'text'
+    FROM jsonb_array_elements(quiz."questions") WITH ORDINALITY AS question(value, idx)
+    ORDER BY question.idx
+), '{}');
+
+ALTER TABLE "quiz_game" DROP COLUMN "questions";
+ALTER TABLE "quiz_game" RENAME COLUMN "questions_text" TO "questions";
+ALTER TABLE "quiz_game" ALTER COLUMN "questions" DROP DEFAULT;
@@ -0,0 +1,22 @@
+-- Add up migration script here
+
+ALTER TABLE "quiz_game" ADD COLUMN "questions_json" JSONB NOT NULL DEFAULT '[]'::jsonb;
+
+UPDATE "quiz_game" quiz
+SET "questions_json" = COALESCE((
+    SELECT jsonb_agg(
+        jsonb_build_object(
+            'id', uuid_generate_v4(),
+            'text', question.text,
+            'options', '[]'::jsonb,
+            'correct_index', NULL,
+            'seconds', NULL
+        )
+        ORDER BY question.idx
+    )
+    FROM unnest(quiz."questions") WITH ORDINALITY AS question(text, idx)
+), '[]'::jsonb);
+
+ALTER TABLE "quiz_game" DROP COLUMN "questions";
+ALTER TABLE "quiz_game" RENAME COLUMN "questions_json" TO "questions";
+ALTER TABLE "quiz_game" ALTER COLUMN "questions" DROP DEFAULT;
@@ -0,0 +1,4 @@
+-- Add down migration script here
+
+UPDATE "game_base" SET "description" = LEFT("description", 150) WHERE LENGTH("description") > 150;
+ALTER TABLE "game_base" ALTER COLUMN "description" TYPE VARCHAR(150);
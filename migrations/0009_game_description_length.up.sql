@@ -0,0 +1,3 @@
+-- Add up migration script here
+
+ALTER TABLE "game_base" ALTER COLUMN "description" TYPE VARCHAR(500);
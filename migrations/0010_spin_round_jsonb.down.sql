@@ -0,0 +1,14 @@
+-- Add down migration script here
+
+ALTER TABLE "spin_game" ADD COLUMN "rounds_text" TEXT[] NOT NULL DEFAULT '{}';
+
+UPDATE "spin_game" spin
+SET "rounds_text" = COALESCE(ARRAY(
+    SELECT round.value->>'text'
+    FROM jsonb_array_elements(spin."rounds") WITH ORDINALITY AS round(value, idx)
+    ORDER BY round.idx
+), '{}');
+
+ALTER TABLE "spin_game" DROP COLUMN "rounds";
+ALTER TABLE "spin_game" RENAME COLUMN "rounds_text" TO "rounds";
+ALTER TABLE "spin_game" ALTER COLUMN "rounds" DROP DEFAULT;
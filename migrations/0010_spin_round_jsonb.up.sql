@@ -0,0 +1,20 @@
+-- Add up migration script here
+
+ALTER TABLE "spin_game" ADD COLUMN "rounds_json" JSONB NOT NULL DEFAULT '[]'::jsonb;
+
+UPDATE "spin_game" spin
+SET "rounds_json" = COALESCE((
+    SELECT jsonb_agg(
+        jsonb_build_object(
+            'text', round.text,
+            'weight', 1,
+            'exclude_repeat', FALSE
+        )
+        ORDER BY round.idx
+    )
+    FROM unnest(spin."rounds") WITH ORDINALITY AS round(text, idx)
+), '[]'::jsonb);
+
+ALTER TABLE "spin_game" DROP COLUMN "rounds";
+ALTER TABLE "spin_game" RENAME COLUMN "rounds_json" TO "rounds";
+ALTER TABLE "spin_game" ALTER COLUMN "rounds" DROP DEFAULT;
@@ -0,0 +1,4 @@
+-- Add down migration script here
+
+DROP INDEX IF EXISTS "idx_game_base_created_by";
+ALTER TABLE "game_base" DROP COLUMN "created_by";
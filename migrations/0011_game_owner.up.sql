@@ -0,0 +1,6 @@
+-- Add up migration script here
+
+-- No foreign key, the owner can be either a pseudo user or a base user
+ALTER TABLE "game_base" ADD COLUMN "created_by" UUID;
+
+CREATE INDEX "idx_game_base_created_by" ON "game_base" ("created_by", "last_played" DESC);
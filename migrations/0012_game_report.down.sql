@@ -0,0 +1,5 @@
+-- Add down migration script here
+
+DROP TABLE IF EXISTS "game_report";
+ALTER TABLE "game_base" DROP COLUMN "hidden";
+DROP TYPE IF EXISTS "report_reason";
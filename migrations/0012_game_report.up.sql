@@ -0,0 +1,23 @@
+-- Add up migration script here
+
+CREATE TYPE "report_reason" AS ENUM (
+    'offensive',
+    'spam',
+    'other'
+);
+
+ALTER TABLE "game_base" ADD COLUMN "hidden" BOOLEAN NOT NULL DEFAULT FALSE;
+
+CREATE TABLE "game_report" (
+    "id" UUID PRIMARY KEY,
+    "base_id" UUID NOT NULL,
+    "reporter_id" UUID NOT NULL,
+    "reason" report_reason NOT NULL,
+    "description" VARCHAR(500),
+    "created_at" TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    UNIQUE ("base_id", "reporter_id")
+);
+
+ALTER TABLE "game_report"
+ADD CONSTRAINT "fk_game_report_base"
+FOREIGN KEY ("base_id") REFERENCES "game_base"("id") ON DELETE CASCADE;
@@ -0,0 +1,4 @@
+-- Add down migration script here
+
+ALTER TABLE "game_base" DROP COLUMN "visibility";
+DROP TYPE IF EXISTS "game_visibility";
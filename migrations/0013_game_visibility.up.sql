@@ -0,0 +1,9 @@
+-- Add up migration script here
+
+CREATE TYPE "game_visibility" AS ENUM (
+    'public',
+    'unlisted',
+    'private'
+);
+
+ALTER TABLE "game_base" ADD COLUMN "visibility" game_visibility NOT NULL DEFAULT 'public';
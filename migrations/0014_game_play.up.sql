@@ -0,0 +1,14 @@
+-- Add up migration script here
+
+CREATE TABLE "game_play" (
+    "id" UUID PRIMARY KEY,
+    "base_id" UUID NOT NULL,
+    "user_id" UUID,
+    "played_at" TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX "idx_game_play_played_at" ON "game_play" ("played_at", "base_id");
+
+ALTER TABLE "game_play"
+ADD CONSTRAINT "fk_game_play_base"
+FOREIGN KEY ("base_id") REFERENCES "game_base"("id") ON DELETE CASCADE;
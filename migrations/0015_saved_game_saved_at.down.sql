@@ -0,0 +1,5 @@
+-- Add down migration script here
+
+DROP INDEX IF EXISTS "idx_saved_game_user_saved_at";
+
+ALTER TABLE "saved_game" DROP COLUMN IF EXISTS "saved_at";
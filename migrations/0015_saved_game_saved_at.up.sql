@@ -0,0 +1,6 @@
+-- Add up migration script here
+
+ALTER TABLE "saved_game"
+ADD COLUMN "saved_at" TIMESTAMPTZ NOT NULL DEFAULT NOW();
+
+CREATE INDEX "idx_saved_game_user_saved_at" ON "saved_game" ("user_id", "saved_at" DESC);
@@ -0,0 +1,5 @@
+-- Add down migration script here
+
+DROP INDEX IF EXISTS "idx_system_log_correlation_id";
+
+ALTER TABLE "system_log" DROP COLUMN IF EXISTS "correlation_id";
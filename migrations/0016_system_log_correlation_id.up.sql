@@ -0,0 +1,5 @@
+-- Add up migration script here
+
+ALTER TABLE "system_log" ADD COLUMN "correlation_id" UUID;
+
+CREATE INDEX "idx_system_log_correlation_id" ON "system_log" ("correlation_id");
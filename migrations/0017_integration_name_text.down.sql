@@ -0,0 +1,13 @@
+-- Add down migration script here
+
+CREATE TYPE "integration_name" AS ENUM (
+    'auth0',
+    'session',
+    'stripe'
+);
+
+ALTER TABLE "integration"
+    DROP CONSTRAINT IF EXISTS "integration_name_format";
+
+ALTER TABLE "integration"
+    ALTER COLUMN "name" TYPE "integration_name" USING "name"::"integration_name";
@@ -0,0 +1,9 @@
+-- Add up migration script here
+
+ALTER TABLE "integration"
+    ALTER COLUMN "name" TYPE TEXT USING "name"::text;
+
+ALTER TABLE "integration"
+    ADD CONSTRAINT "integration_name_format" CHECK ("name" ~ '^[a-z0-9_]{1,40}$');
+
+DROP TYPE IF EXISTS "integration_name";
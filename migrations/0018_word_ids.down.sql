@@ -0,0 +1,5 @@
+-- Add down migration script here
+
+ALTER TABLE "suffix_word" DROP COLUMN IF EXISTS "id";
+
+ALTER TABLE "prefix_word" DROP COLUMN IF EXISTS "id";
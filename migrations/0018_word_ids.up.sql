@@ -0,0 +1,5 @@
+-- Add up migration script here
+
+ALTER TABLE "prefix_word" ADD COLUMN "id" UUID NOT NULL UNIQUE DEFAULT uuid_generate_v4();
+
+ALTER TABLE "suffix_word" ADD COLUMN "id" UUID NOT NULL UNIQUE DEFAULT uuid_generate_v4();
@@ -0,0 +1,5 @@
+-- Add down migration script here
+
+DROP INDEX IF EXISTS "idx_base_user_last_login_at";
+
+ALTER TABLE "base_user" DROP COLUMN IF EXISTS "last_login_at";
@@ -0,0 +1,5 @@
+-- Add up migration script here
+
+ALTER TABLE "base_user" ADD COLUMN "last_login_at" TIMESTAMPTZ;
+
+CREATE INDEX "idx_base_user_last_login_at" ON "base_user" ("last_login_at");
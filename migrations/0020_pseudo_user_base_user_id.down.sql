@@ -0,0 +1,3 @@
+-- Add down migration script here
+
+ALTER TABLE "pseudo_user" DROP COLUMN IF EXISTS "base_user_id";
@@ -0,0 +1,10 @@
+-- Add up migration script here
+
+ALTER TABLE "pseudo_user"
+ADD COLUMN "base_user_id" UUID REFERENCES "base_user"("id") ON DELETE SET NULL;
+
+-- Registered users share their id with the pseudo user created alongside them
+UPDATE "pseudo_user" AS p
+SET "base_user_id" = b."id"
+FROM "base_user" AS b
+WHERE b."id" = p."id";
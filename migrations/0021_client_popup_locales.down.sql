@@ -0,0 +1,14 @@
+-- Add down migration script here
+
+ALTER TABLE "client_popup"
+ADD COLUMN "heading" TEXT NOT NULL DEFAULT '',
+ADD COLUMN "paragraph" TEXT NOT NULL DEFAULT '';
+
+UPDATE "client_popup"
+SET "heading" = COALESCE("content" -> "default_locale" ->> 'heading', ''),
+    "paragraph" = COALESCE("content" -> "default_locale" ->> 'paragraph', '');
+
+ALTER TABLE "client_popup" ALTER COLUMN "heading" DROP DEFAULT;
+ALTER TABLE "client_popup" ALTER COLUMN "paragraph" DROP DEFAULT;
+ALTER TABLE "client_popup" DROP COLUMN "content";
+ALTER TABLE "client_popup" DROP COLUMN "default_locale";
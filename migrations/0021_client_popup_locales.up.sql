@@ -0,0 +1,14 @@
+-- Add up migration script here
+
+ALTER TABLE "client_popup"
+ADD COLUMN "content" JSONB NOT NULL DEFAULT '{}',
+ADD COLUMN "default_locale" TEXT NOT NULL DEFAULT 'nb';
+
+-- Existing popups were written in norwegian
+UPDATE "client_popup"
+SET "content" = jsonb_build_object(
+    'nb', jsonb_build_object('heading', "heading", 'paragraph', "paragraph")
+);
+
+ALTER TABLE "client_popup" DROP COLUMN "heading";
+ALTER TABLE "client_popup" DROP COLUMN "paragraph";
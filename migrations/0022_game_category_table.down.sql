@@ -0,0 +1,22 @@
+-- Add down migration script here
+
+ALTER TABLE "game_base" DROP CONSTRAINT IF EXISTS "game_base_category_fkey";
+ALTER TABLE "game_base" ALTER COLUMN "category" DROP DEFAULT;
+
+-- Games in categories added after the table was introduced fall back to the default
+UPDATE "game_base"
+SET "category" = 'default'
+WHERE "category" NOT IN ('casual', 'ladies', 'boys', 'default', 'random');
+
+DROP TABLE IF EXISTS "game_category";
+
+CREATE TYPE game_category AS ENUM (
+    'casual',
+    'ladies',
+    'boys',
+    'default',
+    'random'
+);
+
+ALTER TABLE "game_base" ALTER COLUMN "category" TYPE game_category USING "category"::game_category;
+ALTER TABLE "game_base" ALTER COLUMN "category" SET DEFAULT 'casual';
@@ -0,0 +1,26 @@
+-- Add up migration script here
+
+-- The enum type has to go first, the table would take its name
+ALTER TABLE "game_base" ALTER COLUMN "category" DROP DEFAULT;
+ALTER TABLE "game_base" ALTER COLUMN "category" TYPE TEXT USING "category"::TEXT;
+DROP TYPE "game_category";
+
+CREATE TABLE "game_category" (
+    "slug" TEXT PRIMARY KEY,
+    "display_name" JSONB NOT NULL DEFAULT '{}',
+    "sort_order" SMALLINT NOT NULL DEFAULT 0,
+    "active" BOOLEAN NOT NULL DEFAULT TRUE,
+    CONSTRAINT "game_category_slug_format" CHECK ("slug" ~ '^[a-z0-9_-]{1,30}$')
+);
+
+INSERT INTO "game_category" ("slug", "display_name", "sort_order") VALUES
+    ('casual', '{"nb": "Avslappet", "en": "Casual"}', 0),
+    ('random', '{"nb": "Tilfeldig", "en": "Random"}', 1),
+    ('ladies', '{"nb": "Jenter", "en": "Ladies"}', 2),
+    ('boys', '{"nb": "Gutter", "en": "Boys"}', 3),
+    ('default', '{"nb": "Standard", "en": "Default"}', 4);
+
+ALTER TABLE "game_base"
+ALTER COLUMN "category" SET DEFAULT 'casual',
+ADD CONSTRAINT "game_base_category_fkey"
+    FOREIGN KEY ("category") REFERENCES "game_category"("slug") ON UPDATE CASCADE;
@@ -0,0 +1,3 @@
+-- Add down migration script here
+
+ALTER TABLE "game_base" DROP COLUMN IF EXISTS "total_rounds_played";
@@ -0,0 +1,5 @@
+-- Add up migration script here
+
+-- Stored games only ever got the rounds of the session that created them
+ALTER TABLE "game_base" ADD COLUMN "total_rounds_played" BIGINT NOT NULL DEFAULT 0;
+UPDATE "game_base" SET "total_rounds_played" = "iterations";
@@ -0,0 +1,4 @@
+-- Add up migration script here
+
+-- The game pages count saves per game
+CREATE INDEX "idx_saved_game_base_id" ON "saved_game" ("base_id");
@@ -7,22 +7,26 @@ use axum::{
     middleware::Next,
     response::Response,
 };
-use jsonwebtoken::{Algorithm, DecodingKey, TokenData, Validation, decode, decode_header};
+use jsonwebtoken::{
+    Algorithm, DecodingKey, Validation, decode, decode_header, errors::ErrorKind,
+    get_current_timestamp,
+};
 use sqlx::{Pool, Postgres};
-use tracing::{error, info};
+use tracing::{error, info, warn};
+use uuid::Uuid;
 
 use crate::{
     config::config::CONFIG,
-    db::user::{ensure_pseudo_user, get_base_user_by_auth0_id},
+    db::user::{ensure_pseudo_user, get_base_user_by_auth0_id, touch_last_login},
     models::{
         app_state::AppState,
         auth::{Claims, Jwks},
         error::ServerError,
-        integration::{INTEGRATION_NAMES, IntegrationName},
+        request_context::RequestContext,
         system_log::{LogAction, LogCeverity},
         user::SubjectId,
     },
-    service::util::{extract_header, to_uuid},
+    service::{guest_token::verify_guest_token, trace_context::TraceContext, util::extract_header},
 };
 
 static GUEST_AUTHORIZATION: &str = "X-Guest-Authentication";
@@ -34,13 +38,16 @@ pub async fn auth_mw(
 ) -> Result<Response, ServerError> {
     let pseudo_header = extract_header(GUEST_AUTHORIZATION, req.headers());
     let token_header = extract_header(AUTHORIZATION.as_str(), req.headers());
+    let correlation_id = TraceContext::current()
+        .unwrap_or_else(TraceContext::new_root)
+        .correlation_id();
 
     match (pseudo_header, token_header) {
         (Some(pseudo_header), ..) => {
-            handle_pseudo_user(state.get_pool(), &mut req, &pseudo_header).await?;
+            handle_pseudo_user(state.get_pool(), &mut req, &pseudo_header, correlation_id).await?;
         }
         (None, Some(token_header)) => {
-            handle_token_header(state.clone(), &mut req, &token_header).await?;
+            handle_token_header(state.clone(), &mut req, &token_header, correlation_id).await?;
         }
         _ => {
             error!("Unauthorized request");
@@ -55,17 +62,19 @@ async fn handle_pseudo_user(
     pool: &Pool<Postgres>,
     request: &mut Request<Body>,
     pseudo_header: &str,
+    correlation_id: Uuid,
 ) -> Result<(), ServerError> {
-    let pseudo_id = to_uuid(pseudo_header)?;
+    let pseudo_id = verify_guest_token(pseudo_header, &CONFIG.guest)?;
 
     let pool_clone = pool.clone();
-    tokio::task::spawn(async move { ensure_pseudo_user(&pool_clone, pseudo_id).await });
+    tokio::task::spawn(
+        async move { ensure_pseudo_user(&pool_clone, pseudo_id, correlation_id).await },
+    );
 
     let subject = SubjectId::PseudoUser(pseudo_id);
     info!("Request by subject: {:?}", subject);
 
-    request.extensions_mut().insert(subject);
-    request.extensions_mut().insert(Claims::empty());
+    insert_context(request, subject, Claims::empty(), correlation_id);
 
     Ok(())
 }
@@ -74,6 +83,7 @@ async fn handle_token_header(
     state: Arc<AppState>,
     request: &mut Request<Body>,
     token_header: &str,
+    correlation_id: Uuid,
 ) -> Result<(), ServerError> {
     let Some(token) = token_header.strip_prefix("Bearer ") else {
         return Err(ServerError::Api(
@@ -82,14 +92,23 @@ async fn handle_token_header(
         ));
     };
 
-    let token_data = verify_jwt(token, state.get_jwks()).await?;
-    let claims: Claims = serde_json::from_value(token_data.claims)?;
+    let token_cache = state.get_token_cache();
+    let claims = match token_cache.get(token) {
+        Some(claims) => claims,
+        None => {
+            let claims = verify_jwt(token, state.get_jwks()).await?;
+            token_cache.insert(token, &claims);
+            claims
+        }
+    };
+
+    if claims.is_expired(CONFIG.auth0.jwt_leeway_secs) {
+        return Err(ServerError::JwtVerification("Token has expired".into()));
+    }
 
     let subject = match claims.is_machine() {
         true => {
-            let Some(int_name) =
-                IntegrationName::from_subject(&claims.sub, &INTEGRATION_NAMES).await
-            else {
+            let Some(int_name) = state.get_integrations().name_for_subject(&claims.sub) else {
                 error!("Unknown integration subject: {}", claims.sub);
                 return Err(ServerError::AccessDenied);
             };
@@ -101,11 +120,11 @@ async fn handle_token_header(
                 get_base_user_by_auth0_id(state.get_pool(), claims.auth0_id()).await?
             else {
                 state
-                    .syslog()
+                    .syslog("handle_base_user")
                     .action(LogAction::Read)
                     .ceverity(LogCeverity::Critical)
-                    .function("handle_base_user")
                     .description("Failed to get base user from auth0 id in middleware")
+                    .correlation_id(correlation_id)
                     .log_async();
 
                 return Err(ServerError::Internal(
@@ -113,19 +132,43 @@ async fn handle_token_header(
                 ));
             };
 
+            let pool = state.get_pool().clone();
+            tokio::spawn(async move {
+                if let Err(e) = touch_last_login(&pool, base_user.id).await {
+                    warn!("Failed to update last login for {}: {}", base_user.id, e);
+                }
+            });
+
             SubjectId::BaseUser(base_user.id)
         }
     };
 
     info!("Request by subject: {:?}", subject);
-    request.extensions_mut().insert(claims);
-    request.extensions_mut().insert(subject);
+    insert_context(request, subject, claims, correlation_id);
 
     return Ok(());
 }
 
+/// Handlers not yet on the `RequestContext` extractor still read the subject
+/// and claims as separate extensions
+pub fn insert_context(
+    request: &mut Request<Body>,
+    subject: SubjectId,
+    claims: Claims,
+    request_id: Uuid,
+) {
+    let extensions = request.extensions_mut();
+    extensions.insert(subject.clone());
+    extensions.insert(claims.clone());
+    extensions.insert(RequestContext::new(subject, claims, request_id));
+}
+
 // Warning: 65% AI generated code
-async fn verify_jwt(token: &str, jwks: &Jwks) -> Result<TokenData<serde_json::Value>, ServerError> {
+pub async fn verify_jwt(token: &str, jwks: &Jwks) -> Result<Claims, ServerError> {
+    if jwks.keys.is_empty() {
+        return Err(ServerError::JwtVerification("No JWKs are loaded".into()));
+    }
+
     let header = decode_header(token)
         .map_err(|e| ServerError::JwtVerification(format!("Failed to decode header: {}", e)))?;
 
@@ -139,13 +182,57 @@ async fn verify_jwt(token: &str, jwks: &Jwks) -> Result<TokenData<serde_json::Va
         .find(|jwk| jwk.kid == kid)
         .ok_or_else(|| ServerError::JwtVerification("JWK is not well known".into()))?;
 
-    let decoding_key = DecodingKey::from_rsa_components(&jwk.n, &jwk.e)
-        .map_err(|e| ServerError::JwtVerification(format!("Failed to get decoding key: {}", e)))?;
+    let decoding_key = DecodingKey::from_rsa_components(&jwk.n, &jwk.e).map_err(|e| {
+        error!("Failed to get decoding key for kid {}: {}", kid, e);
+        ServerError::JwtVerification("Failed to get decoding key".into())
+    })?;
+
+    decode_claims(
+        token,
+        &decoding_key,
+        &CONFIG.auth0.audience,
+        &CONFIG.auth0.domain,
+        CONFIG.auth0.jwt_leeway_secs,
+    )
+}
 
+/// Validates signature, expiry, `nbf`, `iat`, audience and issuer. The error
+/// message names the failed check so clients can tell their 401s apart
+pub fn decode_claims(
+    token: &str,
+    decoding_key: &DecodingKey,
+    audience: &str,
+    issuer: &str,
+    leeway: u64,
+) -> Result<Claims, ServerError> {
     let mut validation = Validation::new(Algorithm::RS256);
-    validation.set_audience(&[&CONFIG.auth0.audience]);
-    validation.set_issuer(&[&CONFIG.auth0.domain]);
+    validation.leeway = leeway;
+    validation.validate_nbf = true;
+    validation.set_audience(&[audience]);
+    validation.set_issuer(&[issuer]);
+
+    let claims = decode::<Claims>(token, decoding_key, &validation)
+        .map_err(|e| {
+            let reason = match e.kind() {
+                ErrorKind::ExpiredSignature => "Token has expired",
+                ErrorKind::ImmatureSignature => "Token is not valid yet",
+                ErrorKind::InvalidAudience => "Invalid token audience",
+                ErrorKind::InvalidIssuer => "Invalid token issuer",
+                ErrorKind::InvalidSignature => "Invalid token signature",
+                ErrorKind::InvalidAlgorithm => "Invalid token algorithm",
+                ErrorKind::MissingRequiredClaim(_) => "Token is missing a required claim",
+                ErrorKind::Json(_) => "Invalid token claims",
+                _ => "Malformed token",
+            };
+            ServerError::JwtVerification(reason.into())
+        })?
+        .claims;
+
+    if claims.issued_at() > (get_current_timestamp() + leeway) as i64 {
+        return Err(ServerError::JwtVerification(
+            "Token is issued in the future".into(),
+        ));
+    }
 
-    decode::<serde_json::Value>(token, &decoding_key, &validation)
-        .map_err(|e| ServerError::JwtVerification(format!("Failed to validate token: {}", e)))
+    Ok(claims)
 }
@@ -0,0 +1,58 @@
+use std::sync::Arc;
+
+use axum::{Extension, Json, extract::State, response::IntoResponse};
+use reqwest::StatusCode;
+use serde_json::json;
+use tracing::{error, info, instrument};
+use utoipa::OpenApi;
+
+use crate::models::{
+    app_state::AppState,
+    error::ServerError,
+    integration::IntegrationName,
+    system_log::{LogAction, LogCeverity},
+    user::SubjectId,
+};
+
+#[derive(OpenApi)]
+#[openapi(paths(billing_webhook))]
+pub struct BillingApi;
+
+#[utoipa::path(
+    post,
+    path = "/webhook",
+    tag = "webhooks",
+    security(("stripe_webhook_key" = [])),
+    request_body(content = Object, description = "Stripe event"),
+    responses((status = 200, description = "The event was received"))
+)]
+#[instrument(skip_all)]
+pub async fn billing_webhook(
+    State(state): State<Arc<AppState>>,
+    Extension(subject_id): Extension<SubjectId>,
+    Json(event): Json<serde_json::Value>,
+) -> Result<impl IntoResponse, ServerError> {
+    if !matches!(&subject_id, SubjectId::Integration(name) if *name == IntegrationName::STRIPE) {
+        error!("Subject {:?} tried calling the billing webhook", subject_id);
+        return Err(ServerError::AccessDenied);
+    }
+
+    let event_type = event
+        .get("type")
+        .and_then(|t| t.as_str())
+        .unwrap_or("unknown")
+        .to_string();
+
+    info!("Recieved billing event: {}", event_type);
+
+    state
+        .syslog("billing_webhook")
+        .subject(subject_id)
+        .action(LogAction::Other)
+        .ceverity(LogCeverity::Info)
+        .description("Recieved billing event")
+        .metadata(json!({"event_type": event_type, "event_id": event.get("id")}))
+        .log_async();
+
+    Ok(StatusCode::OK)
+}
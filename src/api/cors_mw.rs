@@ -0,0 +1,44 @@
+use std::time::Duration;
+
+use axum::http::{
+    HeaderName, HeaderValue, Method,
+    header::{AUTHORIZATION, CONTENT_TYPE},
+};
+use tower_http::cors::{AllowOrigin, CorsLayer};
+use tracing::warn;
+
+use crate::config::config::CorsConfig;
+
+const GUEST_AUTHORIZATION: HeaderName = HeaderName::from_static("x-guest-authentication");
+const GUEST_TOKEN: HeaderName = HeaderName::from_static("x-guest-token");
+const REQUEST_ID: HeaderName = HeaderName::from_static("x-request-id");
+
+/// Answers browser preflights for the configured origins, requests from
+/// other origins get no CORS headers and are blocked by the browser
+pub fn cors_layer(config: &CorsConfig) -> CorsLayer {
+    let origins: Vec<HeaderValue> = config
+        .allowed_origins
+        .iter()
+        .filter_map(|origin| match HeaderValue::from_str(origin) {
+            Ok(value) => Some(value),
+            Err(_) => {
+                warn!("Skipping invalid CORS origin: {}", origin);
+                None
+            }
+        })
+        .collect();
+
+    CorsLayer::new()
+        .allow_origin(AllowOrigin::list(origins))
+        .allow_methods([
+            Method::GET,
+            Method::POST,
+            Method::PUT,
+            Method::PATCH,
+            Method::DELETE,
+        ])
+        .allow_headers([AUTHORIZATION, CONTENT_TYPE, GUEST_AUTHORIZATION])
+        .expose_headers([REQUEST_ID, GUEST_TOKEN])
+        .allow_credentials(config.allow_credentials)
+        .max_age(Duration::from_secs(3600))
+}
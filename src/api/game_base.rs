@@ -1,40 +1,61 @@
-use std::sync::Arc;
+use std::{collections::HashSet, sync::Arc};
 
 use axum::{
-    Extension, Json, Router,
-    extract::{Path, Query, State},
+    Json, Router,
+    extract::{DefaultBodyLimit, Path, Query, State},
     response::IntoResponse,
     routing::{delete, get, patch, post},
 };
 use reqwest::StatusCode;
+use serde_json::json;
+use sqlx::{Pool, Postgres};
 use uuid::Uuid;
 
-use tracing::{debug, error};
+use tracing::{debug, instrument};
+use utoipa::OpenApi;
 
 use crate::{
-    client::gs_client::InteractiveGameResponse,
+    api::integration_guard_mw::ensure_integration,
+    client::gs_client::GameSessionDescriptor,
     config::config::CONFIG,
     db::{
         self,
         game_base::{
-            delete_saved_game, get_game_page, get_saved_games_page, increment_times_played,
-            save_game,
+            bulk_delete_saved_games, bulk_save_games, delete_saved_game, game_base_exists,
+            get_game_by_id, get_game_page, get_saved_games_page, get_user_games_page,
+            is_game_saved, record_session_played, save_game,
         },
-        quiz_game::{get_quiz_session_by_id, tx_persist_quiz_session},
+        game_report::{create_game_report, get_reported_games_page, moderate_game},
+        pool::with_tx,
+        quiz_game::{get_quiz_session_by_id, tx_clone_quiz_game, tx_persist_quiz_session},
         spin_game::{get_spin_session_by_game_id, tx_persist_spin_session},
     },
     models::{
         app_state::AppState,
-        auth::Claims,
-        error::ServerError,
+        error::{ErrorBody, ServerError},
         game_base::{
-            CreateGameRequest, GameConverter, GamePageQuery, GameType, InteractiveEnvelope,
-            SavedGamesPageQuery, StandaloneEnvelope,
+            BulkSaveRequest, BulkSaveResponse, CreateGameRequest, ENVELOPE_SCHEMA_VERSION,
+            GameBase, GameConverter, GamePageItem, GamePageQuery, GameSession, GameStartResponse,
+            GameType, GameVisibility, InteractiveEnvelope, SavedGame, SavedGamesPageQuery,
+            UserGamesPageQuery, Validate,
         },
+        game_category::{Category, CategoryQuery, CategorySlug},
+        game_report::{
+            CreateReportRequest, ModerateRequest, ReportOutcome, ReportedGame, ReportsPageQuery,
+        },
+        integration::IntegrationName,
+        popup_manager::PagedResponse,
         quiz_game::QuizSession,
+        request_context::RequestContext,
         spin_game::SpinSession,
+        system_log::{LogAction, LogCeverity},
         user::{Permission, SubjectId},
     },
+    service::{
+        audit::Audited,
+        idempotency_vault::IdempotencyVault,
+        key_vault::{KeyVault, parse_game_key},
+    },
 };
 
 ///
@@ -49,11 +70,21 @@ pub fn game_routes(state: Arc<AppState>) -> Router {
     let generic_routes = Router::new()
         .route("/page", post(get_games))
         .route("/{game_type}/create", post(create_interactive_game))
-        .route("/{game_type}/{game_id}", delete(delete_game))
+        .route("/{game_type}/{game_id}", get(get_game).delete(delete_game))
+        .route("/{game_type}/{game_id}/clone", post(clone_game))
         .route("/{game_type}/free-key/{key_word}", patch(free_game_key))
         .route("/save/{game_id}", post(user_save_game))
         .route("/unsave/{game_id}", delete(user_usaved_game))
-        .route("/saved", get(get_saved_games))
+        .route("/save/bulk", post(bulk_save))
+        .route("/unsave/bulk", delete(bulk_unsave))
+        .route("/saved", get(get_saved_games).post(post_saved_games))
+        .route("/saved/{base_id}/exists", get(get_game_saved))
+        .route("/mine", post(get_user_games))
+        .route("/report/{game_id}", post(report_game))
+        .route("/reports", get(get_reported_games))
+        .route("/moderate/{game_id}", patch(moderate_reported_game))
+        .route("/categories", get(get_categories).put(update_category))
+        .route("/categories/{slug}", delete(delete_category))
         .with_state(state.clone());
 
     let standalone_routes = Router::new()
@@ -61,11 +92,17 @@ pub fn game_routes(state: Arc<AppState>) -> Router {
             "/{game_type}/initiate/{game_id}",
             get(initiate_standalone_game),
         )
-        .route("/persist", post(persist_standalone_game))
+        .route(
+            "/persist",
+            post(persist_standalone_game).layer(envelope_body_limit()),
+        )
         .with_state(state.clone());
 
     let interactive_routes = Router::new()
-        .route("/persist", post(persist_interactive_game))
+        .route(
+            "/persist",
+            post(persist_interactive_game).layer(envelope_body_limit()),
+        )
         .route(
             "/{game_type}/initiate/{game_id}",
             post(initiate_interactive_game),
@@ -79,68 +116,269 @@ pub fn game_routes(state: Arc<AppState>) -> Router {
         .nest("/session", interactive_routes)
 }
 
-async fn delete_game(
+#[derive(OpenApi)]
+#[openapi(paths(
+    get_games,
+    create_interactive_game,
+    get_game,
+    delete_game,
+    clone_game,
+    free_game_key,
+    user_save_game,
+    user_usaved_game,
+    bulk_save,
+    bulk_unsave,
+    get_saved_games,
+    post_saved_games,
+    get_game_saved,
+    get_user_games,
+    report_game,
+    get_reported_games,
+    moderate_reported_game,
+    get_categories,
+    update_category,
+    delete_category
+))]
+struct GeneralGameApi;
+
+#[derive(OpenApi)]
+#[openapi(paths(initiate_standalone_game, persist_standalone_game))]
+struct StandaloneGameApi;
+
+#[derive(OpenApi)]
+#[openapi(paths(
+    persist_interactive_game,
+    initiate_interactive_game,
+    join_interactive_game
+))]
+struct InteractiveGameApi;
+
+#[derive(OpenApi)]
+#[openapi(nest(
+    (path = "/general", api = GeneralGameApi),
+    (path = "/static", api = StandaloneGameApi),
+    (path = "/session", api = InteractiveGameApi)
+))]
+pub struct GameApi;
+
+/// Finished sessions are sent whole, so the persist routes get a higher
+/// limit than the global `max_body_bytes`
+fn envelope_body_limit() -> DefaultBodyLimit {
+    DefaultBodyLimit::max(CONFIG.server.max_envelope_bytes)
+}
+
+/// Pages past this depth are rarely requested and are left to expire by TTL
+static INVALIDATED_PAGE_DEPTH: u16 = 10;
+
+/// Drops the cached game pages for a game type, for every category filter
+async fn invalidate_game_pages(state: &AppState, game_type: &GameType) {
+    let cache = state.get_cache();
+    let slugs = state.get_category_manager().slugs().await;
+    let categories = slugs.into_iter().map(Some).chain([None]);
+
+    for category in categories {
+        for page_num in 0..INVALIDATED_PAGE_DEPTH {
+            for trending in [false, true] {
+                cache.invalidate(&GamePageQuery {
+                    page_num,
+                    game_type: game_type.clone(),
+                    category: category.clone(),
+                    trending,
+                });
+            }
+        }
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = "/{game_type}/{game_id}",
+    tag = "games",
+    responses((status = 200, body = GameBase))
+)]
+#[instrument(skip_all)]
+async fn get_game(
     State(state): State<Arc<AppState>>,
-    Extension(subject_id): Extension<SubjectId>,
-    Extension(claims): Extension<Claims>,
+    ctx: RequestContext,
     Path((game_type, game_id)): Path<(GameType, Uuid)>,
 ) -> Result<impl IntoResponse, ServerError> {
-    if let SubjectId::Integration(_) | SubjectId::PseudoUser(_) = subject_id {
-        return Err(ServerError::AccessDenied);
-    }
+    ctx.require_user()?;
+
+    let is_admin = ctx.has_permission(Permission::ReadAdmin);
+    let game = require_accessible_game(
+        state.get_pool(),
+        &ctx.subject,
+        &game_type,
+        game_id,
+        is_admin,
+    )
+    .await?;
+
+    Ok((StatusCode::OK, Json(game)))
+}
+
+/// Loads a game of the given type the subject may see. Private games are
+/// reported as missing so their existence is not leaked
+async fn require_accessible_game(
+    pool: &Pool<Postgres>,
+    subject_id: &SubjectId,
+    game_type: &GameType,
+    game_id: Uuid,
+    is_admin: bool,
+) -> Result<GameBase, ServerError> {
+    let (game, owner) = get_game_by_id(pool, game_id).await?;
+
+    let accessible = match subject_id {
+        SubjectId::PseudoUser(id) | SubjectId::BaseUser(id) => {
+            game.visibility.is_accessible(owner, *id, is_admin)
+        }
+        SubjectId::Integration(_) => is_admin || game.visibility != GameVisibility::Private,
+    };
 
-    if let Some(missing) = claims.missing_permission([Permission::WriteAdmin]) {
-        return Err(ServerError::Permission(missing));
+    if game.game_type != *game_type || !accessible {
+        return Err(ServerError::NotFound(format!(
+            "Game with id {} does not exist",
+            game_id
+        )));
     }
 
-    db::game_base::delete_game(state.get_pool(), &game_type, game_id).await?;
+    Ok(game)
+}
+
+#[utoipa::path(
+    delete,
+    path = "/{game_type}/{game_id}",
+    tag = "games",
+    responses((status = 200))
+)]
+#[instrument(skip_all)]
+async fn delete_game(
+    State(state): State<Arc<AppState>>,
+    ctx: RequestContext,
+    Path((game_type, game_id)): Path<(GameType, Uuid)>,
+) -> Result<impl IntoResponse, ServerError> {
+    let user_id = ctx.require_base_user()?;
+
+    // Admins may delete any game, other users only the games they created
+    let owner = (!ctx.has_permission(Permission::WriteAdmin)).then_some(user_id);
+
+    db::game_base::delete_game(state.get_pool(), &game_type, game_id, owner)
+        .await
+        .audited(
+            state.get_pool(),
+            &ctx.subject,
+            LogAction::Delete,
+            "delete_game",
+            json!({ "game_id": game_id, "game_type": game_type }),
+        )?;
+    invalidate_game_pages(&state, &game_type).await;
     Ok(StatusCode::OK)
 }
 
-async fn join_interactive_game(
+#[utoipa::path(
+    post,
+    path = "/{game_type}/{game_id}/clone",
+    tag = "games",
+    responses((status = 201, description = "The cloned quiz", body = QuizSession))
+)]
+#[instrument(skip_all)]
+async fn clone_game(
     State(state): State<Arc<AppState>>,
-    Extension(subject_id): Extension<SubjectId>,
-    Path((game_type, key_word)): Path<(GameType, String)>,
+    ctx: RequestContext,
+    Path((game_type, game_id)): Path<(GameType, Uuid)>,
 ) -> Result<impl IntoResponse, ServerError> {
-    if let SubjectId::Integration(id) = subject_id {
-        error!("Integration {} tried accessing user endpoint", id);
-        return Err(ServerError::AccessDenied);
-    }
+    let user_id = ctx.require_base_user()?;
 
-    let words: Vec<&str> = key_word.split(" ").collect();
-    let tuple = match (words.get(0), words.get(1)) {
-        (Some(p), Some(s)) => (p.to_string(), s.to_string()),
+    let pool = state.get_pool();
+    let session = match game_type {
+        GameType::Quiz => {
+            let mut tx = pool.begin().await?;
+            let new_id = tx_clone_quiz_game(&mut tx, game_id, user_id).await?;
+            tx.commit().await?;
+            invalidate_game_pages(&state, &GameType::Quiz).await;
+            get_quiz_session_by_id(pool, &new_id).await?
+        }
         _ => {
             return Err(ServerError::Api(
                 StatusCode::BAD_REQUEST,
-                "Key word in invalid format".into(),
+                "This game does not have clone support".into(),
             ));
         }
     };
 
-    if !state.get_vault().key_active(&tuple) {
+    debug!(
+        "User {} cloned game {} into {}",
+        user_id, game_id, session.base_id
+    );
+    Ok((StatusCode::CREATED, Json(session)))
+}
+
+#[utoipa::path(
+    post,
+    path = "/{game_type}/join/{game_id}",
+    tag = "games",
+    params(
+        ("game_type" = GameType, Path),
+        ("game_id" = String, Path, description = "Game key as \"prefix suffix\"")
+    ),
+    responses((status = 200, body = GameSessionDescriptor))
+)]
+#[instrument(skip_all)]
+async fn join_interactive_game(
+    State(state): State<Arc<AppState>>,
+    ctx: RequestContext,
+    Path((game_type, key_word)): Path<(GameType, String)>,
+) -> Result<impl IntoResponse, ServerError> {
+    ctx.require_user()?;
+
+    let tuple = parse_game_key(&key_word)?;
+
+    let Some(created_at) = state.get_vault().key_created_at(&tuple) else {
         return Err(ServerError::Api(
             StatusCode::NOT_FOUND,
             "Game with game key does not exist".into(),
         ));
-    }
-
-    let hub_address = format!(
-        "{}hubs/{}",
-        CONFIG.server.gs_domain,
-        game_type.column_name()
-    );
-    let response = InteractiveGameResponse {
-        key_word,
-        hub_address,
     };
 
+    let response = GameSessionDescriptor::new(&game_type, key_word).created_at(created_at);
     Ok((StatusCode::OK, Json(response)))
 }
 
+/// Reserves a game key and hands it to `start`. The key is freed again when
+/// the session could not be started, so failed calls to tero-session do not
+/// leave keys reserved until the vault cleanup runs
+pub async fn start_with_game_key<F, Fut>(
+    vault: &KeyVault,
+    pool: &Pool<Postgres>,
+    start: F,
+) -> Result<String, ServerError>
+where
+    F: FnOnce(String) -> Fut,
+    Fut: Future<Output = Result<(), ServerError>>,
+{
+    let key_word = vault.create_key(pool)?;
+    if let Err(e) = start(key_word.clone()).await {
+        vault.release_key(&key_word);
+        return Err(e);
+    }
+
+    Ok(key_word)
+}
+
+#[utoipa::path(
+    post,
+    path = "/{game_type}/create",
+    tag = "games",
+    request_body = CreateGameRequest,
+    responses(
+        (status = 201, body = GameStartResponse),
+        (status = 422, body = ErrorBody)
+    )
+)]
+#[instrument(skip_all)]
 async fn create_interactive_game(
     State(state): State<Arc<AppState>>,
-    Extension(subject_id): Extension<SubjectId>,
+    ctx: RequestContext,
     Path(game_type): Path<GameType>,
     Json(request): Json<CreateGameRequest>,
 ) -> Result<impl IntoResponse, ServerError> {
@@ -149,18 +387,21 @@ async fn create_interactive_game(
         "Recieved request: {}",
         serde_json::to_string_pretty(&request).unwrap()
     );
-    let user_id = match subject_id {
-        SubjectId::PseudoUser(id) | SubjectId::BaseUser(id) => id,
-        _ => return Err(ServerError::AccessDenied),
-    };
+    let user_id = ctx.require_user()?;
+
+    request.validate()?;
+    if let Some(category) = &request.category {
+        state
+            .get_category_manager()
+            .require_active(category)
+            .await?;
+    }
 
     let client = state.get_client();
     let gs_client = state.get_gs_client();
     let vault = state.get_vault();
     let pool = state.get_pool();
 
-    let key_word = vault.create_key(pool)?;
-
     let payload = match game_type {
         GameType::Spin => {
             let session = SpinSession::from_create_request(user_id, request);
@@ -172,72 +413,98 @@ async fn create_interactive_game(
         }
     };
 
-    let envelope = InteractiveEnvelope {
-        game_type: game_type.clone(),
-        host_id: user_id,
-        game_key: key_word.clone(),
-        payload,
-    };
-
-    gs_client.create_interactive_game(client, &envelope).await?;
+    let key_word = start_with_game_key(vault, pool, |game_key| async {
+        let envelope = InteractiveEnvelope {
+            game_type: game_type.clone(),
+            host_id: user_id,
+            game_key,
+            schema_version: ENVELOPE_SCHEMA_VERSION,
+            payload,
+        };
 
-    let hub_address = format!(
-        "{}/hubs/{}",
-        CONFIG.server.gs_domain,
-        game_type.column_name()
-    );
+        gs_client.create_interactive_game(client, &envelope).await?;
+        Ok(())
+    })
+    .await?;
 
-    let response = InteractiveGameResponse {
-        key_word,
-        hub_address,
-    };
+    let response = GameStartResponse::Interactive(GameSessionDescriptor::new(&game_type, key_word));
 
     debug!("Interactive game was created");
     Ok((StatusCode::CREATED, Json(response)))
 }
 
+#[utoipa::path(
+    get,
+    path = "/{game_type}/initiate/{game_id}",
+    tag = "games",
+    responses((status = 200, body = GameStartResponse))
+)]
+#[instrument(skip_all)]
 async fn initiate_standalone_game(
     State(state): State<Arc<AppState>>,
-    Extension(_subject_id): Extension<SubjectId>,
+    ctx: RequestContext,
     Path((game_type, game_id)): Path<(GameType, Uuid)>,
 ) -> Result<impl IntoResponse, ServerError> {
+    ctx.require_user_or_permission(Permission::ReadGame)?;
+
+    let is_admin = ctx.has_permission(Permission::ReadAdmin);
+    let value =
+        standalone_session(state.get_pool(), &ctx.subject, game_type, game_id, is_admin).await?;
+    let response = GameStartResponse::Standalone { session: value };
+    Ok((StatusCode::OK, Json(response)))
+}
+
+/// Loads the session a client plays on its own. Spin sessions are hosted by
+/// the requesting user
+pub async fn standalone_session(
+    pool: &Pool<Postgres>,
+    subject_id: &SubjectId,
+    game_type: GameType,
+    game_id: Uuid,
+    is_admin: bool,
+) -> Result<serde_json::Value, ServerError> {
+    require_accessible_game(pool, subject_id, &game_type, game_id, is_admin).await?;
+
     let value = match game_type {
         GameType::Quiz => {
-            let session = get_quiz_session_by_id(state.get_pool(), &game_id).await?;
+            let session = get_quiz_session_by_id(pool, &game_id).await?;
             session.to_json_value()?
         }
-        _ => {
-            return Err(ServerError::Api(
-                StatusCode::BAD_REQUEST,
-                "This game does not have static support".into(),
-            ));
-        }
-    };
+        GameType::Spin => {
+            let user_id = match subject_id {
+                SubjectId::PseudoUser(id) | SubjectId::BaseUser(id) => *id,
+                SubjectId::Integration(_) => return Err(ServerError::AccessDenied),
+            };
 
-    let envelope = StandaloneEnvelope {
-        game_type: game_type,
-        payload: value,
+            let session = get_spin_session_by_game_id(pool, user_id, game_id).await?;
+            session.to_json_value()?
+        }
     };
 
-    return Ok((StatusCode::OK, Json(envelope)));
+    Ok(value)
 }
 
+#[utoipa::path(
+    post,
+    path = "/{game_type}/initiate/{game_id}",
+    tag = "games",
+    responses((status = 200, body = GameStartResponse))
+)]
+#[instrument(skip_all)]
 async fn initiate_interactive_game(
     State(state): State<Arc<AppState>>,
-    Extension(subject_id): Extension<SubjectId>,
+    ctx: RequestContext,
     Path((game_type, game_id)): Path<(GameType, Uuid)>,
 ) -> Result<impl IntoResponse, ServerError> {
-    let user_id = match subject_id {
-        SubjectId::PseudoUser(id) | SubjectId::BaseUser(id) => id,
-        _ => return Err(ServerError::AccessDenied),
-    };
+    let user_id = ctx.require_user()?;
 
     let client = state.get_client();
     let gs_client = state.get_gs_client();
     let vault = state.get_vault();
     let pool = state.get_pool();
 
-    let key_word = vault.create_key(pool)?;
+    let is_admin = ctx.has_permission(Permission::ReadAdmin);
+    require_accessible_game(pool, &ctx.subject, &game_type, game_id, is_admin).await?;
 
     let payload = match game_type {
         GameType::Spin => {
@@ -252,39 +519,46 @@ async fn initiate_interactive_game(
         }
     };
 
-    let envelope = InteractiveEnvelope {
-        game_type: game_type.clone(),
-        host_id: user_id,
-        game_key: key_word.clone(),
-        payload,
-    };
-
-    gs_client.initiate_game_session(client, &envelope).await?;
+    let key_word = start_with_game_key(vault, pool, |game_key| async {
+        let envelope = InteractiveEnvelope {
+            game_type: game_type.clone(),
+            host_id: user_id,
+            game_key,
+            schema_version: ENVELOPE_SCHEMA_VERSION,
+            payload,
+        };
 
-    let hub_address = format!(
-        "{}/hubs/{}",
-        CONFIG.server.gs_domain,
-        game_type.column_name()
-    );
+        gs_client.initiate_game_session(client, &envelope).await?;
+        Ok(())
+    })
+    .await?;
 
-    let response = InteractiveGameResponse {
-        key_word,
-        hub_address,
-    };
+    let response = GameStartResponse::Interactive(GameSessionDescriptor::new(&game_type, key_word));
 
     Ok((StatusCode::OK, Json(response)))
 }
 
+#[utoipa::path(
+    post,
+    path = "/page",
+    tag = "games",
+    request_body = GamePageQuery,
+    responses((status = 200, body = PagedResponse<GamePageItem>))
+)]
+#[instrument(skip_all)]
 async fn get_games(
     State(state): State<Arc<AppState>>,
-    Extension(subject_id): Extension<SubjectId>,
+    ctx: RequestContext,
     Json(request): Json<GamePageQuery>,
 ) -> Result<impl IntoResponse, ServerError> {
-    if let SubjectId::Integration(_) = subject_id {
-        return Err(ServerError::AccessDenied);
+    ctx.require_user_or_permission(Permission::ReadGame)?;
+
+    // Unknown slugs would otherwise fill the page cache with empty pages
+    if let Some(category) = &request.category {
+        state.get_category_manager().require_known(category).await?;
     }
 
-    let pool = state.get_pool();
+    let pool = state.get_read_pool();
     let cache = state.get_cache();
 
     let page = cache
@@ -294,160 +568,690 @@ async fn get_games(
     Ok((StatusCode::OK, Json(page)))
 }
 
+#[utoipa::path(
+    post,
+    path = "/persist",
+    tag = "games",
+    request_body = InteractiveEnvelope,
+    responses(
+        (status = 201),
+        (status = 422, body = ErrorBody)
+    )
+)]
+#[instrument(skip_all)]
 pub async fn persist_standalone_game(
     State(state): State<Arc<AppState>>,
-    Extension(subject_id): Extension<SubjectId>,
+    ctx: RequestContext,
     Json(request): Json<InteractiveEnvelope>,
 ) -> Result<impl IntoResponse, ServerError> {
-    if let SubjectId::Integration(id) = subject_id {
-        error!("Integration {} tried to store a static game", id);
-        return Err(ServerError::AccessDenied);
-    }
+    let user_id = ctx.require_user()?;
+    let details = json!({
+        "game_id": request.payload.get("base_id"),
+        "game_type": request.game_type,
+    });
 
-    match request.game_type {
-        GameType::Quiz => {
-            let session: QuizSession = serde_json::from_value(request.payload)?;
-            let mut tx = state.get_pool().begin().await?;
-            tx_persist_quiz_session(&mut tx, &session).await?;
-            tx.commit().await?;
-        }
-        _ => {
-            return Err(ServerError::Api(
-                StatusCode::BAD_REQUEST,
-                "This game does not have static persist support".into(),
-            ));
-        }
-    }
+    let game_type = persist_standalone_session(state.get_pool(), request, user_id)
+        .await
+        .audited(
+            state.get_pool(),
+            &ctx.subject,
+            LogAction::Create,
+            "persist_standalone_game",
+            details,
+        )?;
+    invalidate_game_pages(&state, &game_type).await;
 
     Ok(StatusCode::CREATED)
 }
 
+/// Stores a session played without tero-session, owned by `user_id`
+pub async fn persist_standalone_session(
+    pool: &Pool<Postgres>,
+    request: InteractiveEnvelope,
+    user_id: Uuid,
+) -> Result<GameType, ServerError> {
+    let session = request.try_into_session()?;
+    with_tx(pool, |tx| {
+        Box::pin(async move {
+            match session {
+                GameSession::Spin(session) => {
+                    tx_persist_spin_session(tx, &session, user_id).await?;
+                    Ok(GameType::Spin)
+                }
+                GameSession::Quiz(session) => {
+                    tx_persist_quiz_session(tx, &session, user_id).await?;
+                    Ok(GameType::Quiz)
+                }
+            }
+        })
+    })
+    .await
+}
+
+#[utoipa::path(
+    post,
+    path = "/persist",
+    tag = "games",
+    request_body = InteractiveEnvelope,
+    responses(
+        (status = 201),
+        (status = 422, body = ErrorBody)
+    )
+)]
+#[instrument(skip_all)]
 async fn persist_interactive_game(
     State(state): State<Arc<AppState>>,
-    Extension(subject_id): Extension<SubjectId>,
-    Extension(claims): Extension<Claims>,
+    ctx: RequestContext,
     Json(request): Json<InteractiveEnvelope>,
 ) -> Result<impl IntoResponse, ServerError> {
-    let SubjectId::Integration(_) = subject_id else {
-        error!("User tried to persist game session");
-        return Err(ServerError::AccessDenied);
-    };
+    ensure_integration(
+        state.get_pool(),
+        &ctx.subject,
+        &[IntegrationName::SESSION],
+        "persist_interactive_game",
+    )?;
 
-    if let Some(missing) = claims.missing_permission([Permission::WriteGame]) {
-        return Err(ServerError::Permission(missing));
-    }
+    ctx.require_permission(Permission::WriteGame)?;
 
-    let words: Vec<&str> = request.game_key.split(" ").collect();
-    let tuple = match (words.get(0), words.get(1)) {
-        (Some(prefix), Some(suffix)) => (prefix.to_string(), suffix.to_string()),
-        _ => {
-            return Err(ServerError::Api(
-                StatusCode::BAD_REQUEST,
-                "Key word in invalid format".into(),
-            ));
-        }
-    };
+    let tuple = parse_game_key(&request.game_key)?;
 
     state.get_vault().remove_key(tuple);
-    let pool = state.get_pool();
 
-    match request.game_type {
-        GameType::Spin => {
-            let session: SpinSession = serde_json::from_value(request.payload)?;
-            match session.times_played {
-                0 => {
-                    let mut tx = pool.begin().await?;
-                    tx_persist_spin_session(&mut tx, &session).await?;
-                    tx.commit().await?;
+    let details = json!({
+        "game_key": request.game_key,
+        "game_id": request.payload.get("base_id"),
+        "game_type": request.game_type,
+    });
+
+    let vault = state.get_idempotency_vault();
+    let persisted = persist_session_envelope(state.get_pool(), vault, request)
+        .await
+        .audited(
+            state.get_pool(),
+            &ctx.subject,
+            LogAction::Create,
+            "persist_interactive_game",
+            details,
+        )?;
+
+    if let Some(game_type) = persisted {
+        invalidate_game_pages(&state, &game_type).await;
+    }
+
+    Ok(StatusCode::CREATED)
+}
+
+/// Stores a finished session. tero-session retries the call when it does not
+/// get a timely response, so replays of the same envelope are no-ops. Returns
+/// the game type when a new game was stored
+pub async fn persist_session_envelope(
+    pool: &Pool<Postgres>,
+    vault: &IdempotencyVault,
+    request: InteractiveEnvelope,
+) -> Result<Option<GameType>, ServerError> {
+    let (game_key, host_id) = (request.game_key.clone(), request.host_id);
+    match request.try_into_session()? {
+        GameSession::Spin(session) => {
+            let (base_id, times_played) = (session.base_id, session.times_played);
+            let rounds_played = session.iterations;
+            let key = format!("persist:{}:{}", game_key, base_id);
+            run_once(vault, &key, async {
+                if !game_base_exists(pool, base_id).await? {
+                    let created = with_tx(pool, move |tx| {
+                        Box::pin(
+                            async move { tx_persist_spin_session(tx, &session, host_id).await },
+                        )
+                    })
+                    .await?;
+
+                    // Not created means a concurrent call stored it first
+                    if created {
+                        return Ok(Some(GameType::Spin));
+                    }
                 }
-                _ => increment_times_played(pool, GameType::Spin, session.base_id).await?,
-            }
+
+                // A fresh session that is already stored is a replay
+                if times_played > 0 {
+                    record_session_played(
+                        pool,
+                        GameType::Spin,
+                        base_id,
+                        Some(host_id),
+                        rounds_played,
+                    )
+                    .await?;
+                }
+
+                Ok(None)
+            })
+            .await
         }
-        GameType::Quiz => {
-            let session: QuizSession = serde_json::from_value(request.payload)?;
-            match session.times_played {
-                0 => {
-                    let mut tx = pool.begin().await?;
-                    tx_persist_quiz_session(&mut tx, &session).await?;
-                    tx.commit().await?;
+        GameSession::Quiz(session) => {
+            let (base_id, times_played) = (session.base_id, session.times_played);
+            let rounds_played = session.iterations;
+            let key = format!("persist:{}:{}", game_key, base_id);
+            run_once(vault, &key, async {
+                if !game_base_exists(pool, base_id).await? {
+                    let created = with_tx(pool, move |tx| {
+                        Box::pin(
+                            async move { tx_persist_quiz_session(tx, &session, host_id).await },
+                        )
+                    })
+                    .await?;
+
+                    // Not created means a concurrent call stored it first
+                    if created {
+                        return Ok(Some(GameType::Quiz));
+                    }
                 }
-                _ => increment_times_played(pool, GameType::Quiz, session.base_id).await?,
-            }
-            increment_times_played(pool, GameType::Quiz, session.quiz_id).await?;
+
+                // A fresh session that is already stored is a replay
+                if times_played > 0 {
+                    record_session_played(
+                        pool,
+                        GameType::Quiz,
+                        base_id,
+                        Some(host_id),
+                        rounds_played,
+                    )
+                    .await?;
+                }
+
+                Ok(None)
+            })
+            .await
         }
     }
+}
+
+/// Skips `persist` if the key was seen within the vault ttl, the key is
+/// released again when `persist` fails so the retry can go through
+async fn run_once<F>(
+    vault: &IdempotencyVault,
+    key: &str,
+    persist: F,
+) -> Result<Option<GameType>, ServerError>
+where
+    F: Future<Output = Result<Option<GameType>, ServerError>>,
+{
+    if vault.check_and_insert(key) {
+        debug!("Skipped replayed persist: {}", key);
+        return Ok(None);
+    }
+
+    let result = persist.await;
+    if result.is_err() {
+        vault.remove(key);
+    }
 
-    return Ok(StatusCode::CREATED);
+    result
 }
 
+#[utoipa::path(
+    patch,
+    path = "/{game_type}/free-key/{key_word}",
+    tag = "games",
+    params(
+        ("game_type" = GameType, Path),
+        ("key_word" = String, Path, description = "Game key as \"prefix suffix\"")
+    ),
+    responses((status = 200))
+)]
+#[instrument(skip_all)]
 async fn free_game_key(
     State(state): State<Arc<AppState>>,
-    Extension(subject_id): Extension<SubjectId>,
-    Extension(claims): Extension<Claims>,
+    ctx: RequestContext,
     Path(key_word): Path<String>,
 ) -> Result<impl IntoResponse, ServerError> {
-    let SubjectId::Integration(_) = subject_id else {
-        error!("User tried to free game keys/word");
-        return Err(ServerError::AccessDenied);
-    };
+    ensure_integration(
+        state.get_pool(),
+        &ctx.subject,
+        &[IntegrationName::SESSION],
+        "free_game_key",
+    )?;
 
-    if let Some(missing) = claims.missing_permission([Permission::WriteGame]) {
-        return Err(ServerError::Permission(missing));
-    }
+    ctx.require_permission(Permission::WriteGame)?;
 
-    let words: Vec<&str> = key_word.split(" ").collect();
-    let tuple = match (words.get(0), words.get(1)) {
-        (Some(prefix), Some(suffix)) => (prefix.to_string(), suffix.to_string()),
-        _ => {
-            return Err(ServerError::Api(
-                StatusCode::BAD_REQUEST,
-                "Key word in invalid format".into(),
-            ));
-        }
-    };
+    let tuple = parse_game_key(&key_word)?;
 
     state.get_vault().remove_key(tuple);
     Ok(StatusCode::OK)
 }
 
+#[utoipa::path(
+    post,
+    path = "/save/{game_id}",
+    tag = "saved games",
+    responses((status = 201))
+)]
+#[instrument(skip_all)]
 async fn user_save_game(
     State(state): State<Arc<AppState>>,
-    Extension(subject_id): Extension<SubjectId>,
+    ctx: RequestContext,
     Path(game_id): Path<Uuid>,
 ) -> Result<impl IntoResponse, ServerError> {
-    let SubjectId::BaseUser(user_id) = subject_id else {
-        error!("Unregistered user or integration tried saving a game");
-        return Err(ServerError::AccessDenied);
-    };
+    let user_id = ctx.require_base_user()?;
+
+    let (game, owner) = get_game_by_id(state.get_pool(), game_id).await?;
+    if !game.visibility.is_accessible(owner, user_id, false) {
+        return Err(ServerError::NotFound(format!(
+            "Game with id {} does not exist",
+            game_id
+        )));
+    }
 
-    save_game(state.get_pool(), user_id, game_id).await?;
+    save_game(state.get_pool(), user_id, game_id)
+        .await
+        .audited(
+            state.get_pool(),
+            &ctx.subject,
+            LogAction::Create,
+            "user_save_game",
+            json!({ "game_id": game_id }),
+        )?;
+    state.get_saved_cache().invalidate(&(user_id, game_id));
     Ok(StatusCode::CREATED)
 }
 
+#[utoipa::path(
+    delete,
+    path = "/unsave/{game_id}",
+    tag = "saved games",
+    responses((status = 204))
+)]
+#[instrument(skip_all)]
 async fn user_usaved_game(
     State(state): State<Arc<AppState>>,
-    Extension(subject_id): Extension<SubjectId>,
+    ctx: RequestContext,
     Path(game_id): Path<Uuid>,
 ) -> Result<impl IntoResponse, ServerError> {
-    let SubjectId::BaseUser(user_id) = subject_id else {
-        error!("Unregistered user or integration tried saving a game");
-        return Err(ServerError::AccessDenied);
-    };
+    let user_id = ctx.require_base_user()?;
 
-    delete_saved_game(state.get_pool(), user_id, game_id).await?;
+    delete_saved_game(state.get_pool(), user_id, game_id)
+        .await
+        .audited(
+            state.get_pool(),
+            &ctx.subject,
+            LogAction::Delete,
+            "delete_saved_game",
+            json!({ "game_id": game_id }),
+        )?;
+    state.get_saved_cache().invalidate(&(user_id, game_id));
     Ok(StatusCode::NO_CONTENT)
 }
 
+/// Distinct ids in request order, duplicates would count as skipped otherwise
+fn distinct_ids(base_ids: Vec<Uuid>) -> Vec<Uuid> {
+    let mut seen = HashSet::new();
+    base_ids.into_iter().filter(|id| seen.insert(*id)).collect()
+}
+
+#[utoipa::path(
+    post,
+    path = "/save/bulk",
+    tag = "saved games",
+    request_body = BulkSaveRequest,
+    responses(
+        (status = 200, body = BulkSaveResponse),
+        (status = 422, body = ErrorBody)
+    )
+)]
+#[instrument(skip_all)]
+async fn bulk_save(
+    State(state): State<Arc<AppState>>,
+    ctx: RequestContext,
+    Json(request): Json<BulkSaveRequest>,
+) -> Result<impl IntoResponse, ServerError> {
+    let user_id = ctx.require_base_user()?;
+
+    request.validate()?;
+    let base_ids = distinct_ids(request.base_ids);
+
+    let saved = bulk_save_games(state.get_pool(), user_id, &base_ids)
+        .await
+        .audited(
+            state.get_pool(),
+            &ctx.subject,
+            LogAction::Create,
+            "bulk_save",
+            json!({ "base_ids": base_ids }),
+        )?;
+
+    for base_id in &base_ids {
+        state.get_saved_cache().invalidate(&(user_id, *base_id));
+    }
+
+    let response = BulkSaveResponse {
+        saved,
+        skipped: base_ids.len() as u64 - saved,
+    };
+    Ok((StatusCode::OK, Json(response)))
+}
+
+#[utoipa::path(
+    delete,
+    path = "/unsave/bulk",
+    tag = "saved games",
+    request_body = BulkSaveRequest,
+    responses(
+        (status = 200, body = BulkSaveResponse),
+        (status = 422, body = ErrorBody)
+    )
+)]
+#[instrument(skip_all)]
+async fn bulk_unsave(
+    State(state): State<Arc<AppState>>,
+    ctx: RequestContext,
+    Json(request): Json<BulkSaveRequest>,
+) -> Result<impl IntoResponse, ServerError> {
+    let user_id = ctx.require_base_user()?;
+
+    request.validate()?;
+    let base_ids = distinct_ids(request.base_ids);
+
+    let removed = bulk_delete_saved_games(state.get_pool(), user_id, &base_ids)
+        .await
+        .audited(
+            state.get_pool(),
+            &ctx.subject,
+            LogAction::Delete,
+            "bulk_unsave",
+            json!({ "base_ids": base_ids }),
+        )?;
+
+    for base_id in &base_ids {
+        state.get_saved_cache().invalidate(&(user_id, *base_id));
+    }
+
+    let response = BulkSaveResponse {
+        saved: removed,
+        skipped: base_ids.len() as u64 - removed,
+    };
+    Ok((StatusCode::OK, Json(response)))
+}
+
+#[utoipa::path(
+    get,
+    path = "/saved/{base_id}/exists",
+    tag = "saved games",
+    responses((status = 200, description = "`{\"saved\": bool}`", body = Object))
+)]
+#[instrument(skip_all)]
+async fn get_game_saved(
+    State(state): State<Arc<AppState>>,
+    ctx: RequestContext,
+    Path(base_id): Path<Uuid>,
+) -> Result<impl IntoResponse, ServerError> {
+    let user_id = ctx.require_base_user()?;
+
+    let pool = state.get_pool();
+    let saved = state
+        .get_saved_cache()
+        .get_or(&(user_id, base_id), || {
+            is_game_saved(pool, user_id, base_id)
+        })
+        .await?;
+
+    Ok((StatusCode::OK, Json(json!({ "saved": saved }))))
+}
+
+#[utoipa::path(
+    get,
+    path = "/saved",
+    tag = "saved games",
+    params(SavedGamesPageQuery),
+    responses((status = 200, body = PagedResponse<SavedGame>))
+)]
+#[instrument(skip_all)]
 async fn get_saved_games(
     State(state): State<Arc<AppState>>,
-    Extension(subject_id): Extension<SubjectId>,
+    ctx: RequestContext,
     Query(query): Query<SavedGamesPageQuery>,
 ) -> Result<impl IntoResponse, ServerError> {
-    let SubjectId::BaseUser(user_id) = subject_id else {
-        error!("Unregistered user or integration tried saving a game");
-        return Err(ServerError::AccessDenied);
-    };
+    saved_games_page(&state, &ctx, query).await
+}
+
+/// Same as `get_saved_games` with the query as a JSON body, like the other
+/// page endpoints
+#[utoipa::path(
+    post,
+    path = "/saved",
+    tag = "saved games",
+    request_body = SavedGamesPageQuery,
+    responses((status = 200, body = PagedResponse<SavedGame>))
+)]
+#[instrument(skip_all)]
+async fn post_saved_games(
+    State(state): State<Arc<AppState>>,
+    ctx: RequestContext,
+    Json(query): Json<SavedGamesPageQuery>,
+) -> Result<impl IntoResponse, ServerError> {
+    saved_games_page(&state, &ctx, query).await
+}
+
+async fn saved_games_page(
+    state: &AppState,
+    ctx: &RequestContext,
+    query: SavedGamesPageQuery,
+) -> Result<impl IntoResponse + use<>, ServerError> {
+    let user_id = ctx.require_base_user()?;
+
+    let page = get_saved_games_page(state.get_read_pool(), user_id, query).await?;
+    Ok((StatusCode::OK, Json(page)))
+}
+
+#[utoipa::path(
+    post,
+    path = "/mine",
+    tag = "games",
+    request_body = UserGamesPageQuery,
+    responses((status = 200, body = PagedResponse<GameBase>))
+)]
+#[instrument(skip_all)]
+async fn get_user_games(
+    State(state): State<Arc<AppState>>,
+    ctx: RequestContext,
+    Json(query): Json<UserGamesPageQuery>,
+) -> Result<impl IntoResponse, ServerError> {
+    let user_id = ctx.require_user()?;
+
+    let page = get_user_games_page(state.get_pool(), user_id, &query).await?;
+    Ok((StatusCode::OK, Json(page)))
+}
+
+#[utoipa::path(
+    post,
+    path = "/report/{game_id}",
+    tag = "moderation",
+    request_body = CreateReportRequest,
+    responses(
+        (status = 200, description = "The caller already reported this game"),
+        (status = 201, description = "The report was stored"),
+        (status = 422, body = ErrorBody)
+    )
+)]
+#[instrument(skip_all)]
+async fn report_game(
+    State(state): State<Arc<AppState>>,
+    ctx: RequestContext,
+    Path(game_id): Path<Uuid>,
+    Json(request): Json<CreateReportRequest>,
+) -> Result<impl IntoResponse, ServerError> {
+    let reporter_id = ctx.require_user()?;
+
+    request.validate()?;
+
+    let outcome = create_game_report(state.get_pool(), game_id, reporter_id, &request).await?;
+    match outcome {
+        ReportOutcome::Duplicate => Ok(StatusCode::OK),
+        ReportOutcome::Created => Ok(StatusCode::CREATED),
+        ReportOutcome::Hidden(game_type) => {
+            invalidate_game_pages(&state, &game_type).await;
+            state
+                .syslog("report_game")
+                .context(&ctx)
+                .action(LogAction::Update)
+                .ceverity(LogCeverity::Warning)
+                .description("Game was hidden after crossing the report threshold")
+                .metadata(json!({"game_id": game_id, "reason": request.reason}))
+                .log_async();
+
+            Ok(StatusCode::CREATED)
+        }
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = "/reports",
+    tag = "moderation",
+    params(ReportsPageQuery),
+    responses((status = 200, body = PagedResponse<ReportedGame>))
+)]
+#[instrument(skip_all)]
+async fn get_reported_games(
+    State(state): State<Arc<AppState>>,
+    ctx: RequestContext,
+    Query(query): Query<ReportsPageQuery>,
+) -> Result<impl IntoResponse, ServerError> {
+    ctx.require_base_user()?;
+
+    ctx.require_permission(Permission::ReadAdmin)?;
+
+    let page = get_reported_games_page(state.get_pool(), &query).await?;
+
+    state
+        .syslog("get_reported_games")
+        .context(&ctx)
+        .action(LogAction::Read)
+        .ceverity(LogCeverity::Info)
+        .description("Reviewed reported games")
+        .metadata(json!({"page_num": query.page_num}))
+        .log_async();
 
-    let page = get_saved_games_page(state.get_pool(), user_id, query).await?;
     Ok((StatusCode::OK, Json(page)))
 }
+
+#[utoipa::path(
+    patch,
+    path = "/moderate/{game_id}",
+    tag = "moderation",
+    request_body = ModerateRequest,
+    responses((status = 200))
+)]
+#[instrument(skip_all)]
+async fn moderate_reported_game(
+    State(state): State<Arc<AppState>>,
+    ctx: RequestContext,
+    Path(game_id): Path<Uuid>,
+    Json(request): Json<ModerateRequest>,
+) -> Result<impl IntoResponse, ServerError> {
+    ctx.require_base_user()?;
+
+    ctx.require_permission(Permission::WriteAdmin)?;
+
+    let (game_type, resolved) = moderate_game(state.get_pool(), game_id, request.action).await?;
+    invalidate_game_pages(&state, &game_type).await;
+
+    state
+        .syslog("moderate_reported_game")
+        .context(&ctx)
+        .action(LogAction::Update)
+        .ceverity(LogCeverity::Info)
+        .description("Moderated reported game")
+        .metadata(
+            json!({"game_id": game_id, "action": request.action, "resolved_reports": resolved}),
+        )
+        .log_async();
+
+    Ok(StatusCode::OK)
+}
+
+#[utoipa::path(
+    get,
+    path = "/categories",
+    tag = "categories",
+    params(CategoryQuery),
+    responses((status = 200, body = [Category]))
+)]
+#[instrument(skip_all)]
+async fn get_categories(
+    State(state): State<Arc<AppState>>,
+    ctx: RequestContext,
+    Query(query): Query<CategoryQuery>,
+) -> Result<impl IntoResponse, ServerError> {
+    if query.include_inactive {
+        ctx.require_permission(Permission::ReadAdmin)?;
+    }
+
+    let categories = state
+        .get_category_manager()
+        .list(query.include_inactive)
+        .await;
+
+    Ok((StatusCode::OK, Json(categories)))
+}
+
+#[utoipa::path(
+    put,
+    path = "/categories",
+    tag = "categories",
+    request_body = Category,
+    responses(
+        (status = 200, body = Category),
+        (status = 422, body = ErrorBody)
+    )
+)]
+#[instrument(skip_all)]
+async fn update_category(
+    State(state): State<Arc<AppState>>,
+    ctx: RequestContext,
+    Json(request): Json<Category>,
+) -> Result<impl IntoResponse, ServerError> {
+    ctx.require_base_user()?;
+    ctx.require_permission(Permission::WriteAdmin)?;
+    request.validate()?;
+
+    let category = state.get_category_manager().update(request).await?;
+
+    state
+        .syslog("update_category")
+        .context(&ctx)
+        .action(LogAction::Update)
+        .ceverity(LogCeverity::Info)
+        .description("Created or updated a game category")
+        .metadata(json!({"slug": category.slug, "active": category.active}))
+        .log_async();
+
+    Ok((StatusCode::OK, Json(category)))
+}
+
+#[utoipa::path(
+    delete,
+    path = "/categories/{slug}",
+    tag = "categories",
+    params(("slug" = String, Path)),
+    responses((status = 204))
+)]
+#[instrument(skip_all)]
+async fn delete_category(
+    State(state): State<Arc<AppState>>,
+    ctx: RequestContext,
+    Path(slug): Path<CategorySlug>,
+) -> Result<impl IntoResponse, ServerError> {
+    ctx.require_base_user()?;
+    ctx.require_permission(Permission::WriteAdmin)?;
+
+    state.get_category_manager().remove(&slug).await?;
+
+    state
+        .syslog("delete_category")
+        .context(&ctx)
+        .action(LogAction::Delete)
+        .ceverity(LogCeverity::Warning)
+        .description("Removed a game category")
+        .metadata(json!({"slug": slug}))
+        .log_async();
+
+    Ok(StatusCode::NO_CONTENT)
+}
@@ -4,7 +4,8 @@ use axum::{Json, Router, extract::State, response::IntoResponse, routing::get};
 use reqwest::StatusCode;
 use serde_json::json;
 
-use tracing::error;
+use tracing::{error, instrument};
+use utoipa::OpenApi;
 
 use crate::{
     db,
@@ -19,21 +20,50 @@ pub fn health_routes(state: Arc<AppState>) -> Router {
     Router::new()
         .route("/", get(health))
         .route("/detailed", get(health_detailed))
+        .route("/migrations", get(health_migrations))
         .with_state(state.clone())
 }
 
+#[derive(OpenApi)]
+#[openapi(paths(health, health_detailed, health_migrations))]
+pub struct HealthApi;
+
+#[utoipa::path(
+    get,
+    path = "/",
+    tag = "health",
+    security(()),
+    responses((status = 200, description = "The platform is up", body = String))
+)]
+#[instrument(skip_all)]
 async fn health() -> impl IntoResponse {
     "OK".into_response()
 }
 
+#[utoipa::path(
+    get,
+    path = "/detailed",
+    tag = "health",
+    security(()),
+    responses((status = 200, description = "Status of the platform and its dependencies", body = Object))
+)]
+#[instrument(skip_all)]
 async fn health_detailed(
     State(state): State<Arc<AppState>>,
 ) -> Result<impl IntoResponse, ServerError> {
     let platform = true;
 
-    let db_status = match db::health::health_check(state.get_pool()).await {
-        Ok(_) => true,
-        Err(_) => false,
+    let db_status = db::health::health_check(state.get_pool()).await.is_ok();
+    let read_db_status = db::health::health_check(state.get_read_pool())
+        .await
+        .is_ok();
+
+    let pending_migrations = match db::health::pending_migrations(state.get_pool()).await {
+        Ok(pending) => Some(pending),
+        Err(e) => {
+            error!("Failed to read applied migrations: {}", e);
+            None
+        }
     };
 
     let session_status = match state.get_gs_client().health_check(state.get_client()).await {
@@ -41,10 +71,9 @@ async fn health_detailed(
         Err(e) => {
             error!("Failed game session health check: {}", e);
             state
-                .syslog()
+                .syslog("health_check")
                 .action(LogAction::Other)
                 .ceverity(LogCeverity::Critical)
-                .function("health_check")
                 .description("Failed health check on tero-session")
                 .log_async();
 
@@ -55,8 +84,44 @@ async fn health_detailed(
     let json = json!({
         "platform": platform,
         "database": db_status,
+        "read_database": read_db_status,
         "session": session_status,
+        "migrations_pending": pending_migrations.as_ref().is_none_or(|p| !p.is_empty()),
+        "pending_migrations": pending_migrations,
+        "page_cache": state.get_cache_stats(),
+        "key_vault": state.get_vault().stats(),
+        "user_syncs_in_flight": state.get_user_syncs().in_flight_count(),
     });
 
     Ok((StatusCode::OK, Json(json)))
 }
+
+/// Readiness probe, 503 until every migration in the binary has been applied
+#[utoipa::path(
+    get,
+    path = "/migrations",
+    tag = "health",
+    security(()),
+    responses(
+        (status = 200, description = "Every migration is applied", body = Object),
+        (status = 503, description = "Migrations are pending", body = Object)
+    )
+)]
+#[instrument(skip_all)]
+async fn health_migrations(
+    State(state): State<Arc<AppState>>,
+) -> Result<impl IntoResponse, ServerError> {
+    let pending = db::health::pending_migrations(state.get_pool()).await?;
+
+    let status = match pending.is_empty() {
+        true => StatusCode::OK,
+        false => StatusCode::SERVICE_UNAVAILABLE,
+    };
+
+    let json = json!({
+        "migrations_pending": !pending.is_empty(),
+        "pending_migrations": pending,
+    });
+
+    Ok((status, Json(json)))
+}
@@ -0,0 +1,204 @@
+use std::{collections::HashMap, sync::Arc, time::Duration};
+
+use axum::{
+    Extension, Json, Router,
+    extract::{Path, State},
+    response::IntoResponse,
+    routing::{delete, get},
+};
+use reqwest::StatusCode;
+use serde_json::json;
+use tracing::{debug, error, info, instrument};
+use utoipa::OpenApi;
+use uuid::Uuid;
+
+use crate::{
+    db,
+    models::{
+        app_state::AppState,
+        auth::Claims,
+        error::{ErrorBody, ServerError},
+        game_base::Validate,
+        integration::{CreateIntegrationRequest, Integration, IntegrationName},
+        system_log::{LogAction, LogCeverity},
+        user::{Permission, SubjectId},
+    },
+};
+
+pub fn integration_routes(state: Arc<AppState>) -> Router {
+    Router::new()
+        .route("/", get(list_integrations).post(create_integration))
+        .route("/health", get(integration_health))
+        .route("/{integration_id}", delete(delete_integration))
+        .with_state(state)
+}
+
+#[derive(OpenApi)]
+#[openapi(paths(
+    list_integrations,
+    create_integration,
+    delete_integration,
+    integration_health
+))]
+pub struct IntegrationApi;
+
+#[utoipa::path(
+    get,
+    path = "/",
+    tag = "integrations",
+    responses((status = 200, body = [Integration]))
+)]
+#[instrument(skip_all)]
+async fn list_integrations(
+    State(state): State<Arc<AppState>>,
+    Extension(claims): Extension<Claims>,
+) -> Result<impl IntoResponse, ServerError> {
+    if let Some(missing) = claims.missing_permission([Permission::WriteAdmin]) {
+        return Err(ServerError::Permission(missing));
+    }
+
+    let integrations = db::integration::list_integrations(state.get_pool()).await?;
+    Ok((StatusCode::OK, Json(integrations)))
+}
+
+#[utoipa::path(
+    post,
+    path = "/",
+    tag = "integrations",
+    request_body = CreateIntegrationRequest,
+    responses(
+        (status = 201, body = Integration),
+        (status = 422, body = ErrorBody)
+    )
+)]
+#[instrument(skip_all)]
+async fn create_integration(
+    State(state): State<Arc<AppState>>,
+    Extension(subject_id): Extension<SubjectId>,
+    Extension(claims): Extension<Claims>,
+    Json(request): Json<CreateIntegrationRequest>,
+) -> Result<impl IntoResponse, ServerError> {
+    let SubjectId::Integration(_) = subject_id else {
+        error!("Non integration subject tried registering an integration");
+        return Err(ServerError::AccessDenied);
+    };
+
+    if let Some(missing) = claims.missing_permission([Permission::WriteAdmin]) {
+        return Err(ServerError::Permission(missing));
+    }
+
+    request.validate()?;
+
+    let pool = state.get_pool();
+    let integration =
+        db::integration::create_integration(pool, &request.subject, &request.name, &request.url)
+            .await?;
+    state.get_integrations().reload(pool).await?;
+
+    info!("Integration {} was registered", integration.name);
+    state
+        .syslog("create_integration")
+        .subject(subject_id)
+        .action(LogAction::Create)
+        .ceverity(LogCeverity::Info)
+        .description("Registered a new integration")
+        .metadata(json!({"integration_id": integration.id, "name": integration.name}))
+        .log_async();
+
+    Ok((StatusCode::CREATED, Json(integration)))
+}
+
+#[utoipa::path(
+    delete,
+    path = "/{integration_id}",
+    tag = "integrations",
+    responses((status = 204))
+)]
+#[instrument(skip_all)]
+async fn delete_integration(
+    State(state): State<Arc<AppState>>,
+    Extension(subject_id): Extension<SubjectId>,
+    Extension(claims): Extension<Claims>,
+    Path(integration_id): Path<Uuid>,
+) -> Result<impl IntoResponse, ServerError> {
+    let SubjectId::Integration(_) = subject_id else {
+        error!("Non integration subject tried deleting an integration");
+        return Err(ServerError::AccessDenied);
+    };
+
+    if let Some(missing) = claims.missing_permission([Permission::WriteAdmin]) {
+        return Err(ServerError::Permission(missing));
+    }
+
+    let pool = state.get_pool();
+    db::integration::delete_integration(pool, integration_id).await?;
+    state.get_integrations().reload(pool).await?;
+
+    state
+        .syslog("delete_integration")
+        .subject(subject_id)
+        .action(LogAction::Delete)
+        .ceverity(LogCeverity::Warning)
+        .description("Removed an integration")
+        .metadata(json!({"integration_id": integration_id}))
+        .log_async();
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+#[utoipa::path(
+    get,
+    path = "/health",
+    tag = "integrations",
+    responses((status = 200, description = "Health per integration name", body = HashMap<String, bool>))
+)]
+#[instrument(skip_all)]
+async fn integration_health(
+    State(state): State<Arc<AppState>>,
+    Extension(subject_id): Extension<SubjectId>,
+    Extension(claims): Extension<Claims>,
+) -> Result<impl IntoResponse, ServerError> {
+    let SubjectId::BaseUser(_) = subject_id else {
+        error!("Unauthorized subject tried reading integration health");
+        return Err(ServerError::AccessDenied);
+    };
+
+    if let Some(missing) = claims.missing_permission([Permission::ReadAdmin]) {
+        return Err(ServerError::Permission(missing));
+    }
+
+    let integrations = db::integration::list_integrations(state.get_pool()).await?;
+    let registered = state.get_integrations().ids();
+
+    let checks = registered.into_iter().map(|(name, id)| {
+        let url = integrations
+            .iter()
+            .find(|i| i.id == id)
+            .and_then(|i| i.url.clone());
+
+        let client = state.get_client().clone();
+        async move {
+            let Some(url) = url else {
+                debug!("Integration {} has no health url", name);
+                return (name, false);
+            };
+
+            let healthy = client
+                .get(&url)
+                .timeout(Duration::from_secs(3))
+                .send()
+                .await
+                .map(|response| response.status().is_success())
+                .unwrap_or(false);
+
+            (name, healthy)
+        }
+    });
+
+    let statuses: HashMap<IntegrationName, bool> = futures::future::join_all(checks)
+        .await
+        .into_iter()
+        .collect();
+
+    Ok((StatusCode::OK, Json(statuses)))
+}
@@ -0,0 +1,73 @@
+use axum::{
+    Extension,
+    body::Body,
+    extract::{Request, State},
+    middleware::Next,
+    response::Response,
+};
+use serde_json::json;
+use sqlx::{Pool, Postgres};
+
+use crate::{
+    models::{
+        error::ServerError,
+        system_log::{LogAction, LogCeverity},
+        user::SubjectId,
+    },
+    service::system_log_builder::SystemLogBuilder,
+};
+
+/// Restricts a route group to a fixed set of integrations
+#[derive(Clone)]
+pub struct IntegrationGuard {
+    pool: Pool<Postgres>,
+    allowed: &'static [&'static str],
+}
+
+impl IntegrationGuard {
+    pub fn new(pool: &Pool<Postgres>, allowed: &'static [&'static str]) -> Self {
+        Self {
+            pool: pool.clone(),
+            allowed,
+        }
+    }
+}
+
+/// Must be layered inside the middleware that resolves the `SubjectId`
+pub async fn integration_guard_mw(
+    State(guard): State<IntegrationGuard>,
+    Extension(subject_id): Extension<SubjectId>,
+    req: Request<Body>,
+    next: Next,
+) -> Result<Response, ServerError> {
+    ensure_integration(
+        &guard.pool,
+        &subject_id,
+        guard.allowed,
+        "integration_guard_mw",
+    )?;
+    Ok(next.run(req).await)
+}
+
+/// Same check as the middleware for single endpoints, violations are
+/// logged as critical since they mean a client is using the wrong routes
+pub fn ensure_integration(
+    pool: &Pool<Postgres>,
+    subject_id: &SubjectId,
+    allowed: &[&str],
+    function: &str,
+) -> Result<(), ServerError> {
+    if let Err(e) = subject_id.require_integration(allowed) {
+        SystemLogBuilder::new_with_function(pool, function)
+            .subject(subject_id.clone())
+            .action(LogAction::Other)
+            .ceverity(LogCeverity::Critical)
+            .description("Subject called a route reserved for other integrations")
+            .metadata(json!({"allowed": allowed}))
+            .log_async();
+
+        return Err(e);
+    }
+
+    Ok(())
+}
@@ -0,0 +1,125 @@
+use std::sync::Arc;
+
+use axum::{
+    Json, Router,
+    extract::{Path, Query, State},
+    http::StatusCode,
+    response::IntoResponse,
+    routing::{delete, get},
+};
+use serde_json::json;
+use tracing::{info, instrument};
+use utoipa::OpenApi;
+use uuid::Uuid;
+
+use crate::{
+    db,
+    models::{
+        app_state::AppState,
+        error::{ErrorBody, ServerError},
+        game_base::Validate,
+        key_vault::{CreateWordRequest, Word, WordQuery},
+        request_context::RequestContext,
+        system_log::{LogAction, LogCeverity},
+        user::Permission,
+    },
+};
+
+pub fn word_routes(state: Arc<AppState>) -> Router {
+    Router::new()
+        .route("/", get(list_words).post(create_word))
+        .route("/{word_id}", delete(delete_word))
+        .with_state(state)
+}
+
+#[derive(OpenApi)]
+#[openapi(paths(list_words, create_word, delete_word))]
+pub struct WordApi;
+
+#[utoipa::path(
+    get,
+    path = "/",
+    tag = "words",
+    params(WordQuery),
+    responses((status = 200, body = [Word]))
+)]
+#[instrument(skip_all)]
+async fn list_words(
+    State(state): State<Arc<AppState>>,
+    ctx: RequestContext,
+    Query(query): Query<WordQuery>,
+) -> Result<impl IntoResponse, ServerError> {
+    ctx.require_base_user()?;
+    ctx.require_permission(Permission::WriteAdmin)?;
+
+    let words = db::key_vault::list_words(state.get_pool(), query.slot).await?;
+    Ok((StatusCode::OK, Json(words)))
+}
+
+#[utoipa::path(
+    post,
+    path = "/",
+    tag = "words",
+    request_body = CreateWordRequest,
+    responses(
+        (status = 201, body = Word),
+        (status = 422, body = ErrorBody)
+    )
+)]
+#[instrument(skip_all)]
+async fn create_word(
+    State(state): State<Arc<AppState>>,
+    ctx: RequestContext,
+    Json(request): Json<CreateWordRequest>,
+) -> Result<impl IntoResponse, ServerError> {
+    ctx.require_base_user()?;
+    ctx.require_permission(Permission::WriteAdmin)?;
+    request.validate()?;
+
+    let pool = state.get_pool();
+    let word = db::key_vault::insert_word(pool, request.slot, &request.word).await?;
+    state.get_vault().reload_words(pool).await?;
+
+    info!("Added {} word {}", word.slot, word.word);
+    state
+        .syslog("create_word")
+        .context(&ctx)
+        .action(LogAction::Create)
+        .ceverity(LogCeverity::Info)
+        .description("Added a word to the game key pool")
+        .metadata(json!({"word_id": word.id, "slot": word.slot, "word": word.word}))
+        .log_async();
+
+    Ok((StatusCode::CREATED, Json(word)))
+}
+
+#[utoipa::path(
+    delete,
+    path = "/{word_id}",
+    tag = "words",
+    responses((status = 204))
+)]
+#[instrument(skip_all)]
+async fn delete_word(
+    State(state): State<Arc<AppState>>,
+    ctx: RequestContext,
+    Path(word_id): Path<Uuid>,
+) -> Result<impl IntoResponse, ServerError> {
+    ctx.require_base_user()?;
+    ctx.require_permission(Permission::WriteAdmin)?;
+
+    let pool = state.get_pool();
+    db::key_vault::delete_word(pool, word_id).await?;
+    state.get_vault().reload_words(pool).await?;
+
+    state
+        .syslog("delete_word")
+        .context(&ctx)
+        .action(LogAction::Delete)
+        .ceverity(LogCeverity::Warning)
+        .description("Removed a word from the game key pool")
+        .metadata(json!({"word_id": word_id}))
+        .log_async();
+
+    Ok(StatusCode::NO_CONTENT)
+}
@@ -0,0 +1,61 @@
+use std::{net::SocketAddr, sync::Arc};
+
+use axum::{
+    Router,
+    body::Body,
+    extract::{ConnectInfo, Request, State},
+    http::header,
+    middleware::{Next, from_fn},
+    response::{IntoResponse, Response},
+    routing::get,
+};
+use reqwest::StatusCode;
+use tracing::{instrument, warn};
+use utoipa::OpenApi;
+
+use crate::{config::config::CONFIG, models::app_state::AppState};
+
+pub fn metrics_routes(state: Arc<AppState>) -> Router {
+    Router::new()
+        .route("/", get(get_metrics))
+        .layer(from_fn(ip_allowlist_mw))
+        .with_state(state.clone())
+}
+
+#[derive(OpenApi)]
+#[openapi(paths(get_metrics))]
+pub struct MetricsApi;
+
+#[utoipa::path(
+    get,
+    path = "/",
+    tag = "metrics",
+    security(()),
+    responses(
+        (status = 200, description = "Prometheus text format", body = String, content_type = "text/plain"),
+        (status = 403, description = "The peer is not in `metrics.allowed_ips`")
+    )
+)]
+#[instrument(skip_all)]
+async fn get_metrics(State(state): State<Arc<AppState>>) -> impl IntoResponse {
+    let body = state.get_metrics().render(&state.metrics_snapshot());
+    (
+        StatusCode::OK,
+        [(header::CONTENT_TYPE, "text/plain; version=0.0.4")],
+        body,
+    )
+}
+
+/// Only lets peers from `metrics.allowed_ips` through
+pub async fn ip_allowlist_mw(
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    req: Request<Body>,
+    next: Next,
+) -> Response {
+    if !CONFIG.metrics.allowed_ips.contains(&addr.ip()) {
+        warn!("Rejected metrics scrape from {}", addr.ip());
+        return StatusCode::FORBIDDEN.into_response();
+    }
+
+    next.run(req).await
+}
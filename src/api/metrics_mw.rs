@@ -0,0 +1,38 @@
+use std::{sync::Arc, time::Instant};
+
+use axum::{
+    body::Body,
+    extract::{MatchedPath, Request, State},
+    middleware::Next,
+    response::Response,
+};
+
+use crate::models::app_state::AppState;
+
+/// Label used for requests that did not match a route, so raw paths of
+/// unknown urls never end up as label values
+static UNKNOWN_ROUTE: &str = "unknown";
+
+/// Records the request count and latency by route template and status
+pub async fn metrics_mw(
+    State(state): State<Arc<AppState>>,
+    req: Request<Body>,
+    next: Next,
+) -> Response {
+    let started = Instant::now();
+    let method = req.method().to_string();
+    let route = req
+        .extensions()
+        .get::<MatchedPath>()
+        .map(|path| path.as_str().to_string())
+        .unwrap_or_else(|| UNKNOWN_ROUTE.to_string());
+
+    let response = next.run(req).await;
+    let status = response.status().as_u16();
+
+    let metrics = state.get_metrics();
+    metrics.record_request(&method, &route, status);
+    metrics.observe_latency(&route, status, started.elapsed());
+
+    response
+}
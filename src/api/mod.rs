@@ -1,6 +1,17 @@
 pub mod auth_mw;
+pub mod billing;
+pub mod cors_mw;
 pub mod game_base;
 pub mod health;
+pub mod integration;
+pub mod integration_guard_mw;
+pub mod key_vault;
+pub mod metrics;
+pub mod metrics_mw;
+pub mod openapi;
+pub mod payload_mw;
+pub mod router;
 pub mod system_log;
+pub mod trace_mw;
 pub mod user;
 pub mod webhook_mw;
@@ -0,0 +1,182 @@
+use axum::{
+    Json, Router,
+    response::{Html, IntoResponse},
+    routing::get,
+};
+use reqwest::StatusCode;
+use tracing::instrument;
+use utoipa::{
+    Modify, OpenApi, PartialSchema, ToSchema,
+    openapi::{
+        Content, Ref, RefOr, ResponseBuilder,
+        security::{ApiKey, ApiKeyValue, HttpAuthScheme, HttpBuilder, SecurityScheme},
+    },
+};
+
+use crate::{
+    api::{
+        billing::BillingApi,
+        game_base::GameApi,
+        health::HealthApi,
+        integration::IntegrationApi,
+        key_vault::WordApi,
+        metrics::MetricsApi,
+        system_log::LogApi,
+        user::{Auth0WebhookApi, PublicUserApi, UserApi},
+    },
+    config::config::CONFIG,
+    models::error::{ErrorBody, ErrorCode},
+};
+
+/// Feature flag for the Swagger UI, only enabled in `development.toml`
+pub static SWAGGER_UI_FLAG: &str = "swagger_ui";
+
+/// Built from the `#[utoipa::path]` annotations on the handlers, nested the
+/// same way as the router in `router.rs`
+#[derive(OpenApi)]
+#[openapi(
+    info(title = "Tero platform API"),
+    nest(
+        (path = "/health", api = HealthApi),
+        (path = "/pseudo-users", api = PublicUserApi),
+        (path = "/games", api = GameApi),
+        (path = "/users", api = UserApi),
+        (path = "/logs", api = LogApi),
+        (path = "/integrations", api = IntegrationApi),
+        (path = "/admin/words", api = WordApi),
+        (path = "/webhooks/auth0", api = Auth0WebhookApi),
+        (path = "/billing", api = BillingApi),
+        (path = "/metrics", api = MetricsApi)
+    ),
+    modifiers(&NestedRoots, &SecuritySchemes, &ErrorResponse),
+    security(("bearer" = []), ("guest" = []))
+)]
+pub struct ApiDoc;
+
+/// Axum serves a `/` route nested under `/health` at `/health`, utoipa
+/// documents it as `/health/`
+struct NestedRoots;
+
+impl Modify for NestedRoots {
+    fn modify(&self, openapi: &mut utoipa::openapi::OpenApi) {
+        let paths = std::mem::take(&mut openapi.paths.paths);
+        openapi.paths.paths = paths
+            .into_iter()
+            .map(|(path, item)| match path.len() > 1 {
+                true => (path.trim_end_matches('/').to_string(), item),
+                false => (path, item),
+            })
+            .collect();
+    }
+}
+
+struct SecuritySchemes;
+
+impl Modify for SecuritySchemes {
+    fn modify(&self, openapi: &mut utoipa::openapi::OpenApi) {
+        let components = openapi.components.get_or_insert_with(Default::default);
+        components.add_security_scheme(
+            "bearer",
+            SecurityScheme::Http(
+                HttpBuilder::new()
+                    .scheme(HttpAuthScheme::Bearer)
+                    .bearer_format("JWT")
+                    .build(),
+            ),
+        );
+        components.add_security_scheme(
+            "guest",
+            SecurityScheme::ApiKey(ApiKey::Header(ApiKeyValue::with_description(
+                "X-Guest-Authentication",
+                "Pseudo user id, or a signed guest token when signatures are enforced",
+            ))),
+        );
+        components.add_security_scheme(
+            "auth0_signature",
+            SecurityScheme::ApiKey(ApiKey::Header(ApiKeyValue::with_description(
+                "x-auth0-signature",
+                "HMAC-SHA256 of the body with the Auth0 webhook key",
+            ))),
+        );
+        components.add_security_scheme(
+            "stripe_webhook_key",
+            SecurityScheme::ApiKey(ApiKey::Header(ApiKeyValue::new("Stripe-Webhook-Key"))),
+        );
+    }
+}
+
+/// Every handler fails through `ServerError`, so its body is documented once
+/// as the default response of each operation
+struct ErrorResponse;
+
+impl Modify for ErrorResponse {
+    fn modify(&self, openapi: &mut utoipa::openapi::OpenApi) {
+        let components = openapi.components.get_or_insert_with(Default::default);
+        components
+            .schemas
+            .insert(ErrorBody::name().into(), ErrorBody::schema());
+        components
+            .schemas
+            .insert(ErrorCode::name().into(), ErrorCode::schema());
+
+        let response = ResponseBuilder::new()
+            .description("Error code and message, empty for internal errors")
+            .content(
+                "application/json",
+                Content::new(Some(Ref::from_schema_name(ErrorBody::name()))),
+            )
+            .build();
+
+        for item in openapi.paths.paths.values_mut() {
+            let operations = [
+                &mut item.get,
+                &mut item.post,
+                &mut item.put,
+                &mut item.patch,
+                &mut item.delete,
+            ];
+            for operation in operations.into_iter().flatten() {
+                operation
+                    .responses
+                    .responses
+                    .entry("default".into())
+                    .or_insert_with(|| RefOr::T(response.clone()));
+            }
+        }
+    }
+}
+
+pub fn openapi_routes() -> Router {
+    let router = Router::new().route("/openapi.json", get(openapi_spec));
+
+    match CONFIG.features.is_enabled(SWAGGER_UI_FLAG) {
+        true => router.route("/docs", get(swagger_ui)),
+        false => router,
+    }
+}
+
+#[instrument(skip_all)]
+async fn openapi_spec() -> impl IntoResponse {
+    (StatusCode::OK, Json(ApiDoc::openapi()))
+}
+
+#[instrument(skip_all)]
+async fn swagger_ui() -> impl IntoResponse {
+    Html(
+        r##"<!DOCTYPE html>
+<html lang="en">
+<head>
+    <meta charset="utf-8" />
+    <title>Tero platform API</title>
+    <link rel="stylesheet" href="https://unpkg.com/swagger-ui-dist@5/swagger-ui.css" />
+</head>
+<body>
+    <div id="swagger-ui"></div>
+    <script src="https://unpkg.com/swagger-ui-dist@5/swagger-ui-bundle.js"></script>
+    <script>
+        window.ui = SwaggerUIBundle({ url: "/openapi.json", dom_id: "#swagger-ui" });
+    </script>
+</body>
+</html>"##,
+    )
+}
@@ -0,0 +1,56 @@
+use axum::{
+    body::Body,
+    extract::{Request, State},
+    http::StatusCode,
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use serde_json::json;
+use sqlx::{Pool, Postgres};
+
+use crate::{
+    models::{
+        error::ServerError,
+        request_context::RequestContext,
+        system_log::{LogAction, LogCeverity},
+        user::SubjectId,
+    },
+    service::system_log_builder::SystemLogBuilder,
+};
+
+/// Replaces the plain text body rejections from axum's extractors (over the
+/// `DefaultBodyLimit` or not `application/json`) with `ServerError`s. Placed
+/// inside the auth layers so rejected integration calls end up in the system log
+pub async fn payload_mw(
+    State(pool): State<Pool<Postgres>>,
+    req: Request<Body>,
+    next: Next,
+) -> Response {
+    let ctx = req.extensions().get::<RequestContext>().cloned();
+    let path = req.uri().path().to_string();
+
+    let response = next.run(req).await;
+    let error = match response.status() {
+        StatusCode::PAYLOAD_TOO_LARGE => {
+            ServerError::PayloadTooLarge("Request body exceeds the size limit".into())
+        }
+        StatusCode::UNSUPPORTED_MEDIA_TYPE => ServerError::UnsupportedMediaType(
+            "Expected request with `Content-Type: application/json`".into(),
+        ),
+        _ => return response,
+    };
+
+    if let Some(ctx) = ctx
+        && matches!(ctx.subject, SubjectId::Integration(_))
+    {
+        SystemLogBuilder::new_with_function(&pool, "payload_mw")
+            .context(&ctx)
+            .action(LogAction::Other)
+            .ceverity(LogCeverity::Warning)
+            .description("Rejected integration request payload")
+            .metadata(json!({"path": path, "error": error.to_string()}))
+            .log_async();
+    }
+
+    error.into_response()
+}
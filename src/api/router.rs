@@ -0,0 +1,83 @@
+use std::sync::Arc;
+
+use axum::{
+    Router,
+    extract::DefaultBodyLimit,
+    middleware::{from_fn, from_fn_with_state},
+    routing::post,
+};
+
+use crate::{
+    api::{
+        auth_mw::auth_mw,
+        billing::billing_webhook,
+        cors_mw::cors_layer,
+        game_base::game_routes,
+        health::health_routes,
+        integration::integration_routes,
+        integration_guard_mw::{IntegrationGuard, integration_guard_mw},
+        key_vault::word_routes,
+        metrics::metrics_routes,
+        metrics_mw::metrics_mw,
+        openapi::openapi_routes,
+        payload_mw::payload_mw,
+        system_log::log_routes,
+        trace_mw::trace_mw,
+        user::{
+            auth0_delete_endpoint, auth0_trigger_endpoint, protected_auth_routes,
+            public_auth_routes,
+        },
+        webhook_mw::webhook_mw,
+    },
+    config::config::CONFIG,
+    models::{app_state::AppState, integration::IntegrationName},
+};
+
+/// The full api with every middleware, shared by `main` and the test server
+pub fn app_router(state: Arc<AppState>) -> Router {
+    let event_routes = Router::new()
+        .route("/{pseudo_id}", post(auth0_trigger_endpoint))
+        .route("/delete", post(auth0_delete_endpoint))
+        .layer(from_fn_with_state(
+            IntegrationGuard::new(state.get_pool(), &[IntegrationName::AUTH0]),
+            integration_guard_mw,
+        ))
+        .layer(from_fn_with_state(state.get_pool().clone(), payload_mw))
+        .layer(from_fn_with_state(state.clone(), webhook_mw))
+        .with_state(state.clone());
+
+    let billing_routes = Router::new()
+        .route("/webhook", post(billing_webhook))
+        .layer(from_fn_with_state(
+            IntegrationGuard::new(state.get_pool(), &[IntegrationName::STRIPE]),
+            integration_guard_mw,
+        ))
+        .layer(from_fn_with_state(state.get_pool().clone(), payload_mw))
+        .layer(from_fn_with_state(state.clone(), webhook_mw))
+        .with_state(state.clone());
+
+    let public_routes = Router::new()
+        .nest("/health", health_routes(state.clone()))
+        .nest("/pseudo-users", public_auth_routes(state.clone()))
+        .merge(openapi_routes());
+
+    let protected_routes = Router::new()
+        .nest("/games", game_routes(state.clone()))
+        .nest("/users", protected_auth_routes(state.clone()))
+        .nest("/logs", log_routes(state.clone()))
+        .nest("/integrations", integration_routes(state.clone()))
+        .nest("/admin/words", word_routes(state.clone()))
+        .layer(from_fn_with_state(state.get_pool().clone(), payload_mw))
+        .layer(from_fn_with_state(state.clone(), auth_mw));
+
+    Router::new()
+        .merge(protected_routes)
+        .merge(public_routes)
+        .nest("/webhooks/auth0", event_routes)
+        .nest("/billing", billing_routes)
+        .layer(from_fn_with_state(state.clone(), metrics_mw))
+        .layer(from_fn(trace_mw))
+        .layer(DefaultBodyLimit::max(CONFIG.server.max_body_bytes))
+        .layer(cors_layer(&CONFIG.cors))
+        .nest("/metrics", metrics_routes(state.clone()))
+}
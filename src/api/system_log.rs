@@ -1,14 +1,15 @@
 use std::sync::Arc;
 
-use tracing::{error, info};
+use tracing::{error, info, instrument};
 
 use axum::{
     Extension, Json, Router,
     extract::{Query, State},
     response::IntoResponse,
-    routing::{post, get},
+    routing::{get, post},
 };
 use reqwest::StatusCode;
+use utoipa::OpenApi;
 
 use crate::{
     db,
@@ -16,19 +17,44 @@ use crate::{
         app_state::AppState,
         auth::Claims,
         error::ServerError,
-        system_log::{CreateSyslogRequest, SyslogPageQuery},
+        popup_manager::PagedResponse,
+        system_log::{
+            BatchSyslogResult, CreateSyslogRequest, LogCategoryCount, LogStats, LogStatsQuery,
+            MAX_SYSLOG_BATCH_SIZE, SubjectType, SyslogPageQuery, SystemLog,
+        },
         user::{Permission, SubjectId},
     },
+    service::system_log_builder::UNSPECIFIED_FUNCTION,
 };
 
 pub fn log_routes(state: Arc<AppState>) -> Router {
     Router::new()
         .route("/", post(create_system_log))
         .route("/", get(get_system_log_page))
+        .route("/batch", post(create_system_log_batch))
         .route("/count", get(get_log_category_count))
+        .route("/stats", get(get_log_stats))
         .with_state(state)
 }
 
+#[derive(OpenApi)]
+#[openapi(paths(
+    create_system_log,
+    get_system_log_page,
+    create_system_log_batch,
+    get_log_category_count,
+    get_log_stats
+))]
+pub struct LogApi;
+
+#[utoipa::path(
+    get,
+    path = "/",
+    tag = "logs",
+    params(SyslogPageQuery),
+    responses((status = 200, body = PagedResponse<SystemLog>))
+)]
+#[instrument(skip_all)]
 async fn get_system_log_page(
     State(state): State<Arc<AppState>>,
     Extension(subject_id): Extension<SubjectId>,
@@ -44,10 +70,18 @@ async fn get_system_log_page(
         return Err(ServerError::Permission(missing));
     }
 
-    let page = db::system_log::get_system_log_page(state.get_pool(), query).await?;
+    let page = db::system_log::get_system_log_page(state.get_read_pool(), query).await?;
     Ok((StatusCode::OK, Json(page)))
 }
 
+#[utoipa::path(
+    post,
+    path = "/",
+    tag = "logs",
+    request_body = CreateSyslogRequest,
+    responses((status = 201, description = "The log is written in the background"))
+)]
+#[instrument(skip_all)]
 async fn create_system_log(
     State(state): State<Arc<AppState>>,
     Extension(subject_id): Extension<SubjectId>,
@@ -68,7 +102,8 @@ async fn create_system_log(
         }
     };
 
-    let mut builder = state.syslog().subject(subject_id);
+    let function = request.function.as_deref().unwrap_or(UNSPECIFIED_FUNCTION);
+    let mut builder = state.syslog(function).subject(subject_id);
 
     if let Some(action) = request.action {
         builder = builder.action(action);
@@ -86,8 +121,8 @@ async fn create_system_log(
         builder = builder.metadata(metadata);
     }
 
-    if let Some(function) = request.function {
-        builder = builder.function(&function);
+    if let Some(correlation_id) = request.correlation_id {
+        builder = builder.correlation_id(correlation_id);
     }
 
     builder.log_async();
@@ -95,10 +130,104 @@ async fn create_system_log(
     Ok(StatusCode::CREATED)
 }
 
+#[utoipa::path(
+    post,
+    path = "/batch",
+    tag = "logs",
+    request_body = Vec<CreateSyslogRequest>,
+    responses(
+        (status = 201, description = "Every entry was written", body = [BatchSyslogResult]),
+        (status = 207, description = "Some entries were invalid", body = [BatchSyslogResult])
+    )
+)]
+#[instrument(skip_all)]
+async fn create_system_log_batch(
+    State(state): State<Arc<AppState>>,
+    Extension(subject_id): Extension<SubjectId>,
+    Extension(claims): Extension<Claims>,
+    Json(entries): Json<Vec<CreateSyslogRequest>>,
+) -> Result<impl IntoResponse, ServerError> {
+    let SubjectId::Integration(int_name) = &subject_id else {
+        error!("Non integration subject tried writing a system log batch");
+        return Err(ServerError::AccessDenied);
+    };
+
+    if let Some(missing) = claims.missing_permission([Permission::WriteSystemLog]) {
+        return Err(ServerError::Permission(missing));
+    }
+
+    if entries.is_empty() || entries.len() > MAX_SYSLOG_BATCH_SIZE {
+        return Err(ServerError::Api(
+            StatusCode::BAD_REQUEST,
+            format!("Batch must contain 1 to {} entries", MAX_SYSLOG_BATCH_SIZE),
+        ));
+    }
+
+    let (results, valid) = validate_syslog_batch(&entries);
+
+    info!(
+        "Integration {} is writing {} of {} system logs",
+        int_name,
+        valid.len(),
+        entries.len()
+    );
+
+    db::system_log::create_system_logs(
+        state.get_pool(),
+        &int_name.to_string(),
+        &SubjectType::Integration,
+        &valid,
+    )
+    .await?;
+
+    let status = match valid.len() == entries.len() {
+        true => StatusCode::CREATED,
+        false => StatusCode::MULTI_STATUS,
+    };
+
+    Ok((status, Json(results)))
+}
+
+pub fn validate_syslog_batch(
+    entries: &[CreateSyslogRequest],
+) -> (Vec<BatchSyslogResult>, Vec<&CreateSyslogRequest>) {
+    let mut results = Vec::with_capacity(entries.len());
+    let mut valid = Vec::with_capacity(entries.len());
+
+    for (index, entry) in entries.iter().enumerate() {
+        match entry.validate() {
+            Ok(_) => {
+                valid.push(entry);
+                results.push(BatchSyslogResult {
+                    index,
+                    created: true,
+                    error: None,
+                });
+            }
+            Err(e) => results.push(BatchSyslogResult {
+                index,
+                created: false,
+                error: Some(e),
+            }),
+        }
+    }
+
+    (results, valid)
+}
+
+#[utoipa::path(
+    get,
+    path = "/count",
+    tag = "logs",
+    params(LogStatsQuery),
+    responses((status = 200, body = LogCategoryCount))
+)]
+#[instrument(skip_all)]
 async fn get_log_category_count(
     State(state): State<Arc<AppState>>,
     Extension(subject_id): Extension<SubjectId>,
     Extension(claims): Extension<Claims>,
+    Query(window): Query<LogStatsQuery>,
 ) -> Result<impl IntoResponse, ServerError> {
     let SubjectId::BaseUser(_) = subject_id else {
         error!("Unauthorized subject tried reading log category counts");
@@ -109,6 +238,33 @@ async fn get_log_category_count(
         return Err(ServerError::Permission(missing));
     }
 
-    let counts = db::system_log::get_log_category_count(state.get_pool()).await?;
+    let counts = db::system_log::get_log_category_count(state.get_pool(), &window).await?;
     Ok((StatusCode::OK, Json(counts)))
 }
+
+#[utoipa::path(
+    get,
+    path = "/stats",
+    tag = "logs",
+    params(LogStatsQuery),
+    responses((status = 200, body = LogStats))
+)]
+#[instrument(skip_all)]
+async fn get_log_stats(
+    State(state): State<Arc<AppState>>,
+    Extension(subject_id): Extension<SubjectId>,
+    Extension(claims): Extension<Claims>,
+    Query(window): Query<LogStatsQuery>,
+) -> Result<impl IntoResponse, ServerError> {
+    let SubjectId::BaseUser(_) = subject_id else {
+        error!("Unauthorized subject tried reading log stats");
+        return Err(ServerError::AccessDenied);
+    };
+
+    if let Some(missing) = claims.missing_permission([Permission::ReadAdmin]) {
+        return Err(ServerError::Permission(missing));
+    }
+
+    let stats = db::system_log::get_log_stats(state.get_pool(), &window).await?;
+    Ok((StatusCode::OK, Json(stats)))
+}
@@ -0,0 +1,37 @@
+use axum::{body::Body, extract::Request, middleware::Next, response::Response};
+use tracing::{Instrument, field, info_span};
+use tracing_opentelemetry::OpenTelemetrySpanExt;
+
+use crate::service::trace_context::TraceContext;
+
+/// Continues the caller's trace, or starts a new one, and makes it available
+/// to outgoing calls made while handling the request
+pub async fn trace_mw(req: Request<Body>, next: Next) -> Response {
+    let parent = TraceContext::from_headers(req.headers());
+
+    let span = info_span!(
+        "request",
+        method = %req.method(),
+        path = %req.uri().path(),
+        trace_id = field::Empty,
+        span_id = field::Empty,
+    );
+
+    // Exported spans get their ids from the OpenTelemetry layer, the ids are
+    // made up here when spans are not exported
+    if let Some(parent) = &parent {
+        let _ = span.set_parent(parent.otel_context());
+    }
+
+    let tracestate = parent.as_ref().and_then(|parent| parent.tracestate.clone());
+    let cx = match (TraceContext::from_span(&span, tracestate), parent) {
+        (Some(cx), _) => cx,
+        (None, Some(parent)) => parent.child(),
+        (None, None) => TraceContext::new_root(),
+    };
+
+    span.record("trace_id", cx.trace_id.as_str());
+    span.record("span_id", cx.span_id.as_str());
+
+    cx.scope(next.run(req)).instrument(span).await
+}
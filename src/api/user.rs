@@ -1,38 +1,46 @@
-use std::{str::FromStr, sync::Arc};
+use std::sync::Arc;
 
 use axum::{
-    Extension, Json, Router,
+    Json, Router,
     extract::{Path, Query, State},
-    http::StatusCode,
+    http::{HeaderMap, StatusCode, header::ACCEPT_LANGUAGE},
     response::{IntoResponse, Response},
-    routing::{delete, get, post, put},
+    routing::{delete, get, post},
 };
 use serde_json::json;
 use sqlx::{Pool, Postgres};
-use tracing::{debug, error, info};
+use tracing::{debug, error, info, instrument};
+use utoipa::OpenApi;
 use uuid::Uuid;
 
 use crate::{
+    config::config::CONFIG,
     db::{
         self,
+        game_base::tx_reassign_game_owner,
         user::{
-            create_base_user, create_pseudo_user, delete_base_user_by_id, delete_pseudo_user,
-            get_base_user_by_id, list_base_users, patch_base_user_by_id, pseudo_user_exists,
-            tx_create_pseudo_user, update_pseudo_user_activity,
+            create_base_user, create_pseudo_user, delete_base_user_by_auth0_id,
+            delete_base_user_by_id, get_base_user_by_id, list_base_users, patch_base_user_by_id,
+            pseudo_user_exists, tx_create_pseudo_user, update_pseudo_user_activity, username_taken,
         },
     },
     models::{
         app_state::AppState,
-        auth::Claims,
         error::ServerError,
-        popup_manager::ClientPopup,
+        game_base::Validate,
+        integration::IntegrationName,
+        popup_manager::{ClientPopup, LocalizedPopup, PagedResponse, PopupQuery},
+        request_context::RequestContext,
         system_log::{LogAction, LogCeverity},
         user::{
-            Auth0User, EnsureUserQuery, ListUsersQuery, PatchUserRequest, Permission, SubjectId,
-            UserRole,
+            ActivityStats, Auth0DeleteEvent, Auth0User, BaseUser, EnsureUserQuery, ListUsersQuery,
+            PatchUserRequest, Permission, SubjectId, UserRole,
         },
     },
-    service::system_log_builder::SystemLogBuilder,
+    service::{
+        guest_token::guest_token_headers,
+        util::{extract_header, parse_accept_language},
+    },
 };
 
 pub fn public_auth_routes(state: Arc<AppState>) -> Router {
@@ -42,50 +50,92 @@ pub fn public_auth_routes(state: Arc<AppState>) -> Router {
         .with_state(state)
 }
 
+#[derive(OpenApi)]
+#[openapi(paths(ensure_pseudo_user, get_client_popup))]
+pub struct PublicUserApi;
+
 pub fn protected_auth_routes(state: Arc<AppState>) -> Router {
     Router::new()
         .route("/", get(list_all_users))
         .route("/me", get(get_base_user_from_subject))
         .route("/{user_id}", delete(delete_user).patch(patch_user))
         .route("/activity-stats", get(get_user_activity_stats))
-        .route("/popups", put(update_client_popup))
+        .route("/stats", get(get_user_stats))
+        .route("/popups", get(list_client_popups).put(update_client_popup))
+        .route("/popups/{popup_id}", delete(delete_client_popup))
         .with_state(state)
 }
 
+#[derive(OpenApi)]
+#[openapi(paths(
+    list_all_users,
+    get_base_user_from_subject,
+    delete_user,
+    patch_user,
+    get_user_activity_stats,
+    get_user_stats,
+    list_client_popups,
+    update_client_popup,
+    delete_client_popup
+))]
+pub struct UserApi;
+
+/// Mounted under `/webhooks/auth0` in `main.rs`
+#[derive(OpenApi)]
+#[openapi(paths(auth0_trigger_endpoint, auth0_delete_endpoint))]
+pub struct Auth0WebhookApi;
+
+#[utoipa::path(
+    get,
+    path = "/me",
+    tag = "users",
+    responses((status = 200, body = UserRole))
+)]
+#[instrument(skip_all)]
 async fn get_base_user_from_subject(
     State(state): State<Arc<AppState>>,
-    Extension(subject_id): Extension<SubjectId>,
-    Extension(claims): Extension<Claims>,
+    ctx: RequestContext,
 ) -> Result<impl IntoResponse, ServerError> {
-    let user_id = match subject_id {
-        SubjectId::BaseUser(user_id) => user_id,
-        SubjectId::Integration(_) | SubjectId::PseudoUser(_) => {
-            return Err(ServerError::AccessDenied);
-        }
-    };
+    let user_id = ctx.require_base_user()?;
 
     let Some(user) = get_base_user_by_id(state.get_pool(), user_id).await? else {
         error!("Unexpected: user id was previously fetched but is now missing.");
         state
-            .syslog()
-            .subject(subject_id)
+            .syslog("get_user_from_subject")
+            .context(&ctx)
             .action(LogAction::Read)
             .ceverity(LogCeverity::Critical)
-            .function("get_user_from_subject")
             .description("Unexpected: user id was previously fetched but is now missing.")
             .log_async();
 
         return Err(ServerError::NotFound("User not found".into()));
     };
 
-    let wrapped = match claims.missing_permission([Permission::ReadAdmin, Permission::WriteAdmin]) {
-        Some(_missing) => UserRole::BaseUser(user),
-        None => UserRole::Admin(user),
+    let wrapped = match ctx
+        .claims
+        .has_all_permissions([Permission::ReadAdmin, Permission::WriteAdmin])
+    {
+        true => UserRole::Admin(user),
+        false => UserRole::BaseUser(user),
     };
 
     Ok((StatusCode::OK, Json(wrapped)))
 }
 
+#[utoipa::path(
+    post,
+    path = "/",
+    tag = "pseudo-users",
+    security(()),
+    params(EnsureUserQuery),
+    responses(
+        (status = 200, description = "The pseudo user exists", body = Uuid,
+            headers(("x-guest-token" = String, description = "Signed guest token, when a signing key is configured"))),
+        (status = 201, description = "A new pseudo user was created", body = Uuid,
+            headers(("x-guest-token" = String, description = "Signed guest token, when a signing key is configured")))
+    )
+)]
+#[instrument(skip_all)]
 async fn ensure_pseudo_user(
     State(state): State<Arc<AppState>>,
     Query(query): Query<EnsureUserQuery>,
@@ -95,7 +145,8 @@ async fn ensure_pseudo_user(
         Some(mut pseudo_id) => {
             let exists = pseudo_user_exists(state.get_pool(), pseudo_id).await?;
             if exists {
-                return Ok((StatusCode::OK, Json(pseudo_id)));
+                let headers = guest_token_headers(pseudo_id, &CONFIG.guest)?;
+                return Ok((StatusCode::OK, headers, Json(pseudo_id)));
             }
 
             pseudo_id = create_pseudo_user(state.get_pool()).await?;
@@ -106,38 +157,42 @@ async fn ensure_pseudo_user(
     let pool = state.get_pool().clone();
     tokio::spawn(async move {
         if let Err(e) = update_pseudo_user_activity(&pool, pseudo_id).await {
-            let _ = state
-                .syslog()
+            state
+                .syslog("ensure_pseudo_user")
                 .action(LogAction::Update)
                 .ceverity(LogCeverity::Warning)
-                .function("ensure_pseudo_user")
                 .description("Failed to update pseudo user activity")
                 .metadata(json!({"error": e.to_string()}))
-                .log();
+                .log_async();
         };
     });
 
-    Ok((StatusCode::CREATED, Json(pseudo_id)))
+    let headers = guest_token_headers(pseudo_id, &CONFIG.guest)?;
+    Ok((StatusCode::CREATED, headers, Json(pseudo_id)))
 }
 
+#[utoipa::path(
+    patch,
+    path = "/{user_id}",
+    tag = "users",
+    request_body = PatchUserRequest,
+    responses(
+        (status = 200, description = "The caller's own profile was patched", body = BaseUser),
+        (status = 204, description = "An admin patched another user, or nothing was sent"),
+        (status = 409, description = "The username is taken")
+    )
+)]
+#[instrument(skip_all)]
 async fn patch_user(
     State(state): State<Arc<AppState>>,
-    Extension(subject): Extension<SubjectId>,
-    Extension(claims): Extension<Claims>,
+    ctx: RequestContext,
     Path(user_id): Path<Uuid>,
     Json(request): Json<PatchUserRequest>,
 ) -> Result<Response, ServerError> {
-    error!("Birthdate: {}", request.birth_date.unwrap());
-    let SubjectId::BaseUser(uid) = subject else {
-        return Err(ServerError::AccessDenied);
-    };
+    let uid = ctx.require_base_user()?;
 
-    if claims
-        .missing_permission([Permission::WriteAdmin])
-        .is_none()
-        && user_id != uid
-    {
-        patch_base_user_by_id(state.get_pool(), &user_id, request).await?;
+    if ctx.has_permission(Permission::WriteAdmin) && user_id != uid {
+        patch_user_profile(state.get_pool(), user_id, request).await?;
         return Ok(StatusCode::NO_CONTENT.into_response());
     }
 
@@ -146,21 +201,41 @@ async fn patch_user(
         return Ok(StatusCode::NO_CONTENT.into_response());
     }
 
-    let user = patch_base_user_by_id(state.get_pool(), &uid, request).await?;
+    let user = patch_user_profile(state.get_pool(), uid, request).await?;
     Ok((StatusCode::OK, Json(user)).into_response())
 }
 
+/// Usernames are not unique in the schema, so a taken name is rejected
+/// here instead of relying on a constraint
+pub async fn patch_user_profile(
+    pool: &Pool<Postgres>,
+    user_id: Uuid,
+    request: PatchUserRequest,
+) -> Result<BaseUser, ServerError> {
+    if let Some(username) = &request.username
+        && username_taken(pool, username, Some(user_id)).await?
+    {
+        return Err(ServerError::Conflict("Username already taken".into()));
+    }
+
+    patch_base_user_by_id(pool, &user_id, request).await
+}
+
+#[utoipa::path(
+    delete,
+    path = "/{user_id}",
+    tag = "users",
+    responses((status = 200))
+)]
+#[instrument(skip_all)]
 async fn delete_user(
     State(state): State<Arc<AppState>>,
-    Extension(subject_id): Extension<SubjectId>,
-    Extension(claims): Extension<Claims>,
+    ctx: RequestContext,
     Path(user_id): Path<Uuid>,
 ) -> Result<impl IntoResponse, ServerError> {
-    let SubjectId::BaseUser(actual_user_id) = subject_id else {
-        return Err(ServerError::AccessDenied);
-    };
+    let actual_user_id = ctx.require_base_user()?;
 
-    if let None = claims.missing_permission([Permission::WriteAdmin]) {
+    if ctx.has_permission(Permission::WriteAdmin) {
         delete_base_user_by_id(state.get_pool(), &user_id).await?;
         return Ok(StatusCode::OK);
     }
@@ -173,13 +248,22 @@ async fn delete_user(
     Ok(StatusCode::OK)
 }
 
+#[utoipa::path(
+    post,
+    path = "/{pseudo_id}",
+    tag = "webhooks",
+    security(("auth0_signature" = [])),
+    request_body = Auth0User,
+    responses((status = 201, description = "Id of the created base user", body = Uuid))
+)]
+#[instrument(skip_all)]
 pub async fn auth0_trigger_endpoint(
     State(state): State<Arc<AppState>>,
-    Extension(subject_id): Extension<SubjectId>,
-    Path(pseudo_id): Path<String>,
+    ctx: RequestContext,
+    Path(pseudo_id): Path<Uuid>,
     Json(auth0_user): Json<Auth0User>,
 ) -> Result<impl IntoResponse, ServerError> {
-    let SubjectId::Integration(_intname) = &subject_id else {
+    let SubjectId::Integration(_intname) = &ctx.subject else {
         return Err(ServerError::AccessDenied);
     };
 
@@ -189,129 +273,209 @@ pub async fn auth0_trigger_endpoint(
         auth0_user.email.clone().unwrap_or("[no email]".to_string())
     );
 
-    let pseudo_id = Uuid::from_str(&pseudo_id).unwrap();
+    let pid = state
+        .with_tx(|tx| {
+            Box::pin(async move {
+                let bid = create_base_user(tx, &auth0_user).await?;
+                let pid = tx_create_pseudo_user(tx, bid).await?;
 
-    ensure_no_zombie_pseudo(state.get_pool(), pseudo_id, subject_id);
+                if bid != pid {
+                    return Err(ServerError::Internal("Failed to create user pair".into()));
+                }
 
-    let mut tx = state.get_pool().begin().await?;
-    let bid = create_base_user(&mut tx, &auth0_user).await?;
-    let pid = tx_create_pseudo_user(&mut tx, bid).await?;
+                // Games created before registering follow the user, the old pseudo user is
+                // cleaned up as a zombie and would otherwise leave them without an owner
+                let reassigned = tx_reassign_game_owner(tx, pseudo_id, bid).await?;
+                debug!(
+                    "Reassigned {} games from pseudo user {}",
+                    reassigned, pseudo_id
+                );
 
-    if bid != pid {
-        return Err(ServerError::Internal("Failed to create user pair".into()));
-    }
+                Ok(pid)
+            })
+        })
+        .await?;
 
-    tx.commit().await?;
+    state.spawn_sync_user(pid, pseudo_id, ctx.subject);
 
     Ok((StatusCode::CREATED, Json(pid)))
 }
 
-fn ensure_no_zombie_pseudo(pool: &Pool<Postgres>, pseudo_id: Uuid, subject_id: SubjectId) {
-    let pool = pool.clone();
-    tokio::spawn(async move {
-        let pool = pool.clone();
-        let subject_id = subject_id.clone();
-
-        let base_user = match get_base_user_by_id(&pool, pseudo_id).await {
-            Ok(option) => option,
-            Err(e) => {
-                let _ = SystemLogBuilder::new(&pool)
-                    .action(LogAction::Read)
-                    .ceverity(LogCeverity::Warning)
-                    .function("cleanup_subject_pseudo_id")
-                    .description("Failed to fetch base user for pseudo user cleanup")
-                    .subject(subject_id)
-                    .metadata(json!({"pseudo_user_id": pseudo_id, "error": e.to_string()}))
-                    .log();
-
-                return;
-            }
-        };
+#[utoipa::path(
+    post,
+    path = "/delete",
+    tag = "webhooks",
+    security(("auth0_signature" = [])),
+    request_body = Auth0DeleteEvent,
+    responses((status = 200))
+)]
+#[instrument(skip_all)]
+pub async fn auth0_delete_endpoint(
+    State(state): State<Arc<AppState>>,
+    ctx: RequestContext,
+    Json(event): Json<Auth0DeleteEvent>,
+) -> Result<impl IntoResponse, ServerError> {
+    if !matches!(&ctx.subject, SubjectId::Integration(name) if *name == IntegrationName::AUTH0) {
+        return Err(ServerError::AccessDenied);
+    }
 
-        if let Some(_) = base_user {
-            debug!("Base user exists for pseudo user, skipping cleanup");
-            return;
-        }
+    info!("Auth0 pre user deletion trigger was triggered");
+    let user_id = delete_base_user_by_auth0_id(state.get_pool(), &event.user_id).await?;
 
-        let (deleted, error) = match delete_pseudo_user(&pool, pseudo_id).await {
-            Ok(deleted) => (deleted, "no error".to_string()),
-            Err(e) => (false, e.to_string()),
-        };
+    state
+        .syslog("auth0_delete_endpoint")
+        .context(&ctx)
+        .action(LogAction::Delete)
+        .ceverity(LogCeverity::Warning)
+        .description("Deleted user on Auth0 deletion event")
+        .metadata(json!({"user_id": user_id, "auth0_id": event.user_id}))
+        .log_async();
 
-        if !deleted {
-            let _ = SystemLogBuilder::new(&pool)
-                .action(LogAction::Read)
-                .ceverity(LogCeverity::Critical)
-                .function("cleanup_subject_pseudo_id")
-                .description("Failed to fetch base user for pseudo user cleanup")
-                .subject(subject_id)
-                .metadata(json!({"pseudo_user_id": pseudo_id, "error": error}))
-                .log();
-        }
-    });
+    Ok(StatusCode::OK)
 }
 
+#[utoipa::path(
+    get,
+    path = "/",
+    tag = "users",
+    params(ListUsersQuery),
+    responses((status = 200, body = PagedResponse<BaseUser>))
+)]
+#[instrument(skip_all)]
 pub async fn list_all_users(
     State(state): State<Arc<AppState>>,
-    Extension(subject_id): Extension<SubjectId>,
-    Extension(claims): Extension<Claims>,
+    ctx: RequestContext,
     Query(query): Query<ListUsersQuery>,
 ) -> Result<impl IntoResponse, ServerError> {
-    let SubjectId::BaseUser(_) = subject_id else {
-        return Err(ServerError::AccessDenied);
-    };
+    ctx.require_base_user()?;
 
-    if let Some(missing) = claims.missing_permission([Permission::ReadAdmin]) {
-        return Err(ServerError::Permission(missing));
-    }
+    ctx.require_permission(Permission::ReadAdmin)?;
 
     let users = list_base_users(state.get_pool(), query).await?;
     Ok((StatusCode::OK, Json(users)))
 }
 
+#[utoipa::path(
+    get,
+    path = "/activity-stats",
+    tag = "users",
+    responses((status = 200, body = ActivityStats))
+)]
+#[instrument(skip_all)]
 async fn get_user_activity_stats(
     State(state): State<Arc<AppState>>,
-    Extension(subject_id): Extension<SubjectId>,
-    Extension(claims): Extension<Claims>,
+    ctx: RequestContext,
 ) -> Result<impl IntoResponse, ServerError> {
-    let SubjectId::BaseUser(_) = subject_id else {
-        error!("Unauthorized guest user or integration tried accessing admin endpoint");
-        return Err(ServerError::AccessDenied);
-    };
+    ctx.require_base_user()?;
 
-    if let Some(missing) = claims.missing_permission([Permission::ReadAdmin]) {
-        error!("Unauthorized user tried accessing admin endpoint");
-        return Err(ServerError::Permission(missing));
-    }
+    ctx.require_permission(Permission::ReadAdmin)?;
 
-    let stats = db::user::get_user_activity_stats(state.get_pool()).await?;
+    let stats = db::user::get_user_activity_stats(state.get_read_pool()).await?;
     Ok((StatusCode::OK, Json(stats)))
 }
 
+/// Same stats as `/activity-stats`, documented on its own since an operation
+/// has a single path
+#[utoipa::path(
+    get,
+    path = "/stats",
+    tag = "users",
+    responses((status = 200, body = ActivityStats))
+)]
+#[instrument(skip_all)]
+async fn get_user_stats(
+    state: State<Arc<AppState>>,
+    ctx: RequestContext,
+) -> Result<impl IntoResponse, ServerError> {
+    get_user_activity_stats(state, ctx).await
+}
+
+#[utoipa::path(
+    put,
+    path = "/popups",
+    tag = "popups",
+    request_body = ClientPopup,
+    responses((status = 200, body = ClientPopup))
+)]
+#[instrument(skip_all)]
 async fn update_client_popup(
     State(state): State<Arc<AppState>>,
-    Extension(subject_id): Extension<SubjectId>,
-    Extension(claims): Extension<Claims>,
+    ctx: RequestContext,
     Json(payload): Json<ClientPopup>,
 ) -> Result<impl IntoResponse, ServerError> {
-    let SubjectId::BaseUser(_user_id) = subject_id else {
-        return Err(ServerError::AccessDenied);
-    };
+    ctx.require_base_user()?;
 
-    if let Some(missing) = claims.missing_permission([Permission::WriteAdmin]) {
-        return Err(ServerError::Permission(missing));
-    }
+    ctx.require_permission(Permission::WriteAdmin)?;
+    payload.validate()?;
 
     let manager = state.get_popup_manager();
-    let popup = manager.update(payload).await;
+    let popup = manager.update(payload).await?;
     debug!("Popup updated successfully");
 
     Ok((StatusCode::OK, Json(popup)))
 }
 
+#[utoipa::path(
+    get,
+    path = "/popups",
+    tag = "popups",
+    responses((status = 200, body = [ClientPopup]))
+)]
+#[instrument(skip_all)]
+async fn list_client_popups(
+    State(state): State<Arc<AppState>>,
+    ctx: RequestContext,
+) -> Result<impl IntoResponse, ServerError> {
+    ctx.require_base_user()?;
+
+    ctx.require_permission(Permission::ReadAdmin)?;
+
+    let popups = state.get_popup_manager().list().await;
+    Ok((StatusCode::OK, Json(popups)))
+}
+
+#[utoipa::path(
+    delete,
+    path = "/popups/{popup_id}",
+    tag = "popups",
+    responses((status = 204))
+)]
+#[instrument(skip_all)]
+async fn delete_client_popup(
+    State(state): State<Arc<AppState>>,
+    ctx: RequestContext,
+    Path(popup_id): Path<Uuid>,
+) -> Result<impl IntoResponse, ServerError> {
+    ctx.require_base_user()?;
+
+    ctx.require_permission(Permission::WriteAdmin)?;
+
+    state.get_popup_manager().remove(popup_id).await?;
+    debug!("Popup {} removed successfully", popup_id);
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// `?locale=` is tried before the locales in `Accept-Language`
+#[utoipa::path(
+    get,
+    path = "/popups",
+    tag = "popups",
+    security(()),
+    params(PopupQuery),
+    responses((status = 200, description = "The popup to show right now", body = LocalizedPopup))
+)]
+#[instrument(skip_all)]
 pub async fn get_client_popup(
     State(state): State<Arc<AppState>>,
+    Query(query): Query<PopupQuery>,
+    headers: HeaderMap,
 ) -> Result<impl IntoResponse, ServerError> {
+    let mut locales: Vec<String> = query.locale.into_iter().collect();
+    if let Some(header) = extract_header(ACCEPT_LANGUAGE.as_str(), &headers) {
+        locales.extend(parse_accept_language(&header));
+    }
+
     let popup = state.get_popup_manager().read().await;
-    Ok((StatusCode::OK, Json(popup)))
+    Ok((StatusCode::OK, Json(popup.localize(&locales))))
 }
@@ -1,31 +1,124 @@
-use axum::{body::Body, extract::Request, middleware::Next, response::Response};
+use std::sync::Arc;
+
+use axum::{
+    body::{Body, to_bytes},
+    extract::{Request, State},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use hmac::{Hmac, Mac};
 use reqwest::StatusCode;
-use tracing::info;
+use sha2::Sha256;
+use tracing::{info, warn};
 
 use crate::{
+    api::auth_mw::insert_context,
     config::config::CONFIG,
-    models::{error::ServerError, integration::IntegrationName, user::SubjectId},
-    service::util::extract_header,
+    models::{
+        app_state::AppState, auth::Claims, error::ServerError, integration::IntegrationName,
+        user::SubjectId,
+    },
+    service::{trace_context::TraceContext, util::extract_header},
 };
 
-static AUTH0_WEBHOOK_KEY: &str = "Auth0-Webhook-Key";
+static AUTH0_SIGNATURE: &str = "x-auth0-signature";
+static STRIPE_WEBHOOK_KEY: &str = "Stripe-Webhook-Key";
+static IDEMPOTENCY_KEY: &str = "x-idempotency-key";
+static REQUEST_ID: &str = "x-request-id";
+static MAX_WEBHOOK_BODY_BYTES: usize = 1024 * 1024;
+
+pub async fn webhook_mw(
+    State(state): State<Arc<AppState>>,
+    req: Request<Body>,
+    next: Next,
+) -> Result<Response, ServerError> {
+    let (mut req, integration) =
+        if let Some(signature) = extract_header(AUTH0_SIGNATURE, req.headers()) {
+            (
+                verify_auth0_request(req, &signature).await?,
+                IntegrationName::from(IntegrationName::AUTH0),
+            )
+        } else if let Some(header) = extract_header(STRIPE_WEBHOOK_KEY, req.headers()) {
+            let valid_key = CONFIG.stripe.webhook_key.as_ref();
+            if valid_key.is_none_or(|key| *key != header) {
+                return Err(ServerError::Api(
+                    StatusCode::UNAUTHORIZED,
+                    "Invalid webhook key".into(),
+                ));
+            }
+            (req, IntegrationName::from(IntegrationName::STRIPE))
+        } else {
+            return Err(ServerError::Api(
+                StatusCode::UNAUTHORIZED,
+                "Webhook signature not present".into(),
+            ));
+        };
+
+    let idempotency_key = extract_header(IDEMPOTENCY_KEY, req.headers())
+        .or_else(|| extract_header(REQUEST_ID, req.headers()))
+        .map(|key| format!("{}:{}", integration, key));
+
+    let vault = state.get_idempotency_vault();
+    if let Some(key) = &idempotency_key
+        && vault.check_and_insert(key)
+    {
+        info!("Skipping already delivered webhook: {}", key);
+        return Ok(StatusCode::OK.into_response());
+    }
+
+    let subject = SubjectId::Integration(integration);
+    info!("Request by subject: {:?}", subject);
+    let request_id = TraceContext::current()
+        .unwrap_or_else(TraceContext::new_root)
+        .correlation_id();
+    insert_context(&mut req, subject, Claims::empty(), request_id);
+
+    let response = next.run(req).await;
 
-pub async fn webhook_mw(mut req: Request<Body>, next: Next) -> Result<Response, ServerError> {
-    let webhook_header = extract_header(&AUTH0_WEBHOOK_KEY, req.headers()).ok_or_else(|| {
-        ServerError::Api(StatusCode::UNAUTHORIZED, "Webhook key not present".into())
+    // Failed deliveries must stay retryable
+    if let Some(key) = &idempotency_key
+        && !response.status().is_success()
+    {
+        vault.remove(key);
+    }
+
+    Ok(response)
+}
+
+async fn verify_auth0_request(
+    req: Request<Body>,
+    signature: &str,
+) -> Result<Request<Body>, ServerError> {
+    let (parts, body) = req.into_parts();
+    let bytes = to_bytes(body, MAX_WEBHOOK_BODY_BYTES).await.map_err(|e| {
+        ServerError::Api(
+            StatusCode::BAD_REQUEST,
+            format!("Failed to read webhook body: {}", e),
+        )
     })?;
 
-    let valid_key = CONFIG.auth0.webhook_key.to_string();
-    if valid_key != webhook_header {
+    if !verify_signature(&bytes, &CONFIG.auth0.webhook_key, signature) {
+        warn!("Rejected Auth0 webhook with invalid signature");
         return Err(ServerError::Api(
             StatusCode::UNAUTHORIZED,
-            "Invalid webhook key".into(),
+            "Invalid webhook signature".into(),
         ));
     }
 
-    let subject = SubjectId::Integration(IntegrationName::Auth0);
-    info!("Request by subject: {:?}", subject);
-    req.extensions_mut().insert(subject);
+    // Put the buffered body back so the handler can still deserialize it
+    Ok(Request::from_parts(parts, Body::from(bytes)))
+}
+
+/// Checks a hex encoded HMAC-SHA256 of `body` keyed with `key`, compared in constant time
+pub fn verify_signature(body: &[u8], key: &str, signature: &str) -> bool {
+    let Ok(signature) = hex::decode(signature.trim()) else {
+        return false;
+    };
+
+    let Ok(mut mac) = Hmac::<Sha256>::new_from_slice(key.as_bytes()) else {
+        return false;
+    };
 
-    Ok(next.run(req).await)
+    mac.update(body);
+    mac.verify_slice(&signature).is_ok()
 }
@@ -1,8 +1,14 @@
-use reqwest::{Client, StatusCode};
+use chrono::{DateTime, Utc};
+use reqwest::{Client, StatusCode, header::HeaderMap};
 use serde::{Deserialize, Serialize};
 use tracing::{error, info};
+use utoipa::ToSchema;
 
-use crate::models::game_base::InteractiveEnvelope;
+use crate::{
+    config::config::CONFIG,
+    models::game_base::{GameType, InteractiveEnvelope},
+    service::{key_vault::KEY_TTL, trace_context::TraceContext},
+};
 
 #[derive(Debug, thiserror::Error)]
 pub enum GSClientError {
@@ -22,10 +28,40 @@ pub enum GSClientError {
     Serialize(#[from] serde_json::Error),
 }
 
-#[derive(Debug, Serialize, Deserialize)]
-pub struct InteractiveGameResponse {
+/// Everything a client needs to connect to an interactive game, returned by
+/// the create, initiate and join endpoints
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, ToSchema)]
+pub struct GameSessionDescriptor {
     pub key_word: String,
     pub hub_address: String,
+    /// `wss` when the game server is served over https, `ws` otherwise
+    pub websocket_scheme: String,
+    /// When the key is freed if the game never reports back
+    pub expires_at: DateTime<Utc>,
+}
+
+impl GameSessionDescriptor {
+    /// For a key handed out just now
+    pub fn new(game_type: &GameType, key_word: impl Into<String>) -> Self {
+        let domain = &CONFIG.server.gs_domain;
+        let websocket_scheme = match domain.starts_with("https://") {
+            true => "wss",
+            false => "ws",
+        };
+
+        Self {
+            key_word: key_word.into(),
+            hub_address: format!("{}hubs/{}", domain, game_type.column_value()),
+            websocket_scheme: websocket_scheme.into(),
+            expires_at: Utc::now() + KEY_TTL,
+        }
+    }
+
+    /// For a key handed out earlier, e.g. when joining
+    pub fn created_at(mut self, created_at: DateTime<Utc>) -> Self {
+        self.expires_at = created_at + KEY_TTL;
+        self
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -56,8 +92,7 @@ impl GSClient {
         client: &Client,
         envelope: &InteractiveEnvelope,
     ) -> Result<(), GSClientError> {
-        let uri = format!("{}session/create", self.domain);
-        self.send_json(client, &uri, envelope).await
+        self.send_json(client, "session/create", envelope).await
     }
 
     pub async fn initiate_game_session(
@@ -65,8 +100,7 @@ impl GSClient {
         client: &Client,
         envelope: &InteractiveEnvelope,
     ) -> Result<(), GSClientError> {
-        let uri = "/games/initiate".to_string();
-        self.send_json(client, &uri, envelope).await
+        self.send_json(client, "games/initiate", envelope).await
     }
 
     async fn send_json<T: Serialize>(
@@ -76,10 +110,17 @@ impl GSClient {
         body: T,
     ) -> Result<(), GSClientError> {
         info!("GSClient sending request to: {}", uri);
-        let url = format!("{}/{}", self.domain, uri);
+        // The configured domain always ends with a slash
+        let url = format!("{}{}", self.domain, uri);
+        let mut headers = HeaderMap::new();
+        if let Some(cx) = TraceContext::current() {
+            cx.inject(&mut headers);
+        }
+
         let response = client
             .post(&url)
             .header("content-type", "application/json")
+            .headers(headers)
             .json(&body)
             .send()
             .await?;
@@ -1,19 +1,59 @@
 use core::fmt;
-use std::env;
+use std::{
+    collections::HashMap,
+    env,
+    net::{IpAddr, Ipv4Addr, Ipv6Addr},
+};
 
 use config::{Config, ConfigError, Environment, File};
 use once_cell::sync::Lazy;
+use reqwest::Url;
 use serde::{Deserialize, Serialize};
 use tracing::debug;
 
 pub static CONFIG: Lazy<AppConfig> =
     Lazy::new(|| AppConfig::load().unwrap_or_else(|e| panic!("{}", e)));
 
+/// Overrides `src/config/{environment}.toml`, so images can ship without the
+/// source tree
+static CONFIG_PATH_VAR: &str = "TERO_CONFIG_PATH";
+
+// Fails the build when a runtime has no config file, a renamed file would
+// otherwise only show up as a panic on startup
+const _: [&str; 3] = [
+    include_str!("development.toml"),
+    include_str!("staging.toml"),
+    include_str!("production.toml"),
+];
+
+/// Every problem found by `AppConfig::validate`, one per line
+#[derive(Debug, thiserror::Error)]
+#[error("Invalid configuration:\n  - {}", .0.join("\n  - "))]
+pub struct ConfigErrors(pub Vec<String>);
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct AppConfig {
     pub server: ServerConfig,
     pub auth0: Auth0Config,
     pub database_url: String,
+    /// Read replica for the heavy read endpoints, the primary is used when unset
+    pub database_read_url: Option<String>,
+    #[serde(default)]
+    pub database: DatabaseConfig,
+    #[serde(default)]
+    pub log_retention: LogRetentionConfig,
+    #[serde(default)]
+    pub stripe: StripeConfig,
+    #[serde(default)]
+    pub moderation: ModerationConfig,
+    #[serde(default)]
+    pub features: FeatureFlags,
+    #[serde(default)]
+    pub metrics: MetricsConfig,
+    #[serde(default)]
+    pub guest: GuestConfig,
+    #[serde(default)]
+    pub cors: CorsConfig,
 }
 
 fn default_address() -> String {
@@ -28,6 +68,24 @@ fn default_page_size() -> u8 {
     20
 }
 
+fn default_game_retention_days() -> u32 {
+    90
+}
+
+fn default_pseudo_user_retention_days() -> u32 {
+    180
+}
+
+// 256 KB
+fn default_max_body_bytes() -> usize {
+    256 * 1024
+}
+
+// 2 MB
+fn default_max_envelope_bytes() -> usize {
+    2 * 1024 * 1024
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct ServerConfig {
     #[serde(default = "default_address")]
@@ -37,6 +95,113 @@ pub struct ServerConfig {
     pub gs_domain: String,
     #[serde(default = "default_page_size")]
     pub page_size: u8,
+    /// Fallback for game types missing from `game_retention`
+    #[serde(default = "default_game_retention_days")]
+    pub game_retention_days: u32,
+    /// Retention days keyed by game type, e.g. `quiz` or `spin`
+    #[serde(default)]
+    pub game_retention: HashMap<String, u32>,
+    /// Only count the inactive games instead of deleting them
+    #[serde(default)]
+    pub game_cleanup_dry_run: bool,
+    /// Unlinked pseudo users without games are deleted after this many
+    /// inactive days
+    #[serde(default = "default_pseudo_user_retention_days")]
+    pub pseudo_user_retention_days: u32,
+    /// Largest request body accepted by the JSON extractors
+    #[serde(default = "default_max_body_bytes")]
+    pub max_body_bytes: usize,
+    /// Limit for the persist routes, where finished sessions are sent whole
+    #[serde(default = "default_max_envelope_bytes")]
+    pub max_envelope_bytes: usize,
+    /// OTLP/HTTP traces endpoint, e.g. `http://localhost:4318/v1/traces`.
+    /// Spans are only exported when set
+    #[serde(default)]
+    pub otel_endpoint: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct DatabaseConfig {
+    #[serde(default = "default_max_connections")]
+    pub max_connections: u32,
+    #[serde(default)]
+    pub min_connections: u32,
+    #[serde(default = "default_acquire_timeout_secs")]
+    pub acquire_timeout_secs: u64,
+    /// Idle connections above `min_connections` are closed after this long
+    #[serde(default = "default_idle_timeout_secs")]
+    pub idle_timeout_secs: u64,
+    /// Attempts at reaching the database on startup before giving up
+    #[serde(default = "default_connect_attempts")]
+    pub connect_attempts: u32,
+    /// Delay after the first failed attempt, doubled for every retry
+    #[serde(default = "default_retry_backoff_ms")]
+    pub retry_backoff_ms: u64,
+}
+
+fn default_max_connections() -> u32 {
+    10
+}
+
+fn default_acquire_timeout_secs() -> u64 {
+    30
+}
+
+fn default_idle_timeout_secs() -> u64 {
+    600
+}
+
+fn default_connect_attempts() -> u32 {
+    10
+}
+
+fn default_retry_backoff_ms() -> u64 {
+    500
+}
+
+impl Default for DatabaseConfig {
+    fn default() -> Self {
+        Self {
+            max_connections: default_max_connections(),
+            min_connections: 0,
+            acquire_timeout_secs: default_acquire_timeout_secs(),
+            idle_timeout_secs: default_idle_timeout_secs(),
+            connect_attempts: default_connect_attempts(),
+            retry_backoff_ms: default_retry_backoff_ms(),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct LogRetentionConfig {
+    #[serde(default = "default_info_retention_days")]
+    pub info_days: u32,
+    #[serde(default = "default_warning_retention_days")]
+    pub warning_days: u32,
+    #[serde(default = "default_critical_retention_days")]
+    pub critical_days: u32,
+}
+
+fn default_info_retention_days() -> u32 {
+    30
+}
+
+fn default_warning_retention_days() -> u32 {
+    90
+}
+
+fn default_critical_retention_days() -> u32 {
+    365
+}
+
+impl Default for LogRetentionConfig {
+    fn default() -> Self {
+        Self {
+            info_days: default_info_retention_days(),
+            warning_days: default_warning_retention_days(),
+            critical_days: default_critical_retention_days(),
+        }
+    }
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -44,11 +209,96 @@ pub struct Auth0Config {
     pub domain: String,
     pub audience: String,
     pub webhook_key: String,
+    /// Clock skew tolerated on `exp`, `nbf` and `iat`
+    #[serde(default = "default_jwt_leeway_secs")]
+    pub jwt_leeway_secs: u64,
+}
+
+fn default_jwt_leeway_secs() -> u64 {
+    30
+}
+
+#[derive(Debug, Serialize, Deserialize, Default)]
+pub struct StripeConfig {
+    pub webhook_key: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ModerationConfig {
+    /// Number of distinct reports before a game is hidden from the game pages
+    #[serde(default = "default_report_threshold")]
+    pub report_threshold: u16,
+}
+
+fn default_report_threshold() -> u16 {
+    5
+}
+
+impl Default for ModerationConfig {
+    fn default() -> Self {
+        Self {
+            report_threshold: default_report_threshold(),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct MetricsConfig {
+    /// Peers that may scrape `/metrics`
+    #[serde(default = "default_metrics_allowed_ips")]
+    pub allowed_ips: Vec<IpAddr>,
+}
+
+fn default_metrics_allowed_ips() -> Vec<IpAddr> {
+    vec![
+        IpAddr::V4(Ipv4Addr::LOCALHOST),
+        IpAddr::V6(Ipv6Addr::LOCALHOST),
+    ]
+}
+
+impl Default for MetricsConfig {
+    fn default() -> Self {
+        Self {
+            allowed_ips: default_metrics_allowed_ips(),
+        }
+    }
+}
+
+/// Browser origins allowed to call the api, none unless configured
+#[derive(Debug, Serialize, Deserialize, Default)]
+pub struct CorsConfig {
+    #[serde(default)]
+    pub allowed_origins: Vec<String>,
+    #[serde(default)]
+    pub allow_credentials: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize, Default)]
+pub struct GuestConfig {
+    /// Key for signing the guest tokens handed out when ensuring a pseudo user
+    pub signing_key: Option<String>,
+    /// Reject unsigned guest ids, turn on once every client sends signed tokens
+    #[serde(default)]
+    pub enforce_signature: bool,
+}
+
+/// Toggles read from the `[features]` table, e.g. `TERO__FEATURES__CACHE_WARMUP=true`
+#[derive(Debug, Serialize, Deserialize, Default)]
+#[serde(transparent)]
+pub struct FeatureFlags(HashMap<String, bool>);
+
+impl FeatureFlags {
+    /// Flags that are not configured are disabled
+    pub fn is_enabled(&self, flag: &str) -> bool {
+        self.0.get(flag).copied().unwrap_or(false)
+    }
 }
 
 #[derive(Debug, Serialize, Deserialize)]
 pub enum RunTime {
     Development,
+    /// Production settings, but reachable from outside the host
+    Staging,
     Production,
 }
 
@@ -56,6 +306,7 @@ impl fmt::Display for RunTime {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
             RunTime::Development => write!(f, "development"),
+            RunTime::Staging => write!(f, "staging"),
             RunTime::Production => write!(f, "production"),
         }
     }
@@ -68,18 +319,22 @@ impl AppConfig {
             .as_str()
         {
             "DEVELOPMENT" => RunTime::Development,
+            "STAGING" => RunTime::Staging,
             "PRODUCTION" => RunTime::Production,
-            _ => panic!("Invalid environment set, must be either `DEVELOPMENT` or ´PRODUCTION´"),
+            _ => panic!(
+                "Invalid environment set, must be one of `DEVELOPMENT`, `STAGING` or `PRODUCTION`"
+            ),
         };
 
-        let config: AppConfig = Config::builder()
-            .add_source(File::with_name(&format!(
-                "src/config/{}.toml",
-                runtime.to_string()
-            )))
-            .add_source(Environment::with_prefix("TERO").separator("__"))
-            .build()?
-            .try_deserialize()?;
+        let path =
+            env::var(CONFIG_PATH_VAR).unwrap_or_else(|_| format!("src/config/{runtime}.toml"));
+
+        let config = Self::from_config(
+            Config::builder()
+                .add_source(File::with_name(&path))
+                .add_source(Environment::with_prefix("TERO").separator("__"))
+                .build()?,
+        )?;
 
         debug!(
             "Loaded config: {}",
@@ -88,4 +343,77 @@ impl AppConfig {
 
         Ok(config)
     }
+
+    /// Urls are joined with paths as `{domain}path`, so the domains always
+    /// end with a slash
+    pub fn from_config(config: Config) -> Result<Self, ConfigError> {
+        let mut config: AppConfig = config.try_deserialize()?;
+        for domain in [&mut config.server.gs_domain, &mut config.auth0.domain] {
+            if !domain.ends_with('/') {
+                domain.push('/');
+            }
+        }
+
+        Ok(config)
+    }
+
+    pub fn validate(&self) -> Result<(), ConfigErrors> {
+        let mut errors = Vec::new();
+
+        for (key, value) in [
+            ("server.gs_domain", &self.server.gs_domain),
+            ("auth0.domain", &self.auth0.domain),
+        ] {
+            if let Err(e) = check_url(value, &["http", "https"]) {
+                errors.push(format!("{} `{}` {}", key, value, e));
+            }
+        }
+
+        if let Some(endpoint) = &self.server.otel_endpoint
+            && let Err(e) = check_url(endpoint, &["http", "https"])
+        {
+            errors.push(format!("server.otel_endpoint `{}` {}", endpoint, e));
+        }
+
+        let database_urls = [
+            ("database_url", Some(&self.database_url)),
+            ("database_read_url", self.database_read_url.as_ref()),
+        ];
+        for (key, value) in database_urls {
+            let Some(value) = value else { continue };
+            if let Err(e) = check_url(value, &["postgres", "postgresql"]) {
+                // The url holds the password, so leave it out of the message
+                errors.push(format!("{} {}", key, e));
+            }
+        }
+
+        match self.server.port.parse::<u16>() {
+            Ok(0) | Err(_) => errors.push(format!(
+                "server.port `{}` must be a number between 1 and 65535",
+                self.server.port
+            )),
+            Ok(_) => {}
+        }
+
+        if self.server.page_size == 0 {
+            errors.push("server.page_size must be greater than 0".into());
+        }
+
+        match errors.is_empty() {
+            true => Ok(()),
+            false => Err(ConfigErrors(errors)),
+        }
+    }
+}
+
+fn check_url(value: &str, schemes: &[&str]) -> Result<(), String> {
+    let url = Url::parse(value).map_err(|e| format!("is not a valid url: {}", e))?;
+    if !schemes.contains(&url.scheme()) {
+        return Err(format!(
+            "must use one of the schemes {}",
+            schemes.join(", ")
+        ));
+    }
+
+    Ok(())
 }
@@ -1,159 +1,459 @@
+use std::collections::HashMap;
+
+use axum::http::StatusCode;
 use chrono::{Duration, Utc};
-use sqlx::{Pool, Postgres};
-use tracing::warn;
+use sqlx::{Pool, Postgres, Transaction};
+use tracing::{info, warn};
 use uuid::Uuid;
 
 use crate::{
     config::config::CONFIG,
     models::{
         error::ServerError,
-        game_base::{GameBase, GamePageQuery, GameType, SavedGamesPageQuery},
+        game_base::{
+            GameBase, GamePageItem, GamePageQuery, GameType, GameVisibility, SavedGame,
+            SavedGameSort, SavedGamesPageQuery, UserGamesPageQuery,
+        },
         popup_manager::PagedResponse,
     },
+    service::db_query_builder::DBQueryBuilder,
 };
 
-pub async fn delete_non_active_games(pool: &Pool<Postgres>) -> Result<(), sqlx::Error> {
-    let timeout = Utc::now() - Duration::days(24);
-    sqlx::query!(
+/// Deletes games not played within the retention window of their game type
+/// and returns how many were removed per type, a dry run only counts them.
+/// Types missing from `retention` use `default_days`
+pub async fn delete_non_active_games(
+    pool: &Pool<Postgres>,
+    retention: &HashMap<String, u32>,
+    default_days: u32,
+    dry_run: bool,
+) -> Result<HashMap<GameType, u64>, sqlx::Error> {
+    let mut counts = HashMap::new();
+
+    for game_type in GameType::ALL {
+        let days = retention
+            .get(game_type.column_value())
+            .copied()
+            .unwrap_or(default_days);
+        let timeout = Utc::now() - Duration::days(days as i64);
+
+        let count = match dry_run {
+            true => {
+                sqlx::query_scalar!(
+                    r#"
+                    SELECT COUNT(*) as "count!"
+                    FROM "game_base"
+                    WHERE game_type = $1 AND last_played < $2
+                    "#,
+                    game_type.clone() as _,
+                    timeout
+                )
+                .fetch_one(pool)
+                .await? as u64
+            }
+            false => sqlx::query!(
+                r#"
+                DELETE FROM "game_base"
+                WHERE game_type = $1 AND last_played < $2
+                "#,
+                game_type.clone() as _,
+                timeout
+            )
+            .execute(pool)
+            .await?
+            .rows_affected(),
+        };
+
+        info!(
+            "Inactive {} games older than {} days: {} (dry run: {})",
+            game_type.column_value(),
+            days,
+            count,
+            dry_run
+        );
+        counts.insert(game_type, count);
+    }
+
+    Ok(counts)
+}
+
+pub async fn game_base_exists(pool: &Pool<Postgres>, id: Uuid) -> Result<bool, ServerError> {
+    let exists = sqlx::query_scalar!(
         r#"
-        DELETE FROM "game_base"
-        WHERE last_played < $1
+        SELECT EXISTS(SELECT 1 FROM "game_base" WHERE id = $1) as "exists!"
         "#,
-        timeout
+        id
     )
-    .execute(pool)
+    .fetch_one(pool)
     .await?;
 
-    Ok(())
+    Ok(exists)
 }
 
-pub async fn get_game_page(
-    pool: &Pool<Postgres>,
-    request: &GamePageQuery,
-) -> Result<PagedResponse<GameBase>, sqlx::Error> {
-    let page_size = CONFIG.server.page_size as u16;
-    let limit = page_size + 1;
-    let offset = page_size * request.page_num;
-
-    let category = match &request.category {
-        Some(category) => format!("AND category = '{}'", category),
-        None => "".to_string(),
-    };
+#[derive(sqlx::FromRow)]
+struct GameRow {
+    #[sqlx(flatten)]
+    game: GameBase,
+    created_by: Option<Uuid>,
+}
 
-    let query = format!(
+/// Returns the game together with its owner, used for access checks
+pub async fn get_game_by_id(
+    pool: &Pool<Postgres>,
+    id: Uuid,
+) -> Result<(GameBase, Option<Uuid>), ServerError> {
+    let row = DBQueryBuilder::select(
         r#"
-        SELECT 
             id,
             name,
             description,
             game_type,
             category,
             iterations,
+            total_rounds_played,
             times_played,
-            last_played
-        FROM "game_base"
-        WHERE game_type = '{}' {}
-        ORDER BY times_played DESC
-        LIMIT {} OFFSET {}
+            last_played,
+            visibility,
+            created_by
+        "#,
+        &[],
+    )
+    .from("game_base")
+    .r#where("id", id)
+    .build()
+    .build_query_as::<GameRow>()
+    .fetch_optional(pool)
+    .await?
+    .ok_or(ServerError::NotFound(format!(
+        "Game with id {} does not exist",
+        id
+    )))?;
+
+    Ok((row.game, row.created_by))
+}
+
+static GAME_BASE_SAFE_COLUMNS: &[&str] =
+    &["times_played", "last_played", "name", "recent_plays", "id"];
+
+static GAME_PLAY_RETENTION_DAYS: i64 = 90;
+
+static RECENT_PLAYS_SUBQUERY: &str = r#"(
+    SELECT base_id, COUNT(*) AS plays
+    FROM "game_play"
+    WHERE played_at >= NOW() - INTERVAL '7 days'
+    GROUP BY base_id
+) recent"#;
+
+static SAVES_SUBQUERY: &str = r#"(
+    SELECT base_id, COUNT(*) AS saves
+    FROM "saved_game"
+    GROUP BY base_id
+) saved"#;
+
+pub async fn get_game_page(
+    pool: &Pool<Postgres>,
+    request: &GamePageQuery,
+) -> Result<PagedResponse<GamePageItem>, sqlx::Error> {
+    let page_size = CONFIG.server.page_size as u16;
+
+    // Qualified since `base_user` has an id as well
+    let builder = DBQueryBuilder::select(
+        r#"
+            game_base.id,
+            game_base.name,
+            game_base.description,
+            game_base.game_type,
+            game_base.category,
+            game_base.iterations,
+            game_base.total_rounds_played,
+            game_base.times_played,
+            game_base.last_played,
+            game_base.visibility,
+            COALESCE(recent.plays, 0) AS recent_plays,
+            COALESCE(saved.saves, 0) AS saves,
+            creator.username AS creator_name
         "#,
-        request.game_type.column_name(),
-        category,
-        limit,
-        offset
-    );
+        GAME_BASE_SAFE_COLUMNS,
+    )
+    .from("game_base")
+    .left_join(RECENT_PLAYS_SUBQUERY, "recent.base_id = game_base.id")
+    .left_join(SAVES_SUBQUERY, "saved.base_id = game_base.id")
+    .left_join(
+        r#""base_user" creator"#,
+        "creator.id = game_base.created_by",
+    )
+    .r#where("game_type", request.game_type.clone())
+    .r#where("hidden", false)
+    .r#where("visibility", GameVisibility::Public)
+    .where_opt("category", request.category.clone());
+
+    // Games without recent plays fall back to their all time plays
+    let builder = match request.trending {
+        true => builder
+            .order_desc("recent_plays")
+            .order_desc("times_played"),
+        false => builder.order_desc("times_played"),
+    };
 
-    let mut games = sqlx::query_as::<_, GameBase>(&query)
+    // Ties must keep a stable order or offset paging skips and repeats games
+    let mut games = builder
+        .order_asc("id")
+        .limit(page_size + 1)
+        .offset(i64::from(page_size) * i64::from(request.page_num))
+        .build()
+        .build_query_as::<GamePageItem>()
         .fetch_all(pool)
         .await?;
 
     let has_next = games.len() > page_size as usize;
-    games.pop();
+    if has_next {
+        games.pop();
+    }
     let page = PagedResponse::new(games, has_next);
 
     Ok(page)
 }
 
-pub async fn increment_times_played(
+/// Counts another finished session of a stored game and records the play for
+/// the trending page. `rounds_played` replaces `iterations` and is added to
+/// `total_rounds_played`
+pub async fn record_session_played(
     pool: &Pool<Postgres>,
     game_type: GameType,
     game_id: Uuid,
+    user_id: Option<Uuid>,
+    rounds_played: i32,
 ) -> Result<(), ServerError> {
-    let query = format!(
+    let mut tx = pool.begin().await?;
+
+    let row = sqlx::query!(
         r#"
-        UPDATE {}
-        SET times_played = times_played + 1, last_played = $1
-        WHERE id = $2
+        UPDATE "game_base"
+        SET times_played = times_played + 1,
+            last_played = $1,
+            iterations = $2::INTEGER,
+            total_rounds_played = total_rounds_played + $2::INTEGER
+        WHERE id = $3 AND game_type = $4
         "#,
-        game_type.column_name()
-    );
-
-    let row = sqlx::query(&query)
-        .bind(Utc::now())
-        .bind(game_id)
-        .execute(pool)
-        .await?;
+        Utc::now(),
+        rounds_played,
+        game_id,
+        game_type as _
+    )
+    .execute(&mut *tx)
+    .await?;
 
     if row.rows_affected() == 0 {
         warn!("Query failed, no game with id: {}", game_id);
         return Err(ServerError::NotFound("Game does not exist".into()));
     }
 
+    tx_record_game_play(&mut tx, game_id, user_id).await?;
+    tx.commit().await?;
+
     Ok(())
 }
 
+pub async fn tx_record_game_play(
+    tx: &mut Transaction<'_, Postgres>,
+    game_id: Uuid,
+    user_id: Option<Uuid>,
+) -> Result<(), ServerError> {
+    sqlx::query!(
+        r#"
+        INSERT INTO "game_play" (id, base_id, user_id, played_at)
+        VALUES ($1, $2, $3, $4)
+        "#,
+        Uuid::new_v4(),
+        game_id,
+        user_id,
+        Utc::now()
+    )
+    .execute(&mut **tx)
+    .await?;
+
+    Ok(())
+}
+
+pub async fn delete_expired_game_plays(pool: &Pool<Postgres>) -> Result<u64, sqlx::Error> {
+    let cutoff = Utc::now() - Duration::days(GAME_PLAY_RETENTION_DAYS);
+    let row = sqlx::query!(
+        r#"
+        DELETE FROM "game_play"
+        WHERE played_at < $1
+        "#,
+        cutoff
+    )
+    .execute(pool)
+    .await?;
+
+    Ok(row.rows_affected())
+}
+
+/// When `owner` is given only a game created by that user is deleted
 pub async fn delete_game(
     pool: &Pool<Postgres>,
     game_type: &GameType,
     id: Uuid,
+    owner: Option<Uuid>,
 ) -> Result<(), ServerError> {
-    let query = format!(
+    let row = sqlx::query!(
         r#"
-        DELETE FROM {}
+        DELETE FROM "game_base"
         WHERE id = $1
+          AND game_type = $2
+          AND ($3::uuid IS NULL OR created_by = $3)
         "#,
-        game_type.column_name()
-    );
+        id,
+        game_type as _,
+        owner
+    )
+    .execute(pool)
+    .await?;
 
-    let row = sqlx::query(&query).bind(id).execute(pool).await?;
     if row.rows_affected() == 0 {
         warn!("Query failed, no game with id: {}", id);
-        return Err(ServerError::Internal("Failed to delete game".into()));
+        return Err(ServerError::NotFound("Game does not exist".into()));
     }
 
     Ok(())
 }
 
+/// Moves the games created by a pseudo user over to the base user it
+/// registered as
+pub async fn tx_reassign_game_owner(
+    tx: &mut Transaction<'_, Postgres>,
+    from: Uuid,
+    to: Uuid,
+) -> Result<u64, ServerError> {
+    let row = sqlx::query!(
+        r#"
+        UPDATE "game_base"
+        SET created_by = $2
+        WHERE created_by = $1
+        "#,
+        from,
+        to
+    )
+    .execute(&mut **tx)
+    .await?;
+
+    Ok(row.rows_affected())
+}
+
+pub async fn is_game_saved(
+    pool: &Pool<Postgres>,
+    user_id: Uuid,
+    base_id: Uuid,
+) -> Result<bool, sqlx::Error> {
+    let saved = sqlx::query_scalar!(
+        r#"
+        SELECT EXISTS (
+            SELECT 1 FROM "saved_game" WHERE user_id = $1 AND base_id = $2
+        ) AS "saved!"
+        "#,
+        user_id,
+        base_id
+    )
+    .fetch_one(pool)
+    .await?;
+
+    Ok(saved)
+}
+
+/// Saves every accessible game in `base_ids` for the user, returns the number
+/// of new rows. Unknown, private and already saved games are skipped
+pub async fn bulk_save_games(
+    pool: &Pool<Postgres>,
+    user_id: Uuid,
+    base_ids: &[Uuid],
+) -> Result<u64, ServerError> {
+    let row = sqlx::query!(
+        r#"
+        INSERT INTO "saved_game" (id, user_id, base_id)
+        SELECT uuid_generate_v4(), $1, base.id
+        FROM (SELECT DISTINCT unnest($2::uuid[]) AS base_id) requested
+        INNER JOIN "game_base" base ON base.id = requested.base_id
+        WHERE base.visibility <> 'private' OR base.created_by = $1
+        ON CONFLICT (user_id, base_id) DO NOTHING
+        "#,
+        user_id,
+        base_ids
+    )
+    .execute(pool)
+    .await?;
+
+    Ok(row.rows_affected())
+}
+
+pub async fn bulk_delete_saved_games(
+    pool: &Pool<Postgres>,
+    user_id: Uuid,
+    base_ids: &[Uuid],
+) -> Result<u64, ServerError> {
+    let row = sqlx::query!(
+        r#"
+        DELETE FROM "saved_game"
+        WHERE user_id = $1 AND base_id = ANY($2::uuid[])
+        "#,
+        user_id,
+        base_ids
+    )
+    .execute(pool)
+    .await?;
+
+    Ok(row.rows_affected())
+}
+
+/// Saves a game for the user, the existence check and insert run as one
+/// statement so a game deleted in between can not slip through
 pub async fn save_game(
     pool: &Pool<Postgres>,
     user_id: Uuid,
-    game_id: Uuid,
+    base_id: Uuid,
 ) -> Result<(), ServerError> {
-    use tracing::warn;
     let id = Uuid::new_v4();
     let row = sqlx::query!(
         r#"
-        INSERT INTO "saved_game" (id, user_id, base_id)
-        VALUES ($1, $2, $3)
-        ON CONFLICT (user_id, base_id) DO NOTHING
+        WITH base AS (
+            SELECT id FROM "game_base" WHERE id = $3
+        ),
+        inserted AS (
+            INSERT INTO "saved_game" (id, user_id, base_id)
+            SELECT $1, $2, id FROM base
+            ON CONFLICT (user_id, base_id) DO NOTHING
+            RETURNING id
+        )
+        SELECT
+            EXISTS (SELECT 1 FROM base) AS "found!",
+            EXISTS (SELECT 1 FROM inserted) AS "inserted!"
         "#,
         id,
         user_id,
-        game_id
+        base_id
     )
-    .execute(pool)
+    .fetch_one(pool)
     .await?;
 
-    if row.rows_affected() == 0 {
-        warn!("User has already saved this game");
+    match (row.found, row.inserted) {
+        (false, _) => Err(ServerError::NotFound(format!(
+            "Game with id {} does not exist",
+            base_id
+        ))),
+        (true, false) => Err(ServerError::Api(
+            StatusCode::CONFLICT,
+            "Game is already saved".into(),
+        )),
+        (true, true) => Ok(()),
     }
-
-    Ok(())
 }
 
+/// Removes a saved game, `saved_game.base_id` references `game_base` so the
+/// game type is not needed
 pub async fn delete_saved_game(
     pool: &Pool<Postgres>,
     user_id: Uuid,
-    game_id: Uuid,
+    base_id: Uuid,
 ) -> Result<(), ServerError> {
     let row = sqlx::query!(
         r#"
@@ -161,55 +461,114 @@ pub async fn delete_saved_game(
         WHERE user_id = $1 AND base_id = $2
         "#,
         user_id,
-        game_id
+        base_id
     )
     .execute(pool)
     .await?;
 
+    // Nothing was saved, which is the caller's mistake and not ours
     if row.rows_affected() == 0 {
-        return Err(ServerError::Internal(
-            "Failed to delete from table `saved_game`".into(),
-        ));
+        return Err(ServerError::NotFound(format!(
+            "Game with id {} is not saved",
+            base_id
+        )));
     }
 
     Ok(())
 }
 
+static SAVED_GAME_SAFE_COLUMNS: &[&str] = &["saved.saved_at", "base.times_played"];
+
 pub async fn get_saved_games_page(
     pool: &Pool<Postgres>,
     user_id: Uuid,
     query: SavedGamesPageQuery,
-) -> Result<PagedResponse<GameBase>, ServerError> {
-    let page_size = CONFIG.server.page_size;
-    let limit = page_size + 1;
-    let offset = query.page_num * page_size;
+) -> Result<PagedResponse<SavedGame>, ServerError> {
+    let page_size = CONFIG.server.page_size as u16;
 
-    let query = format!(
+    let builder = DBQueryBuilder::select(
         r#"
-        SELECT
             base.id,
             base.name,
             base.description,
             base.game_type,
             base.category,
             base.iterations,
+            base.total_rounds_played,
             base.times_played,
-            base.last_played
-        FROM "game_base" base
-        JOIN "saved_game" saved
-        ON base.id = saved.base_id
-        WHERE saved.user_id = $1
-        LIMIT {} OFFSET {}
+            base.last_played,
+            base.visibility,
+            saved.saved_at
         "#,
-        limit, offset
-    );
+        SAVED_GAME_SAFE_COLUMNS,
+    )
+    .from(r#""game_base" base"#)
+    .inner_join(r#""saved_game" saved"#, "base.id = saved.base_id")
+    .r#where("saved.user_id", user_id)
+    .where_opt("base.game_type", query.game_type)
+    .where_opt("base.category", query.category);
 
-    let games = sqlx::query_as::<_, GameBase>(&query)
-        .bind(&user_id)
+    let builder = match query.sort.unwrap_or_default() {
+        SavedGameSort::SavedAt => builder.order_desc("saved.saved_at"),
+        SavedGameSort::TimesPlayed => builder
+            .order_desc("base.times_played")
+            .order_desc("saved.saved_at"),
+    };
+
+    let mut games = builder
+        .limit(page_size + 1)
+        .offset(i64::from(page_size) * i64::from(query.page_num))
+        .build()
+        .build_query_as::<SavedGame>()
         .fetch_all(pool)
         .await?;
 
-    let has_next = games.len() < limit as usize;
+    let has_next = games.len() > page_size as usize;
+    if has_next {
+        games.pop();
+    }
+    let page = PagedResponse::new(games, has_next);
+
+    Ok(page)
+}
+
+pub async fn get_user_games_page(
+    pool: &Pool<Postgres>,
+    user_id: Uuid,
+    query: &UserGamesPageQuery,
+) -> Result<PagedResponse<GameBase>, ServerError> {
+    let page_size = CONFIG.server.page_size as u16;
+
+    let mut games = DBQueryBuilder::select(
+        r#"
+            id,
+            name,
+            description,
+            game_type,
+            category,
+            iterations,
+            total_rounds_played,
+            times_played,
+            last_played,
+            visibility
+        "#,
+        GAME_BASE_SAFE_COLUMNS,
+    )
+    .from("game_base")
+    .r#where("created_by", user_id)
+    .where_opt("game_type", query.game_type.clone())
+    .order_desc("last_played")
+    .limit(page_size + 1)
+    .offset(i64::from(page_size) * i64::from(query.page_num))
+    .build()
+    .build_query_as::<GameBase>()
+    .fetch_all(pool)
+    .await?;
+
+    let has_next = games.len() > page_size as usize;
+    if has_next {
+        games.pop();
+    }
     let page = PagedResponse::new(games, has_next);
 
     Ok(page)
@@ -0,0 +1,80 @@
+use sqlx::{Pool, Postgres};
+use tracing::warn;
+
+use crate::models::{
+    error::ServerError,
+    game_category::{Category, CategorySlug},
+};
+
+pub async fn list_categories(pool: &Pool<Postgres>) -> Result<Vec<Category>, sqlx::Error> {
+    sqlx::query_as!(
+        Category,
+        r#"
+        SELECT slug AS "slug: _", display_name AS "display_name: _", sort_order, active
+        FROM "game_category"
+        ORDER BY sort_order ASC, slug ASC
+        "#
+    )
+    .fetch_all(pool)
+    .await
+}
+
+pub async fn upsert_category(
+    pool: &Pool<Postgres>,
+    category: &Category,
+) -> Result<Category, sqlx::Error> {
+    sqlx::query_as!(
+        Category,
+        r#"
+        INSERT INTO "game_category" (slug, display_name, sort_order, active)
+        VALUES ($1, $2, $3, $4)
+        ON CONFLICT (slug) DO UPDATE
+        SET display_name = EXCLUDED.display_name,
+            sort_order = EXCLUDED.sort_order,
+            active = EXCLUDED.active
+        RETURNING slug AS "slug: _", display_name AS "display_name: _", sort_order, active
+        "#,
+        category.slug as _,
+        category.display_name as _,
+        category.sort_order,
+        category.active
+    )
+    .fetch_one(pool)
+    .await
+}
+
+/// Categories still used by games can only be deactivated
+pub async fn delete_category(
+    pool: &Pool<Postgres>,
+    slug: &CategorySlug,
+) -> Result<(), ServerError> {
+    let result = sqlx::query!(
+        r#"
+        DELETE FROM "game_category"
+        WHERE slug = $1
+        "#,
+        slug as _
+    )
+    .execute(pool)
+    .await;
+
+    let row = match result {
+        Err(e)
+            if e.as_database_error()
+                .is_some_and(|e| e.is_foreign_key_violation()) =>
+        {
+            return Err(ServerError::Conflict(format!(
+                "Category {} is used by games, deactivate it instead",
+                slug
+            )));
+        }
+        result => result?,
+    };
+
+    if row.rows_affected() == 0 {
+        warn!("Query failed, no category with slug: {}", slug);
+        return Err(ServerError::NotFound("Category does not exist".into()));
+    }
+
+    Ok(())
+}
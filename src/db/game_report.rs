@@ -0,0 +1,159 @@
+use chrono::Utc;
+use sqlx::{Pool, Postgres};
+use uuid::Uuid;
+
+use crate::{
+    config::config::CONFIG,
+    models::{
+        error::ServerError,
+        game_base::GameType,
+        game_report::{
+            CreateReportRequest, ModerateAction, ReportOutcome, ReportedGame, ReportsPageQuery,
+        },
+        popup_manager::PagedResponse,
+    },
+};
+
+pub async fn create_game_report(
+    pool: &Pool<Postgres>,
+    base_id: Uuid,
+    reporter_id: Uuid,
+    request: &CreateReportRequest,
+) -> Result<ReportOutcome, ServerError> {
+    let mut tx = pool.begin().await?;
+
+    let exists = sqlx::query_scalar!(r#"SELECT id FROM "game_base" WHERE id = $1"#, base_id)
+        .fetch_optional(&mut *tx)
+        .await?;
+
+    if exists.is_none() {
+        return Err(ServerError::NotFound(format!(
+            "Game with id {} does not exist",
+            base_id
+        )));
+    }
+
+    let report_row = sqlx::query!(
+        r#"
+        INSERT INTO "game_report" (id, base_id, reporter_id, reason, description, created_at)
+        VALUES ($1, $2, $3, $4, $5, $6)
+        ON CONFLICT (base_id, reporter_id) DO NOTHING
+        "#,
+        Uuid::new_v4(),
+        base_id,
+        reporter_id,
+        request.reason as _,
+        request.description,
+        Utc::now()
+    )
+    .execute(&mut *tx)
+    .await?;
+
+    if report_row.rows_affected() == 0 {
+        return Ok(ReportOutcome::Duplicate);
+    }
+
+    let hidden_type = sqlx::query_scalar!(
+        r#"
+        UPDATE "game_base"
+        SET hidden = TRUE
+        WHERE id = $1
+          AND NOT hidden
+          AND (SELECT COUNT(*) FROM "game_report" WHERE base_id = $1) >= $2
+        RETURNING game_type AS "game_type: GameType"
+        "#,
+        base_id,
+        CONFIG.moderation.report_threshold as i64
+    )
+    .fetch_optional(&mut *tx)
+    .await?;
+
+    tx.commit().await?;
+
+    match hidden_type {
+        None => Ok(ReportOutcome::Created),
+        Some(game_type) => Ok(ReportOutcome::Hidden(game_type)),
+    }
+}
+
+pub async fn get_reported_games_page(
+    pool: &Pool<Postgres>,
+    query: &ReportsPageQuery,
+) -> Result<PagedResponse<ReportedGame>, ServerError> {
+    let page_size = CONFIG.server.page_size as i64;
+
+    let mut games = sqlx::query_as!(
+        ReportedGame,
+        r#"
+        SELECT
+            base.id AS base_id,
+            base.name,
+            base.game_type AS "game_type: _",
+            base.hidden,
+            COUNT(report.id) AS "report_count!",
+            MAX(report.created_at) AS "last_reported!"
+        FROM "game_base" base
+        INNER JOIN "game_report" report ON base.id = report.base_id
+        GROUP BY base.id
+        ORDER BY COUNT(report.id) DESC, MAX(report.created_at) DESC
+        LIMIT $1 OFFSET $2
+        "#,
+        page_size + 1,
+        page_size * query.page_num as i64
+    )
+    .fetch_all(pool)
+    .await?;
+
+    let has_next = games.len() > page_size as usize;
+    if has_next {
+        games.pop();
+    }
+    let page = PagedResponse::new(games, has_next);
+
+    Ok(page)
+}
+
+/// Applies the moderation decision and removes the handled reports,
+/// returning the game type and how many reports were resolved
+pub async fn moderate_game(
+    pool: &Pool<Postgres>,
+    base_id: Uuid,
+    action: ModerateAction,
+) -> Result<(GameType, u64), ServerError> {
+    let hidden = match action {
+        ModerateAction::Clear => false,
+        ModerateAction::Hide => true,
+    };
+
+    let mut tx = pool.begin().await?;
+
+    let game_type = sqlx::query_scalar!(
+        r#"
+        UPDATE "game_base"
+        SET hidden = $2
+        WHERE id = $1
+        RETURNING game_type AS "game_type: GameType"
+        "#,
+        base_id,
+        hidden
+    )
+    .fetch_optional(&mut *tx)
+    .await?
+    .ok_or(ServerError::NotFound(format!(
+        "Game with id {} does not exist",
+        base_id
+    )))?;
+
+    let report_row = sqlx::query!(
+        r#"
+        DELETE FROM "game_report"
+        WHERE base_id = $1
+        "#,
+        base_id
+    )
+    .execute(&mut *tx)
+    .await?;
+
+    tx.commit().await?;
+    Ok((game_type, report_row.rows_affected()))
+}
@@ -1,6 +1,40 @@
+use std::collections::HashSet;
+
+use serde::Serialize;
 use sqlx::{Pool, Postgres};
 
 pub async fn health_check(pool: &Pool<Postgres>) -> Result<(), sqlx::Error> {
     let _ = sqlx::query("SELECT 1 as one").fetch_one(pool).await?;
     Ok(())
 }
+
+/// A migration embedded in the binary that the database has not applied
+#[derive(Debug, Serialize, Clone, PartialEq)]
+pub struct PendingMigration {
+    pub version: i64,
+    pub description: String,
+}
+
+/// Compares the embedded migrations with `_sqlx_migrations`, so a deploy that
+/// skipped the migrate step shows up before the inserts start failing
+pub async fn pending_migrations(
+    pool: &Pool<Postgres>,
+) -> Result<Vec<PendingMigration>, sqlx::Error> {
+    let applied: HashSet<i64> =
+        sqlx::query_scalar(r#"SELECT version FROM "_sqlx_migrations" WHERE success"#)
+            .fetch_all(pool)
+            .await?
+            .into_iter()
+            .collect();
+
+    let pending = sqlx::migrate!()
+        .iter()
+        .filter(|m| !m.migration_type.is_down_migration() && !applied.contains(&m.version))
+        .map(|m| PendingMigration {
+            version: m.version,
+            description: m.description.to_string(),
+        })
+        .collect();
+
+    Ok(pending)
+}
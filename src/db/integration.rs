@@ -1,15 +1,62 @@
 use sqlx::{Pool, Postgres};
+use tracing::warn;
+use uuid::Uuid;
 
-use crate::models::integration::Integration;
+use crate::models::{
+    error::ServerError,
+    integration::{Integration, IntegrationName},
+};
 
 pub async fn list_integrations(pool: &Pool<Postgres>) -> Result<Vec<Integration>, sqlx::Error> {
     sqlx::query_as!(
         Integration,
         r#"
-        SELECT id, subject, name as "name: _"
+        SELECT id, subject, name as "name: _", url
         FROM "integration"
         "#,
     )
     .fetch_all(pool)
     .await
 }
+
+pub async fn create_integration(
+    pool: &Pool<Postgres>,
+    subject: &str,
+    name: &IntegrationName,
+    url: &Option<String>,
+) -> Result<Integration, sqlx::Error> {
+    let id = Uuid::new_v4();
+    sqlx::query_as!(
+        Integration,
+        r#"
+        INSERT INTO "integration" (id, subject, name, url)
+        VALUES ($1, $2, $3, $4)
+        RETURNING id, subject, name as "name: _", url
+        "#,
+        id,
+        subject,
+        name as _,
+        url.as_deref()
+    )
+    .fetch_one(pool)
+    .await
+}
+
+pub async fn delete_integration(pool: &Pool<Postgres>, id: Uuid) -> Result<(), ServerError> {
+    let row = sqlx::query!(
+        r#"
+        DELETE FROM "integration"
+        WHERE id = $1
+        "#,
+        id
+    )
+    .execute(pool)
+    .await?;
+
+    if row.rows_affected() == 0 {
+        warn!("Query failed, no integration with id: {}", id);
+        return Err(ServerError::NotFound("Integration does not exist".into()));
+    }
+
+    Ok(())
+}
@@ -1,4 +1,12 @@
+use axum::http::StatusCode;
 use sqlx::{Pool, Postgres};
+use tracing::warn;
+use uuid::Uuid;
+
+use crate::models::{
+    error::ServerError,
+    key_vault::{Word, WordSlot},
+};
 
 pub async fn get_word_sets(
     pool: &Pool<Postgres>,
@@ -16,3 +24,92 @@ pub async fn get_word_sets(
 
     Ok((prefix_result?, suffix_result?))
 }
+
+pub async fn list_words(
+    pool: &Pool<Postgres>,
+    slot: Option<WordSlot>,
+) -> Result<Vec<Word>, sqlx::Error> {
+    sqlx::query_as!(
+        Word,
+        r#"
+        SELECT id as "id!", slot as "slot!: WordSlot", word as "word!"
+        FROM (
+            SELECT id, 'prefix' AS slot, word FROM "prefix_word"
+            UNION ALL
+            SELECT id, 'suffix' AS slot, word FROM "suffix_word"
+        ) AS words
+        WHERE $1::TEXT IS NULL OR slot = $1
+        ORDER BY slot, word
+        "#,
+        slot.map(|s| s.as_str())
+    )
+    .fetch_all(pool)
+    .await
+}
+
+pub async fn insert_word(
+    pool: &Pool<Postgres>,
+    slot: WordSlot,
+    word: &str,
+) -> Result<Word, ServerError> {
+    let id = match slot {
+        WordSlot::Prefix => {
+            sqlx::query_scalar!(
+                r#"
+                INSERT INTO "prefix_word" (word)
+                VALUES ($1)
+                ON CONFLICT (word) DO NOTHING
+                RETURNING id
+                "#,
+                word
+            )
+            .fetch_optional(pool)
+            .await?
+        }
+        WordSlot::Suffix => {
+            sqlx::query_scalar!(
+                r#"
+                INSERT INTO "suffix_word" (word)
+                VALUES ($1)
+                ON CONFLICT (word) DO NOTHING
+                RETURNING id
+                "#,
+                word
+            )
+            .fetch_optional(pool)
+            .await?
+        }
+    };
+
+    let Some(id) = id else {
+        return Err(ServerError::Api(
+            StatusCode::CONFLICT,
+            format!("The {} word already exists", slot),
+        ));
+    };
+
+    Ok(Word {
+        id,
+        slot,
+        word: word.to_string(),
+    })
+}
+
+/// Word ids are unique across both slots, so the caller does not need the slot
+pub async fn delete_word(pool: &Pool<Postgres>, id: Uuid) -> Result<(), ServerError> {
+    let mut tx = pool.begin().await?;
+    let prefix_row = sqlx::query!(r#"DELETE FROM "prefix_word" WHERE id = $1"#, id)
+        .execute(&mut *tx)
+        .await?;
+    let suffix_row = sqlx::query!(r#"DELETE FROM "suffix_word" WHERE id = $1"#, id)
+        .execute(&mut *tx)
+        .await?;
+    tx.commit().await?;
+
+    if prefix_row.rows_affected() + suffix_row.rows_affected() == 0 {
+        warn!("Query failed, no word with id: {}", id);
+        return Err(ServerError::NotFound("Word does not exist".into()));
+    }
+
+    Ok(())
+}
@@ -1,7 +1,11 @@
 pub mod game_base;
+pub mod game_category;
+pub mod game_report;
 pub mod health;
 pub mod integration;
 pub mod key_vault;
+pub mod pool;
+pub mod popup_manager;
 pub mod quiz_game;
 pub mod spin_game;
 pub mod system_log;
@@ -0,0 +1,86 @@
+use std::time::Duration;
+
+use futures::future::BoxFuture;
+use serde_json::json;
+use sqlx::{Pool, Postgres, Transaction, postgres::PgPoolOptions};
+use tracing::warn;
+
+use crate::{
+    config::config::DatabaseConfig,
+    models::{
+        error::ServerError,
+        system_log::{LogAction, LogCeverity},
+    },
+    service::system_log_builder::SystemLogBuilder,
+};
+
+static MAX_RETRY_BACKOFF: Duration = Duration::from_secs(30);
+
+pub fn pool_options(config: &DatabaseConfig) -> PgPoolOptions {
+    PgPoolOptions::new()
+        .max_connections(config.max_connections)
+        .min_connections(config.min_connections)
+        .acquire_timeout(Duration::from_secs(config.acquire_timeout_secs))
+        .idle_timeout(Duration::from_secs(config.idle_timeout_secs))
+        // Pings connections before handing them out, so the ones left stale
+        // by a failover are replaced instead of failing the request
+        .test_before_acquire(true)
+}
+
+/// Retries the initial connection with a doubling backoff, so the server does
+/// not exit when the database comes up after it
+pub async fn connect_with_retry(
+    connection_string: &str,
+    config: &DatabaseConfig,
+) -> Result<Pool<Postgres>, sqlx::Error> {
+    let mut backoff = Duration::from_millis(config.retry_backoff_ms);
+    let mut attempt = 1;
+
+    loop {
+        match pool_options(config).connect(connection_string).await {
+            Ok(pool) => return Ok(pool),
+            Err(e) if attempt < config.connect_attempts => {
+                warn!(
+                    "Database connection attempt {}/{} failed, retrying in {:?}: {}",
+                    attempt, config.connect_attempts, backoff, e
+                );
+                tokio::time::sleep(backoff).await;
+                backoff = (backoff * 2).min(MAX_RETRY_BACKOFF);
+                attempt += 1;
+            }
+            Err(e) => return Err(e),
+        }
+    }
+}
+
+/// Runs `f` in a transaction that is committed when it returns `Ok` and
+/// rolled back when it fails, so early returns never leave partial writes
+pub async fn with_tx<T, F>(pool: &Pool<Postgres>, f: F) -> Result<T, ServerError>
+where
+    F: for<'c> FnOnce(
+        &'c mut Transaction<'static, Postgres>,
+    ) -> BoxFuture<'c, Result<T, ServerError>>,
+{
+    let mut tx = pool.begin().await?;
+    match f(&mut tx).await {
+        Ok(value) => {
+            tx.commit().await?;
+            Ok(value)
+        }
+        Err(e) => {
+            if let Err(rollback_error) = tx.rollback().await {
+                SystemLogBuilder::new_with_function(pool, "with_tx")
+                    .action(LogAction::Other)
+                    .ceverity(LogCeverity::Critical)
+                    .description("Failed to roll back transaction")
+                    .metadata(json!({
+                        "error": rollback_error.to_string(),
+                        "cause": e.to_string(),
+                    }))
+                    .log_async();
+            }
+
+            Err(e)
+        }
+    }
+}
@@ -0,0 +1,67 @@
+use sqlx::{Pool, Postgres};
+use tracing::warn;
+use uuid::Uuid;
+
+use crate::models::{error::ServerError, popup_manager::ClientPopup};
+
+pub async fn list_popups(pool: &Pool<Postgres>) -> Result<Vec<ClientPopup>, sqlx::Error> {
+    sqlx::query_as!(
+        ClientPopup,
+        r#"
+        SELECT id AS popup_id, content AS "content: _", default_locale, active, active_from, active_until, priority
+        FROM "client_popup"
+        ORDER BY priority ASC
+        "#
+    )
+    .fetch_all(pool)
+    .await
+}
+
+pub async fn upsert_popup(
+    pool: &Pool<Postgres>,
+    popup: &ClientPopup,
+) -> Result<ClientPopup, sqlx::Error> {
+    sqlx::query_as!(
+        ClientPopup,
+        r#"
+        INSERT INTO "client_popup" (id, content, default_locale, active, active_from, active_until, priority)
+        VALUES ($1, $2, $3, $4, $5, $6, $7)
+        ON CONFLICT (id) DO UPDATE
+        SET content = EXCLUDED.content,
+            default_locale = EXCLUDED.default_locale,
+            active = EXCLUDED.active,
+            active_from = EXCLUDED.active_from,
+            active_until = EXCLUDED.active_until,
+            priority = EXCLUDED.priority
+        RETURNING id AS popup_id, content AS "content: _", default_locale, active, active_from, active_until, priority
+        "#,
+        popup.popup_id,
+        popup.content as _,
+        popup.default_locale,
+        popup.active,
+        popup.active_from,
+        popup.active_until,
+        popup.priority
+    )
+    .fetch_one(pool)
+    .await
+}
+
+pub async fn delete_popup(pool: &Pool<Postgres>, popup_id: Uuid) -> Result<(), ServerError> {
+    let row = sqlx::query!(
+        r#"
+        DELETE FROM "client_popup"
+        WHERE id = $1
+        "#,
+        popup_id
+    )
+    .execute(pool)
+    .await?;
+
+    if row.rows_affected() == 0 {
+        warn!("Query failed, no popup with id: {}", popup_id);
+        return Err(ServerError::NotFound("Popup does not exist".into()));
+    }
+
+    Ok(())
+}
@@ -1,33 +1,54 @@
 use chrono::Utc;
-use sqlx::{Pool, Postgres, Transaction};
+use sqlx::{Pool, Postgres, Transaction, types::Json};
 use uuid::Uuid;
 
-use crate::models::{error::ServerError, quiz_game::QuizSession};
+use crate::{
+    db::game_base::tx_record_game_play,
+    models::{
+        error::ServerError,
+        game_base::{GameVisibility, MAX_NAME_CHARS},
+        game_category::CategorySlug,
+        quiz_game::{QUIZ_SCHEMA_VERSION, QuizQuestion, QuizSession},
+    },
+    service::db_query_builder::DBQueryBuilder,
+};
+
+#[derive(sqlx::FromRow)]
+struct QuizRow {
+    base_id: Uuid,
+    quiz_id: Uuid,
+    name: String,
+    description: Option<String>,
+    category: CategorySlug,
+    visibility: GameVisibility,
+    iterations: i32,
+    times_played: i32,
+    questions: Json<Vec<QuizQuestion>>,
+}
 
 pub async fn get_quiz_session_by_id(
     pool: &Pool<Postgres>,
     base_id: &Uuid,
 ) -> Result<QuizSession, ServerError> {
-    let session = sqlx::query_as!(
-        QuizSession,
+    let row = DBQueryBuilder::select(
         r#"
-        SELECT 
             base.id AS base_id,
             quiz.id AS quiz_id,
             base.name,
             base.description,
-            base.category as "category: _",
+            base.category,
+            base.visibility,
             base.iterations,
-            base.times_played as "times_played!",
-            0 AS "current_iteration!",
+            base.times_played,
             quiz.questions
-        FROM "game_base" base
-        JOIN "quiz_game" quiz
-        ON base.id = quiz.base_id
-        WHERE base.id = $1
         "#,
-        base_id
+        &[],
     )
+    .from(r#""game_base" base"#)
+    .inner_join(r#""quiz_game" quiz"#, "base.id = quiz.base_id")
+    .r#where("base.id", *base_id)
+    .build()
+    .build_query_as::<QuizRow>()
     .fetch_optional(pool)
     .await?
     .ok_or(ServerError::NotFound(format!(
@@ -35,32 +56,55 @@ pub async fn get_quiz_session_by_id(
         base_id
     )))?;
 
-    Ok(session)
+    Ok(QuizSession {
+        schema_version: QUIZ_SCHEMA_VERSION,
+        base_id: row.base_id,
+        quiz_id: row.quiz_id,
+        name: row.name,
+        description: row.description,
+        category: row.category,
+        visibility: row.visibility,
+        iterations: row.iterations,
+        current_iteration: 0,
+        questions: row.questions.0,
+        times_played: row.times_played,
+    })
 }
 
+/// Returns false when the session was already persisted by an earlier call
 pub async fn tx_persist_quiz_session(
     tx: &mut Transaction<'_, Postgres>,
     session: &QuizSession,
-) -> Result<(), ServerError> {
+    created_by: Uuid,
+) -> Result<bool, ServerError> {
     let times_played = 1;
     let last_played = Utc::now();
 
     let base_row = sqlx::query!(
         r#"
-        INSERT INTO "game_base" (id, name, description, category, iterations, times_played, last_played)
-        VALUES ($1, $2, $3, $4, $5, $6, $7)
+        INSERT INTO "game_base" (id, name, description, game_type, category, iterations, total_rounds_played, times_played, last_played, created_by, visibility)
+        VALUES ($1, $2, $3, 'quiz', $4, $5, $6, $7, $8, $9, $10)
+        ON CONFLICT (id) DO NOTHING
         "#,
         session.base_id,
-        session.name,
+        session.name.trim(),
         session.description,
         session.category as _,
         session.iterations,
+        session.iterations as i64,
         times_played,
-        last_played
+        last_played,
+        created_by,
+        session.visibility as _
     )
     .execute(&mut **tx)
     .await?;
 
+    // Retried persist calls carry the same base id and were already stored
+    if base_row.rows_affected() == 0 {
+        return Ok(false);
+    }
+
     let quiz_row = sqlx::query!(
         r#"
         INSERT INTO "quiz_game" (id, base_id, questions)
@@ -68,16 +112,75 @@ pub async fn tx_persist_quiz_session(
         "#,
         session.quiz_id,
         session.base_id,
-        &session.questions
+        Json(&session.questions) as _
     )
     .execute(&mut **tx)
     .await?;
 
-    if base_row.rows_affected() == 0 || quiz_row.rows_affected() == 0 {
+    if quiz_row.rows_affected() == 0 {
         return Err(ServerError::Internal(
             "Failed to persist quiz session".into(),
         ));
     }
 
-    Ok(())
+    tx_record_game_play(tx, session.base_id, Some(created_by)).await?;
+    Ok(true)
+}
+
+pub async fn tx_clone_quiz_game(
+    tx: &mut Transaction<'_, Postgres>,
+    base_id: Uuid,
+    created_by: Uuid,
+) -> Result<Uuid, ServerError> {
+    let new_base_id = Uuid::new_v4();
+    let new_quiz_id = Uuid::new_v4();
+    let last_played = Utc::now();
+
+    let base_row = sqlx::query!(
+        r#"
+        INSERT INTO "game_base" (id, name, description, game_type, category, iterations, times_played, last_played, created_by, visibility)
+        SELECT $2, LEFT(name || ' (copy)', $5), description, game_type, category, iterations, 0, $3, $4, visibility
+        FROM "game_base"
+        WHERE id = $1
+          AND game_type = 'quiz'
+          AND (visibility <> 'private' OR created_by = $4)
+        "#,
+        base_id,
+        new_base_id,
+        last_played,
+        created_by,
+        MAX_NAME_CHARS as i32
+    )
+    .execute(&mut **tx)
+    .await?;
+
+    if base_row.rows_affected() == 0 {
+        return Err(ServerError::NotFound(format!(
+            "Quiz with id {} does not exist",
+            base_id
+        )));
+    }
+
+    let quiz_row = sqlx::query!(
+        r#"
+        INSERT INTO "quiz_game" (id, base_id, questions)
+        SELECT $2, $3, questions
+        FROM "quiz_game"
+        WHERE base_id = $1
+        "#,
+        base_id,
+        new_quiz_id,
+        new_base_id
+    )
+    .execute(&mut **tx)
+    .await?;
+
+    if quiz_row.rows_affected() == 0 {
+        return Err(ServerError::NotFound(format!(
+            "Quiz with id {} has no questions",
+            base_id
+        )));
+    }
+
+    Ok(new_base_id)
 }
@@ -1,10 +1,13 @@
 use chrono::Utc;
-use sqlx::{Pool, Postgres, Transaction};
+use sqlx::{Pool, Postgres, Transaction, types::Json};
 use uuid::Uuid;
 
-use crate::models::{
-    error::ServerError,
-    spin_game::{SpinSession},
+use crate::{
+    db::game_base::tx_record_game_play,
+    models::{
+        error::ServerError,
+        spin_game::{SpinGame, SpinRound, SpinSession},
+    },
 };
 
 pub async fn get_spin_session_by_game_id(
@@ -21,10 +24,11 @@ pub async fn get_spin_session_by_game_id(
             base.name,
             base.description,
             base.category as "category: _",
+            base.visibility as "visibility: _",
             base.iterations,
             base.times_played,
             base.last_played,
-            spin.rounds
+            spin.rounds as "rounds: Json<Vec<SpinRound>>"
         FROM "game_base" base
         JOIN "spin_game" spin
         ON base.id = spin.base_id
@@ -39,27 +43,38 @@ pub async fn get_spin_session_by_game_id(
     Ok(session)
 }
 
+/// Returns false when the session was already persisted by an earlier call
 pub async fn tx_persist_spin_session(
     tx: &mut Transaction<'_, Postgres>,
     session: &SpinSession,
-) -> Result<(), ServerError> {
+    created_by: Uuid,
+) -> Result<bool, ServerError> {
     let last_played = Utc::now();
     let game_row = sqlx::query!(
         r#"
-        INSERT INTO "game_base" (id, name, description, category, iterations, times_played, last_played)
-        VALUES ($1, $2, $3, $4, $5, $6, $7)
+        INSERT INTO "game_base" (id, name, description, game_type, category, iterations, total_rounds_played, times_played, last_played, created_by, visibility)
+        VALUES ($1, $2, $3, 'spin', $4, $5, $6, $7, $8, $9, $10)
+        ON CONFLICT (id) DO NOTHING
         "#,
         session.base_id,
-        session.name,
+        session.name.trim(),
         session.description,
         session.category as _,
         session.iterations,
+        session.iterations as i64,
         1,
-        last_played
+        last_played,
+        created_by,
+        session.visibility as _
     )
     .execute(&mut **tx)
     .await?;
 
+    // Retried persist calls carry the same base id and were already stored
+    if game_row.rows_affected() == 0 {
+        return Ok(false);
+    }
+
     let spin_id = Uuid::new_v4();
     let round_row = sqlx::query!(
         r#"
@@ -68,16 +83,17 @@ pub async fn tx_persist_spin_session(
         "#,
         spin_id,
         session.base_id,
-        &session.rounds
+        Json(&session.rounds) as _
     )
     .execute(&mut **tx)
     .await?;
 
-    if game_row.rows_affected() == 0 || round_row.rows_affected() == 0 {
+    if round_row.rows_affected() == 0 {
         return Err(ServerError::Internal(
             "Failed to persist spin game session".into(),
         ));
     }
 
-    Ok(())
+    tx_record_game_play(tx, session.base_id, Some(created_by)).await?;
+    Ok(true)
 }
@@ -1,46 +1,62 @@
-use chrono::Utc;
-use sqlx::{Pool, Postgres};
+use chrono::{Duration, Utc};
+use sqlx::{Pool, Postgres, QueryBuilder};
+use std::collections::HashMap;
 
 use crate::{
-    config::config::CONFIG,
+    config::config::{CONFIG, LogRetentionConfig},
     models::{
         error::ServerError,
         popup_manager::PagedResponse,
-        system_log::{LogAction, LogCategoryCount, LogCeverity, SubjectType, SyslogPageQuery, SystemLog},
+        system_log::{
+            CreateSyslogRequest, FunctionCount, LogAction, LogCategoryCount, LogCeverity, LogStats,
+            LogStatsQuery, NewSystemLog, SubjectType, SyslogPageQuery, SystemLog,
+        },
     },
-    service::db_query_builder::DBQueryBuilder,
+    service::{db_query_builder::DBQueryBuilder, system_log_builder::UNSPECIFIED_FUNCTION},
 };
 
+static SYSTEM_LOG_SAFE_COLUMNS: &[&str] = &["created_at", "ceverity", "action", "subject_type"];
+
 pub async fn get_system_log_page(
     pool: &Pool<Postgres>,
     request: SyslogPageQuery,
 ) -> Result<PagedResponse<SystemLog>, sqlx::Error> {
     let page_size = CONFIG.server.page_size as u16;
-    let logs = DBQueryBuilder::select(
+    let mut logs = DBQueryBuilder::select(
         r#"
             id,
             subject_id,
             subject_type,
             action,
             ceverity,
-            function,
+            file_name AS function,
             description,
-            metadata
+            metadata,
+            correlation_id,
+            created_at
         "#,
+        SYSTEM_LOG_SAFE_COLUMNS,
     )
     .from("system_log")
-    .where_opt("subject_type", &request.subject_type)
-    .where_opt("action", &request.action)
-    .where_opt("ceverity", &request.ceverity)
-    .offset(page_size * request.page_num)
-    .limit(page_size + 1)
+    .where_opt("subject_type", request.subject_type)
+    .where_opt("action", request.action)
+    .where_opt("ceverity", request.ceverity)
+    .where_gte_opt("created_at", request.from)
+    .where_lte_opt("created_at", request.to)
+    .where_ilike_any_opt(&["description", "file_name"], request.search.as_deref())
+    .where_opt("correlation_id", request.correlation_id)
     .order_desc("created_at")
+    .limit(page_size + 1)
+    .offset(i64::from(page_size) * i64::from(request.page_num))
     .build()
     .build_query_as::<SystemLog>()
     .fetch_all(pool)
     .await?;
 
-    let has_next = logs.len() < (page_size + 1) as usize;
+    let has_next = logs.len() > page_size as usize;
+    if has_next {
+        logs.pop();
+    }
     let page = PagedResponse::new(logs, has_next);
 
     Ok(page)
@@ -48,27 +64,22 @@ pub async fn get_system_log_page(
 
 pub async fn create_system_log(
     pool: &Pool<Postgres>,
-    subject_id: &str,
-    subject_type: &SubjectType,
-    action: &LogAction,
-    ceverity: &LogCeverity,
-    file_name: &str,
-    description: &str,
-    metadata: &Option<serde_json::Value>,
+    log: &NewSystemLog,
 ) -> Result<(), ServerError> {
     let created_at = Utc::now();
     let row = sqlx::query!(
         r#"
-        INSERT INTO "system_log" (subject_id, subject_type, action, ceverity, file_name, description, metadata, created_at)
-        VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+        INSERT INTO "system_log" (subject_id, subject_type, action, ceverity, file_name, description, metadata, correlation_id, created_at)
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
         "#,
-        subject_id,
-        subject_type as _,
-        action as _,
-        ceverity as _,
-        file_name,
-        description,
-        metadata as _,
+        log.subject_id,
+        log.subject_type as _,
+        log.action as _,
+        log.ceverity as _,
+        log.function,
+        log.description,
+        log.metadata as _,
+        log.correlation_id,
         created_at
     )
     .execute(pool)
@@ -81,8 +92,45 @@ pub async fn create_system_log(
     Ok(())
 }
 
+pub async fn create_system_logs(
+    pool: &Pool<Postgres>,
+    subject_id: &str,
+    subject_type: &SubjectType,
+    entries: &[&CreateSyslogRequest],
+) -> Result<u64, sqlx::Error> {
+    if entries.is_empty() {
+        return Ok(0);
+    }
+
+    let created_at = Utc::now();
+    let mut builder = QueryBuilder::<Postgres>::new(
+        r#"INSERT INTO "system_log" (subject_id, subject_type, action, ceverity, file_name, description, metadata, correlation_id, created_at) "#,
+    );
+
+    builder.push_values(entries, |mut row, entry| {
+        row.push_bind(subject_id.to_string())
+            .push_bind(*subject_type)
+            .push_bind(entry.action.unwrap_or(LogAction::Other))
+            .push_bind(entry.ceverity.unwrap_or(LogCeverity::Info))
+            .push_bind(
+                entry
+                    .function
+                    .clone()
+                    .unwrap_or_else(|| UNSPECIFIED_FUNCTION.into()),
+            )
+            .push_bind(entry.description.clone().unwrap_or_default())
+            .push_bind(entry.metadata.clone())
+            .push_bind(entry.correlation_id)
+            .push_bind(created_at);
+    });
+
+    let result = builder.build().execute(pool).await?;
+    Ok(result.rows_affected())
+}
+
 pub async fn get_log_category_count(
     pool: &Pool<Postgres>,
+    window: &LogStatsQuery,
 ) -> Result<LogCategoryCount, sqlx::Error> {
     #[derive(sqlx::FromRow)]
     struct CountRow {
@@ -98,8 +146,12 @@ pub async fn get_log_category_count(
             COUNT(*) FILTER (WHERE ceverity = 'warning') as warning,
             COUNT(*) FILTER (WHERE ceverity = 'critical') as critical
         FROM system_log
-        "#
+        WHERE ($1::timestamptz IS NULL OR created_at >= $1)
+          AND ($2::timestamptz IS NULL OR created_at <= $2)
+        "#,
     )
+    .bind(window.from)
+    .bind(window.to)
     .fetch_one(pool)
     .await?;
 
@@ -109,3 +161,123 @@ pub async fn get_log_category_count(
         critical: result.critical,
     })
 }
+
+static TOP_FUNCTIONS_LIMIT: i64 = 10;
+
+pub async fn get_log_stats(
+    pool: &Pool<Postgres>,
+    window: &LogStatsQuery,
+) -> Result<LogStats, sqlx::Error> {
+    let ceverity_fut = get_log_category_count(pool, window);
+
+    let action_fut = sqlx::query_as::<_, (LogAction, i64)>(
+        r#"
+        SELECT action, COUNT(*)
+        FROM system_log
+        WHERE ($1::timestamptz IS NULL OR created_at >= $1)
+          AND ($2::timestamptz IS NULL OR created_at <= $2)
+        GROUP BY action
+        "#,
+    )
+    .bind(window.from)
+    .bind(window.to)
+    .fetch_all(pool);
+
+    let subject_type_fut = sqlx::query_as::<_, (SubjectType, i64)>(
+        r#"
+        SELECT subject_type, COUNT(*)
+        FROM system_log
+        WHERE ($1::timestamptz IS NULL OR created_at >= $1)
+          AND ($2::timestamptz IS NULL OR created_at <= $2)
+        GROUP BY subject_type
+        "#,
+    )
+    .bind(window.from)
+    .bind(window.to)
+    .fetch_all(pool);
+
+    let top_functions_fut = sqlx::query_as::<_, FunctionCount>(
+        r#"
+        SELECT file_name AS function, COUNT(*) AS count
+        FROM system_log
+        WHERE ($1::timestamptz IS NULL OR created_at >= $1)
+          AND ($2::timestamptz IS NULL OR created_at <= $2)
+        GROUP BY file_name
+        ORDER BY count DESC, function ASC
+        LIMIT $3
+        "#,
+    )
+    .bind(window.from)
+    .bind(window.to)
+    .bind(TOP_FUNCTIONS_LIMIT)
+    .fetch_all(pool);
+
+    let (ceverity, action, subject_type, top_functions) = tokio::join!(
+        ceverity_fut,
+        action_fut,
+        subject_type_fut,
+        top_functions_fut
+    );
+
+    Ok(LogStats {
+        ceverity: ceverity?,
+        action: action?.into_iter().collect::<HashMap<_, _>>(),
+        subject_type: subject_type?.into_iter().collect::<HashMap<_, _>>(),
+        top_functions: top_functions?,
+    })
+}
+
+static PURGE_BATCH_SIZE: i64 = 10_000;
+
+async fn delete_expired_logs(
+    pool: &Pool<Postgres>,
+    ceverity: LogCeverity,
+    retention_days: u32,
+) -> Result<i64, sqlx::Error> {
+    let threshold = Utc::now() - Duration::days(retention_days as i64);
+    let mut deleted: i64 = 0;
+
+    // Delete in batches so a large purge never holds a long lock on the table
+    loop {
+        let row = sqlx::query!(
+            r#"
+            DELETE FROM "system_log"
+            WHERE id IN (
+                SELECT id FROM "system_log"
+                WHERE ceverity = $1 AND created_at < $2
+                LIMIT $3
+            )
+            "#,
+            &ceverity as _,
+            threshold,
+            PURGE_BATCH_SIZE
+        )
+        .execute(pool)
+        .await?;
+
+        let affected = row.rows_affected() as i64;
+        deleted += affected;
+
+        if affected < PURGE_BATCH_SIZE {
+            break;
+        }
+    }
+
+    Ok(deleted)
+}
+
+pub async fn purge_expired_logs(
+    pool: &Pool<Postgres>,
+    retention: &LogRetentionConfig,
+) -> Result<LogCategoryCount, sqlx::Error> {
+    let info = delete_expired_logs(pool, LogCeverity::Info, retention.info_days).await?;
+    let warning = delete_expired_logs(pool, LogCeverity::Warning, retention.warning_days).await?;
+    let critical =
+        delete_expired_logs(pool, LogCeverity::Critical, retention.critical_days).await?;
+
+    Ok(LogCategoryCount {
+        info,
+        warning,
+        critical,
+    })
+}
@@ -1,25 +1,31 @@
-use chrono::Utc;
+use chrono::{Duration, Utc};
 use serde_json::json;
-use sqlx::{Pool, Postgres, QueryBuilder, Transaction};
+use sqlx::{Pool, Postgres, Transaction};
 use tracing::warn;
 use uuid::Uuid;
 
 use crate::{
     config::config::CONFIG,
+    db::system_log::get_log_category_count,
     models::{
         error::ServerError,
         game_base::Gender,
         popup_manager::PagedResponse,
-        system_log::{LogAction, LogCeverity},
+        system_log::{LogAction, LogCeverity, LogStatsQuery},
         user::{
             ActivityStats, Auth0User, AverageUserStats, BaseUser, ListUsersQuery, PatchUserRequest,
             RecentUserStats,
         },
     },
-    service::system_log_builder::SystemLogBuilder,
+    service::{db_query_builder::DBQueryBuilder, system_log_builder::SystemLogBuilder},
 };
 
-pub async fn delete_pseudo_user(pool: &Pool<Postgres>, id: Uuid) -> Result<bool, sqlx::Error> {
+static BASE_USER_SAFE_COLUMNS: &[&str] = &["created_at"];
+
+pub async fn tx_delete_pseudo_user(
+    tx: &mut Transaction<'_, Postgres>,
+    id: Uuid,
+) -> Result<bool, sqlx::Error> {
     let row = sqlx::query!(
         r#"
         DELETE FROM "pseudo_user"
@@ -27,10 +33,51 @@ pub async fn delete_pseudo_user(pool: &Pool<Postgres>, id: Uuid) -> Result<bool,
         "#,
         id
     )
-    .execute(pool)
+    .execute(&mut **tx)
     .await?;
 
-    Ok(row.rows_affected() == 0)
+    Ok(row.rows_affected() != 0)
+}
+
+/// Deletes pseudo users inactive since before the retention period that never
+/// linked to a base user and own no games. Runs in batches so a large backlog
+/// does not hold the locks for long, returns the number of deleted rows
+pub async fn delete_ghost_pseudo_users(
+    pool: &Pool<Postgres>,
+    retention_days: u32,
+    batch_size: i64,
+) -> Result<u64, sqlx::Error> {
+    let cutoff = Utc::now() - Duration::days(retention_days as i64);
+    let mut purged = 0;
+
+    loop {
+        let row = sqlx::query!(
+            r#"
+            DELETE FROM "pseudo_user"
+            WHERE id IN (
+                SELECT p.id
+                FROM "pseudo_user" p
+                WHERE p.last_active < $1
+                    AND p.base_user_id IS NULL
+                    AND NOT EXISTS (SELECT 1 FROM "base_user" b WHERE b.id = p.id)
+                    AND NOT EXISTS (SELECT 1 FROM "saved_game" s WHERE s.user_id = p.id)
+                    AND NOT EXISTS (SELECT 1 FROM "game_base" g WHERE g.created_by = p.id)
+                LIMIT $2
+            )
+            "#,
+            cutoff,
+            batch_size
+        )
+        .execute(pool)
+        .await?;
+
+        purged += row.rows_affected();
+        if row.rows_affected() < batch_size as u64 {
+            break;
+        }
+    }
+
+    Ok(purged)
 }
 
 pub async fn create_pseudo_user(pool: &Pool<Postgres>) -> Result<Uuid, sqlx::Error> {
@@ -49,6 +96,7 @@ pub async fn create_pseudo_user(pool: &Pool<Postgres>) -> Result<Uuid, sqlx::Err
     .await
 }
 
+/// Creates the pseudo user linked to the base user sharing its id
 pub async fn tx_create_pseudo_user(
     tx: &mut Transaction<'_, Postgres>,
     id: Uuid,
@@ -56,8 +104,8 @@ pub async fn tx_create_pseudo_user(
     let last_active = Utc::now();
     sqlx::query_scalar!(
         r#"
-        INSERT INTO "pseudo_user" (id, last_active)
-        VALUES ($1, $2)
+        INSERT INTO "pseudo_user" (id, last_active, base_user_id)
+        VALUES ($1, $2, $1)
         RETURNING id
         "#,
         id,
@@ -67,7 +115,7 @@ pub async fn tx_create_pseudo_user(
     .await
 }
 
-pub async fn ensure_pseudo_user(pool: &Pool<Postgres>, id: Uuid) {
+pub async fn ensure_pseudo_user(pool: &Pool<Postgres>, id: Uuid, correlation_id: Uuid) {
     let last_active = Utc::now();
     let result = sqlx::query!(
         r#"
@@ -83,22 +131,24 @@ pub async fn ensure_pseudo_user(pool: &Pool<Postgres>, id: Uuid) {
 
     match result {
         Err(e) => {
-            let _ = SystemLogBuilder::new(pool)
+            let _ = SystemLogBuilder::new_with_function(pool, "ensure_psuedo_user")
                 .action(LogAction::Create)
                 .ceverity(LogCeverity::Critical)
-                .function("ensure_psuedo_user")
                 .description("Failed to do insert on pseudo user. Should not fail")
                 .metadata(json!({"error": e.to_string()}))
-                .log();
+                .correlation_id(correlation_id)
+                .log()
+                .await;
         }
         Ok(row) => {
             if row.rows_affected() != 0 {
-                let _ = SystemLogBuilder::new(pool)
+                let _ = SystemLogBuilder::new_with_function(pool, "ensure_psuedo_user")
                     .action(LogAction::Create)
                     .ceverity(LogCeverity::Warning)
-                    .function("ensure_psuedo_user")
                     .description("User had pseudo user that did not exist, so a new was created. This will cause ghost users")
-                    .log();
+                    .correlation_id(correlation_id)
+                    .log()
+                    .await;
             }
         }
     };
@@ -112,7 +162,7 @@ pub async fn get_base_user_by_auth0_id(
         BaseUser,
         r#"
         SELECT id, username, auth0_id, birth_date, gender as "gender: _", email,
-            email_verified, family_name, updated_at, given_name, created_at
+            email_verified, family_name, updated_at, given_name, created_at, last_login_at
         FROM "base_user"
         WHERE auth0_id = $1
         "#,
@@ -130,7 +180,7 @@ pub async fn get_base_user_by_id(
         BaseUser,
         r#"
         SELECT id, username, auth0_id, birth_date, gender as "gender: _", email,
-            email_verified, family_name, updated_at, given_name, created_at
+            email_verified, family_name, updated_at, given_name, created_at, last_login_at
         FROM "base_user"
         WHERE id = $1
         "#,
@@ -140,6 +190,26 @@ pub async fn get_base_user_by_id(
     .await
 }
 
+pub async fn tx_base_user_exists(
+    tx: &mut Transaction<'_, Postgres>,
+    id: Uuid,
+) -> Result<bool, sqlx::Error> {
+    sqlx::query_scalar!(
+        r#"SELECT EXISTS (SELECT 1 FROM "base_user" WHERE id = $1) AS "exists!""#,
+        id
+    )
+    .fetch_one(&mut **tx)
+    .await
+}
+
+pub async fn count_pseudo_users(pool: &Pool<Postgres>) -> Result<i64, sqlx::Error> {
+    let count = sqlx::query_scalar!(r#"SELECT COUNT(*) AS "count!" FROM "pseudo_user""#)
+        .fetch_one(pool)
+        .await?;
+
+    Ok(count)
+}
+
 pub async fn pseudo_user_exists(pool: &Pool<Postgres>, id: Uuid) -> Result<bool, sqlx::Error> {
     let exists = sqlx::query_scalar!("SELECT id FROM pseudo_user WHERE id = $1", id)
         .fetch_optional(pool)
@@ -226,43 +296,44 @@ pub async fn update_pseudo_user_activity(
     Ok(())
 }
 
+pub async fn username_taken(
+    pool: &Pool<Postgres>,
+    username: &str,
+    exclude_id: Option<Uuid>,
+) -> Result<bool, sqlx::Error> {
+    let taken = sqlx::query_scalar!(
+        r#"
+        SELECT EXISTS(
+            SELECT 1 FROM "base_user"
+            WHERE username = $1 AND ($2::UUID IS NULL OR id != $2)
+        ) AS "taken!"
+        "#,
+        username,
+        exclude_id
+    )
+    .fetch_one(pool)
+    .await?;
+
+    Ok(taken)
+}
+
 pub async fn patch_base_user_by_id(
     pool: &Pool<Postgres>,
     user_id: &Uuid,
     request: PatchUserRequest,
 ) -> Result<BaseUser, ServerError> {
-    let mut builder: QueryBuilder<'_, Postgres> = sqlx::QueryBuilder::new("UPDATE base_user SET ");
-    let mut separator = builder.separated(", ");
-
-    if let Some(username) = request.username {
-        separator
-            .push("username = ")
-            .push_bind_unseparated(username);
-    }
-
-    if let Some(gname) = request.given_name {
-        separator.push("given_name = ").push_bind_unseparated(gname);
-    }
-
-    if let Some(fname) = request.family_name {
-        separator
-            .push("family_name = ")
-            .push_bind_unseparated(fname);
-    }
-
-    if let Some(gender) = request.gender {
-        separator.push("gender = ").push_bind_unseparated(gender);
-    }
-
-    if let Some(birth_date) = request.birth_date {
-        separator
-            .push("birth_date = ")
-            .push_bind_unseparated(birth_date);
-    }
-
-    builder.push(" WHERE id = ").push_bind(user_id); // Also fixed: use 'id', not 'user_id'
-    builder.push(" RETURNING id, username, auth0_id, birth_date, gender, email, email_verified, family_name, updated_at, given_name, created_at");
-    let result: BaseUser = builder.build_query_as().fetch_one(pool).await?;
+    let result: BaseUser = DBQueryBuilder::update("base_user")
+        .set_if_some("username", request.username)
+        .set_if_some("given_name", request.given_name)
+        .set_if_some("family_name", request.family_name)
+        .set_if_some("gender", request.gender)
+        .set_if_some("birth_date", request.birth_date)
+        .where_id(*user_id)
+        .returning("id, username, auth0_id, birth_date, gender, email, email_verified, family_name, updated_at, given_name, created_at, last_login_at")
+        .build()?
+        .build_query_as()
+        .fetch_one(pool)
+        .await?;
 
     Ok(result)
 }
@@ -285,28 +356,92 @@ pub async fn delete_base_user_by_id(pool: &Pool<Postgres>, id: &Uuid) -> Result<
     Ok(())
 }
 
+pub async fn delete_base_user_by_auth0_id(
+    pool: &Pool<Postgres>,
+    auth0_id: &str,
+) -> Result<Uuid, ServerError> {
+    let mut tx = pool.begin().await?;
+
+    // Saved games cascade from base_user, the paired pseudo user shares its id
+    let Some(id) = sqlx::query_scalar!(
+        r#"
+        DELETE FROM "base_user" WHERE auth0_id = $1
+        RETURNING id
+        "#,
+        auth0_id
+    )
+    .fetch_optional(&mut *tx)
+    .await?
+    else {
+        warn!("Query failed, no user with auth0 id: {}", auth0_id);
+        return Err(ServerError::NotFound("User does not exist".into()));
+    };
+
+    sqlx::query!(
+        r#"
+        DELETE FROM "pseudo_user" WHERE id = $1
+        "#,
+        id
+    )
+    .execute(&mut *tx)
+    .await?;
+
+    tx.commit().await?;
+    Ok(id)
+}
+
+pub async fn touch_last_login(pool: &Pool<Postgres>, user_id: Uuid) -> Result<(), sqlx::Error> {
+    sqlx::query!(
+        r#"
+        UPDATE "base_user"
+        SET last_login_at = NOW()
+        WHERE id = $1
+        "#,
+        user_id
+    )
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
 pub async fn list_base_users(
     pool: &Pool<Postgres>,
     query: ListUsersQuery,
 ) -> Result<PagedResponse<BaseUser>, sqlx::Error> {
-    let offset = CONFIG.server.page_size * query.page_num;
-    let limit = CONFIG.server.page_size + 1;
-
-    let items = sqlx::query_as!(
-        BaseUser,
+    let page_size = CONFIG.server.page_size as u16;
+    let mut items = DBQueryBuilder::select(
         r#"
-        SELECT id, username, auth0_id, birth_date, gender as "gender: _", email, email_verified, updated_at, family_name, given_name, created_at
-        FROM "base_user"
-        ORDER BY created_at DESC
-        LIMIT $1 OFFSET $2
+            id,
+            username,
+            auth0_id,
+            birth_date,
+            gender,
+            email,
+            email_verified,
+            updated_at,
+            family_name,
+            given_name,
+            created_at,
+            last_login_at
         "#,
-        limit as i64,
-        offset as i64
+        BASE_USER_SAFE_COLUMNS,
     )
+    .from(r#""base_user""#)
+    .where_gte_opt("created_at", query.created_after)
+    .where_lte_opt("created_at", query.created_before)
+    .order_desc("created_at")
+    .limit(page_size + 1)
+    .offset(i64::from(page_size) * i64::from(query.page_num))
+    .build()
+    .build_query_as::<BaseUser>()
     .fetch_all(pool)
     .await?;
 
-    let has_next = items.len() > CONFIG.server.page_size as usize;
+    let has_next = items.len() > page_size as usize;
+    if has_next {
+        items.pop();
+    }
     let response = PagedResponse::new(items, has_next);
 
     Ok(response)
@@ -317,9 +452,11 @@ pub async fn get_user_activity_stats(pool: &Pool<Postgres>) -> Result<ActivitySt
         RecentUserStats,
         r#"
         SELECT
-            COUNT(*) FILTER (WHERE last_active >= date_trunc('month', CURRENT_DATE)) AS "this_month_users!",
-            COUNT(*) FILTER (WHERE last_active >= date_trunc('week', CURRENT_DATE)) AS "this_week_users!",
-            COUNT(*) FILTER (WHERE last_active >= CURRENT_DATE) AS "todays_users!"
+            COUNT(*) FILTER (WHERE base_user_id IS NULL AND last_active >= date_trunc('month', CURRENT_DATE)) AS "this_month_users!",
+            COUNT(*) FILTER (WHERE base_user_id IS NULL AND last_active >= date_trunc('week', CURRENT_DATE)) AS "this_week_users!",
+            COUNT(*) FILTER (WHERE base_user_id IS NULL AND last_active >= CURRENT_DATE) AS "todays_users!",
+            COUNT(*) FILTER (WHERE base_user_id IS NOT NULL AND last_active >= date_trunc('month', CURRENT_DATE)) AS "this_month_registered_users!",
+            COUNT(*) FILTER (WHERE base_user_id IS NOT NULL AND last_active >= date_trunc('week', CURRENT_DATE)) AS "this_week_registered_users!"
         FROM pseudo_user
         "#
     )
@@ -366,22 +503,49 @@ pub async fn get_user_activity_stats(pool: &Pool<Postgres>) -> Result<ActivitySt
     let total_user_count_fut =
         sqlx::query_scalar!("SELECT COUNT(*)::bigint as count FROM base_user").fetch_one(pool);
 
-    let (recent, average, total_game_count, total_user_count): (
-        Result<RecentUserStats, sqlx::Error>,
-        Result<AverageUserStats, sqlx::Error>,
-        Result<Option<i64>, sqlx::Error>,
-        Result<Option<i64>, sqlx::Error>,
+    let pseudo_user_count_fut =
+        sqlx::query_scalar!("SELECT COUNT(*)::bigint as count FROM pseudo_user").fetch_one(pool);
+
+    let recently_logged_in_fut = sqlx::query_scalar!(
+        r#"
+        SELECT COUNT(*)::bigint as count
+        FROM base_user
+        WHERE last_login_at >= NOW() - INTERVAL '7 days'
+        "#
+    )
+    .fetch_one(pool);
+
+    let all_time = LogStatsQuery::default();
+    let log_counts_fut = get_log_category_count(pool, &all_time);
+
+    let (
+        recent,
+        average,
+        total_game_count,
+        total_user_count,
+        pseudo_user_count,
+        recently_logged_in,
+        log_counts,
     ) = tokio::join!(
         recent_fut,
         average_fut,
         total_game_count_fut,
-        total_user_count_fut
+        total_user_count_fut,
+        pseudo_user_count_fut,
+        recently_logged_in_fut,
+        log_counts_fut
     );
 
+    let base_user_count = total_user_count?.unwrap_or(0);
+
     Ok(ActivityStats {
         total_game_count: total_game_count?.unwrap_or(0),
-        total_user_count: total_user_count?.unwrap_or(0),
+        total_user_count: base_user_count,
+        base_user_count,
+        pseudo_user_count: pseudo_user_count?.unwrap_or(0),
+        recently_logged_in_count: recently_logged_in?.unwrap_or(0),
         recent: recent?,
         average: average?,
+        log_counts: log_counts?,
     })
 }
@@ -1,27 +1,17 @@
-use std::collections::HashMap;
+use std::{env, net::SocketAddr, process::ExitCode, sync::Arc};
 
-use axum::{Router, middleware::from_fn_with_state, routing::post};
+use axum::Router;
 use dotenv::dotenv;
 use models::app_state::AppState;
-use sqlx::{Pool, Postgres};
 use tracing::{error, info};
 use tracing_subscriber::{EnvFilter, fmt, layer::SubscriberExt, util::SubscriberInitExt};
-use uuid::Uuid;
 
 use crate::{
-    api::{
-        auth_mw::auth_mw,
-        game_base::game_routes,
-        health::health_routes,
-        system_log::log_routes,
-        user::{auth0_trigger_endpoint, protected_auth_routes, public_auth_routes},
-        webhook_mw::webhook_mw,
-    },
+    api::router::app_router,
     config::config::CONFIG,
-    db::integration,
-    models::{
-        error::ServerError,
-        integration::{INTEGRATION_IDS, INTEGRATION_NAMES, IntegrationName},
+    service::{
+        self_check::{CHECK_FLAG, SelfCheckReport, run_self_check},
+        telemetry::{otel_layer, tracer_provider},
     },
 };
 
@@ -34,55 +24,91 @@ mod service;
 mod tests;
 
 #[tokio::main]
-async fn main() {
+async fn main() -> ExitCode {
     // Initialize .env
     dotenv().ok();
 
-    // Initialize logging
+    // Initialize logging, spans are exported when an OTLP endpoint is set
+    let (provider, otel_error) = match tracer_provider(CONFIG.server.otel_endpoint.as_deref()) {
+        Ok(provider) => (provider, None),
+        Err(e) => (None, Some(e)),
+    };
     tracing_subscriber::registry()
         .with(fmt::layer())
         .with(EnvFilter::from_default_env())
+        .with(provider.as_ref().map(otel_layer))
         .init();
 
+    if let Some(e) = otel_error {
+        error!(
+            "Failed to build the OTLP exporter, spans are not exported: {}",
+            e
+        );
+    }
+
+    let exit_code = match env::args().nth(1).is_some_and(|arg| arg == CHECK_FLAG) {
+        true => check().await,
+        false => serve().await,
+    };
+
+    if let Some(provider) = provider
+        && let Err(e) = provider.shutdown()
+    {
+        error!("Failed to flush the exported spans: {}", e);
+    }
+
+    exit_code
+}
+
+/// Everything both modes need before traffic could be handled
+async fn bootstrap() -> Result<(Arc<AppState>, Router), String> {
+    // Report every config problem at once instead of failing on first use
+    CONFIG.validate().map_err(|e| e.to_string())?;
+
     // Initialize state
     let state = AppState::from_connection_string(&CONFIG.database_url)
         .await
-        .unwrap_or_else(|e| panic!("{}", e));
+        .map_err(|e| e.to_string())?;
 
-    // Spawn cron jobs
-    state.spawn_game_cleanup();
+    let app = app_router(state.clone());
+    Ok((state, app))
+}
 
-    // Initiate integrations
-    if let Err(e) = load_integrations(state.get_pool()).await {
-        error!("{}", e);
-        return;
+/// Validates the environment and prints the report, without binding the port
+/// or spawning the cron jobs
+async fn check() -> ExitCode {
+    let report = match bootstrap().await {
+        Ok((state, _)) => run_self_check(&state).await,
+        Err(e) => SelfCheckReport::bootstrap_failed(e),
+    };
+
+    println!("{}", serde_json::to_string_pretty(&report).unwrap());
+    match report.passed {
+        true => ExitCode::SUCCESS,
+        false => ExitCode::FAILURE,
     }
+}
 
-    // Run migrations
-    if let Err(e) = sqlx::migrate!().run(state.get_pool()).await {
-        error!("Failed to run migrations: {}", e);
-        return;
+async fn serve() -> ExitCode {
+    let (state, app) = match bootstrap().await {
+        Ok(bootstrapped) => bootstrapped,
+        Err(e) => {
+            error!("Failed to start the server: {}", e);
+            return ExitCode::FAILURE;
+        }
+    };
+
+    // Warm up the page cache, a failure only costs a few cold requests
+    if CONFIG.features.is_enabled("cache_warmup") {
+        match state.warm_cache().await {
+            Ok(_) => info!("Page cache warmed up"),
+            Err(e) => error!("Failed to warm up page cache: {}", e),
+        }
     }
 
-    let event_routes = Router::new()
-        .route("/{pseudo_id}", post(auth0_trigger_endpoint))
-        .layer(from_fn_with_state(state.clone(), webhook_mw))
-        .with_state(state.clone());
-
-    let public_routes = Router::new()
-        .nest("/health", health_routes(state.clone()))
-        .nest("/pseudo-users", public_auth_routes(state.clone()));
-
-    let protected_routes = Router::new()
-        .nest("/games", game_routes(state.clone()))
-        .nest("/users", protected_auth_routes(state.clone()))
-        .nest("/logs", log_routes(state.clone()))
-        .layer(from_fn_with_state(state.clone(), auth_mw));
-
-    let app = Router::new()
-        .merge(protected_routes)
-        .merge(public_routes)
-        .nest("/webhooks/auth0", event_routes);
+    // Spawn cron jobs
+    state.spawn_game_cleanup();
+    state.spawn_metrics_poll();
 
     // Initialize webserver
     let listener =
@@ -94,31 +120,12 @@ async fn main() {
         "Server listening on address: {}",
         listener.local_addr().unwrap()
     );
-    axum::serve(listener, app).await.unwrap();
-}
-
-async fn load_integrations(pool: &Pool<Postgres>) -> Result<(), ServerError> {
-    let integrations = integration::list_integrations(pool).await?;
-
-    let integration_names: HashMap<String, IntegrationName> = integrations
-        .iter()
-        .map(|i| (i.subject.clone(), i.name.clone()))
-        .collect();
-
-    let integration_ids: HashMap<IntegrationName, Uuid> = integrations
-        .iter()
-        .map(|i| (i.name.clone(), i.id))
-        .collect();
-
-    {
-        let mut lock = INTEGRATION_IDS.lock().await;
-        *lock = integration_ids;
-    }
-
-    {
-        let mut lock = INTEGRATION_NAMES.lock().await;
-        *lock = integration_names;
-    }
-
-    Ok(())
+    axum::serve(
+        listener,
+        app.into_make_service_with_connect_info::<SocketAddr>(),
+    )
+    .await
+    .unwrap();
+
+    ExitCode::SUCCESS
 }
@@ -1,56 +1,136 @@
 use std::{sync::Arc, time::Duration};
 
+use futures::future::BoxFuture;
 use serde_json::json;
 
 use reqwest::Client;
-use sqlx::{Pool, Postgres};
+use sqlx::{Pool, Postgres, Transaction};
+use tracing::{debug, warn};
+use uuid::Uuid;
 
 use crate::{
     client::gs_client::GSClient,
     config::config::CONFIG,
-    db::game_base::delete_non_active_games,
+    db::{
+        game_base::{delete_expired_game_plays, delete_non_active_games, get_game_page},
+        pool::{connect_with_retry, with_tx},
+        system_log::purge_expired_logs,
+        user::{
+            count_pseudo_users, delete_ghost_pseudo_users, tx_base_user_exists,
+            tx_delete_pseudo_user,
+        },
+    },
     models::{
         auth::Jwks,
         error::ServerError,
-        game_base::GameBase,
+        game_base::{GamePageItem, GamePageQuery, GameType},
+        game_category::CategoryManager,
+        integration::IntegrationRegistry,
         popup_manager::{PagedResponse, PopupManager},
         system_log::{LogAction, LogCeverity},
+        user::SubjectId,
+    },
+    service::{
+        cache::{CacheStats, GustCache},
+        idempotency_vault::{IDEMPOTENCY_TTL, IdempotencyVault},
+        key_vault::KeyVault,
+        metrics::{Metrics, MetricsSnapshot},
+        system_log_builder::SystemLogBuilder,
+        token_cache::{TOKEN_CACHE_MAX_ENTRIES, TokenCache},
+        user_sync::UserSyncs,
+        util::try_with_retry,
     },
-    service::{cache::GustCache, key_vault::KeyVault, system_log_builder::SystemLogBuilder},
 };
 
+static WARM_PAGE_COUNT: u16 = 2;
+static SAVED_CACHE_TTL: u64 = 30;
+static SYNC_USER_ATTEMPTS: u8 = 3;
+static SYNC_USER_RETRY_DELAY: Duration = Duration::from_secs(2);
+/// The cleanup cron runs daily, ghost pseudo users are only purged weekly
+static PSEUDO_USER_CLEANUP_INTERVAL_DAYS: u32 = 7;
+static PSEUDO_USER_CLEANUP_BATCH_SIZE: i64 = 1000;
+
 #[derive(Clone)]
 pub struct AppState {
     pool: Pool<Postgres>,
+    read_pool: Pool<Postgres>,
     jwks: Jwks,
     client: Client,
     gs_client: GSClient,
-    page_cache: Arc<GustCache<PagedResponse<GameBase>>>,
+    page_cache: Arc<GustCache<PagedResponse<GamePageItem>>>,
+    saved_cache: Arc<GustCache<bool>>,
     key_vault: Arc<KeyVault>,
+    integrations: Arc<IntegrationRegistry>,
     popup_manager: PopupManager,
+    category_manager: CategoryManager,
+    idempotency_vault: IdempotencyVault,
+    token_cache: TokenCache,
+    user_syncs: UserSyncs,
+    metrics: Metrics,
 }
 
 impl AppState {
     pub async fn from_connection_string(connection_string: &str) -> Result<Arc<Self>, ServerError> {
-        let pool = Pool::<Postgres>::connect(&connection_string).await?;
-        let client = Client::new();
-        let gs_client = GSClient::new(&CONFIG.server.gs_domain);
+        let pool = connect_with_retry(connection_string, &CONFIG.database).await?;
+        let read_pool = match &CONFIG.database_read_url {
+            Some(url) => connect_with_retry(url, &CONFIG.database).await?,
+            None => pool.clone(),
+        };
 
+        let client = Client::new();
         let jwks_url = format!("{}.well-known/jwks.json", CONFIG.auth0.domain);
         let response = client.get(jwks_url).send().await?;
         let jwks = response.json::<Jwks>().await?;
+        if jwks.keys.is_empty() {
+            warn!("Auth0 returned no JWKs, every user token will be rejected");
+        }
+
+        let gs_client = GSClient::new(&CONFIG.server.gs_domain);
+        Self::from_parts(pool, read_pool, client, gs_client, jwks).await
+    }
+
+    /// Builds the state around connected pools and already fetched keys, so
+    /// tests can run it without reaching Auth0 or tero-session
+    pub async fn from_parts(
+        pool: Pool<Postgres>,
+        read_pool: Pool<Postgres>,
+        client: Client,
+        gs_client: GSClient,
+        jwks: Jwks,
+    ) -> Result<Arc<Self>, ServerError> {
+        // Migrations must run before any state is loaded from the database
+        sqlx::migrate!()
+            .run(&pool)
+            .await
+            .map_err(|e| ServerError::Internal(format!("Failed to run migrations: {}", e)))?;
+
         let page_cache = Arc::new(GustCache::from_ttl(120));
+        let saved_cache = Arc::new(GustCache::from_ttl(SAVED_CACHE_TTL));
         let key_vault = Arc::new(KeyVault::load_words(&pool).await?);
-        let popup_manager = PopupManager::new();
+        let popup_manager = PopupManager::load(&pool).await?;
+        let category_manager = CategoryManager::load(&pool).await?;
+        let integrations = Arc::new(IntegrationRegistry::load(&pool).await?);
+        let idempotency_vault = IdempotencyVault::from_ttl(IDEMPOTENCY_TTL);
+        let token_cache = TokenCache::with_capacity(TOKEN_CACHE_MAX_ENTRIES);
+        let user_syncs = UserSyncs::default();
+        let metrics = Metrics::default();
 
         let state = Arc::new(Self {
             pool,
+            read_pool,
             jwks,
             client,
             gs_client,
             page_cache,
+            saved_cache,
             key_vault,
+            integrations,
             popup_manager,
+            category_manager,
+            idempotency_vault,
+            token_cache,
+            user_syncs,
+            metrics,
         });
 
         Ok(state)
@@ -60,14 +140,74 @@ impl AppState {
         &self.pool
     }
 
+    /// See `db::pool::with_tx`
+    pub async fn with_tx<T, F>(&self, f: F) -> Result<T, ServerError>
+    where
+        F: for<'c> FnOnce(
+            &'c mut Transaction<'static, Postgres>,
+        ) -> BoxFuture<'c, Result<T, ServerError>>,
+    {
+        with_tx(&self.pool, f).await
+    }
+
+    /// Only for plain reads, writes and transactions must use `get_pool`
+    pub fn get_read_pool(&self) -> &Pool<Postgres> {
+        &self.read_pool
+    }
+
     pub fn get_jwks(&self) -> &Jwks {
         &self.jwks
     }
 
-    pub fn get_cache(&self) -> &Arc<GustCache<PagedResponse<GameBase>>> {
+    pub fn get_cache(&self) -> &Arc<GustCache<PagedResponse<GamePageItem>>> {
         &self.page_cache
     }
 
+    /// Keyed by `(user_id, base_id)`
+    pub fn get_saved_cache(&self) -> &Arc<GustCache<bool>> {
+        &self.saved_cache
+    }
+
+    pub fn get_integrations(&self) -> &IntegrationRegistry {
+        &self.integrations
+    }
+
+    pub fn get_metrics(&self) -> &Metrics {
+        &self.metrics
+    }
+
+    pub fn metrics_snapshot(&self) -> MetricsSnapshot {
+        MetricsSnapshot {
+            active_game_keys: self.key_vault.active_key_count(),
+            page_cache_hits: self.page_cache.stats().hits,
+        }
+    }
+
+    pub fn get_cache_stats(&self) -> CacheStats {
+        self.page_cache.stats()
+    }
+
+    /// Loads the first unfiltered game pages of every game type into the
+    /// page cache, so the first requests after a cold start skip the database
+    pub async fn warm_cache(&self) -> Result<(), ServerError> {
+        for game_type in GameType::ALL {
+            for page_num in 0..WARM_PAGE_COUNT {
+                let query = GamePageQuery {
+                    page_num,
+                    game_type: game_type.clone(),
+                    category: None,
+                    trending: false,
+                };
+
+                self.page_cache
+                    .get_or(&query, || get_game_page(&self.read_pool, &query))
+                    .await?;
+            }
+        }
+
+        Ok(())
+    }
+
     pub fn get_client(&self) -> &Client {
         &self.client
     }
@@ -76,8 +216,8 @@ impl AppState {
         &self.gs_client
     }
 
-    pub fn syslog(&self) -> SystemLogBuilder {
-        SystemLogBuilder::new(self.get_pool())
+    pub fn syslog(&self, function: &str) -> SystemLogBuilder {
+        SystemLogBuilder::new_with_function(self.get_pool(), function)
     }
 
     pub fn get_vault(&self) -> &KeyVault {
@@ -88,23 +228,199 @@ impl AppState {
         &self.popup_manager
     }
 
+    pub fn get_category_manager(&self) -> &CategoryManager {
+        &self.category_manager
+    }
+
+    pub fn get_idempotency_vault(&self) -> &IdempotencyVault {
+        &self.idempotency_vault
+    }
+
+    pub fn get_token_cache(&self) -> &TokenCache {
+        &self.token_cache
+    }
+
+    pub fn get_user_syncs(&self) -> &UserSyncs {
+        &self.user_syncs
+    }
+
+    /// Removes the pseudo user a guest used before registering as `base_id`,
+    /// unless it is the registered user's own. At most one task runs per pair
+    pub fn spawn_sync_user(&self, base_id: Uuid, pseudo_id: Uuid, subject: SubjectId) {
+        let Some(guard) = self.user_syncs.try_begin(base_id, pseudo_id) else {
+            debug!("Sync already running for pseudo user {}", pseudo_id);
+            return;
+        };
+
+        let pool = self.pool.clone();
+        tokio::spawn(async move {
+            let _guard = guard;
+
+            let result = try_with_retry(SYNC_USER_ATTEMPTS, SYNC_USER_RETRY_DELAY, || {
+                with_tx(&pool, |tx| {
+                    Box::pin(async move {
+                        if tx_base_user_exists(tx, pseudo_id).await? {
+                            return Ok(false);
+                        }
+
+                        Ok(tx_delete_pseudo_user(tx, pseudo_id).await?)
+                    })
+                })
+            })
+            .await;
+
+            match result {
+                Ok(true) => debug!("Deleted pseudo user {} after registration", pseudo_id),
+                Ok(false) => debug!("No pseudo user to clean up for {}", pseudo_id),
+                Err(e) => {
+                    let _ = SystemLogBuilder::new_with_function(&pool, "spawn_sync_user")
+                        .action(LogAction::Delete)
+                        .ceverity(LogCeverity::Critical)
+                        .description("Failed to clean up pseudo user after all retries")
+                        .subject(subject)
+                        .metadata(json!({
+                            "base_user_id": base_id,
+                            "pseudo_user_id": pseudo_id,
+                            "attempts": SYNC_USER_ATTEMPTS,
+                            "error": e.to_string(),
+                        }))
+                        .log()
+                        .await;
+                }
+            }
+        });
+    }
+
+    pub fn spawn_metrics_poll(&self) {
+        let pool = self.get_pool().clone();
+        let metrics = self.metrics.clone();
+        let mut interval = tokio::time::interval(Duration::from_secs(60));
+
+        tokio::spawn(async move {
+            loop {
+                interval.tick().await;
+                match count_pseudo_users(&pool).await {
+                    Ok(count) => metrics.set_pseudo_user_count(count),
+                    Err(e) => warn!("Failed to poll pseudo user count: {}", e),
+                }
+            }
+        });
+    }
+
     pub fn spawn_game_cleanup(&self) {
         let pool = self.get_pool().clone();
+        let idempotency_vault = self.idempotency_vault.clone();
         let mut interval = tokio::time::interval(Duration::from_secs(86_400));
+        let mut day: u32 = 0;
 
         tokio::spawn(async move {
             loop {
                 interval.tick().await;
-                if let Err(e) = delete_non_active_games(&pool).await {
-                    let _ = SystemLogBuilder::new(&pool)
+                idempotency_vault.prune();
+
+                if day.is_multiple_of(PSEUDO_USER_CLEANUP_INTERVAL_DAYS) {
+                    purge_ghost_pseudo_users(&pool).await;
+                }
+                day = day.wrapping_add(1);
+
+                if let Err(e) = delete_expired_game_plays(&pool).await {
+                    let _ = SystemLogBuilder::new_with_function(&pool, "spawn_game_cleanup")
                         .action(LogAction::Delete)
-                        .ceverity(LogCeverity::Info)
-                        .description("Failed to purge inactive games")
+                        .ceverity(LogCeverity::Warning)
+                        .description("Failed to prune expired game plays")
                         .metadata(json!({"error": e.to_string()}))
                         .log()
                         .await;
                 }
+
+                let server = &CONFIG.server;
+                let dry_run = server.game_cleanup_dry_run;
+                match delete_non_active_games(
+                    &pool,
+                    &server.game_retention,
+                    server.game_retention_days,
+                    dry_run,
+                )
+                .await
+                {
+                    Ok(counts) => {
+                        let description = match dry_run {
+                            true => "Dry run: found inactive games to purge",
+                            false => "Purged inactive games",
+                        };
+
+                        let _ = SystemLogBuilder::new_with_function(&pool, "spawn_game_cleanup")
+                            .action(LogAction::Delete)
+                            .ceverity(LogCeverity::Info)
+                            .description(description)
+                            .metadata(json!({
+                                "counts": counts,
+                                "retention": server.game_retention,
+                                "default_retention_days": server.game_retention_days,
+                                "dry_run": dry_run
+                            }))
+                            .log()
+                            .await;
+                    }
+                    Err(e) => {
+                        let _ = SystemLogBuilder::new_with_function(&pool, "spawn_game_cleanup")
+                            .action(LogAction::Delete)
+                            .ceverity(LogCeverity::Info)
+                            .description("Failed to purge inactive games")
+                            .metadata(json!({"error": e.to_string()}))
+                            .log()
+                            .await;
+                    }
+                }
+
+                match purge_expired_logs(&pool, &CONFIG.log_retention).await {
+                    Ok(purged) => {
+                        let _ = SystemLogBuilder::new_with_function(&pool, "spawn_game_cleanup")
+                            .action(LogAction::Delete)
+                            .ceverity(LogCeverity::Info)
+                            .description("Purged expired system logs")
+                            .metadata(json!(purged))
+                            .log()
+                            .await;
+                    }
+                    Err(e) => {
+                        let _ = SystemLogBuilder::new_with_function(&pool, "spawn_game_cleanup")
+                            .action(LogAction::Delete)
+                            .ceverity(LogCeverity::Warning)
+                            .description("Failed to purge expired system logs")
+                            .metadata(json!({"error": e.to_string()}))
+                            .log()
+                            .await;
+                    }
+                }
             }
         });
     }
 }
+
+async fn purge_ghost_pseudo_users(pool: &Pool<Postgres>) {
+    let retention_days = CONFIG.server.pseudo_user_retention_days;
+    match delete_ghost_pseudo_users(pool, retention_days, PSEUDO_USER_CLEANUP_BATCH_SIZE).await {
+        Ok(purged) => {
+            let _ = SystemLogBuilder::new_with_function(pool, "spawn_game_cleanup")
+                .action(LogAction::Delete)
+                .ceverity(LogCeverity::Info)
+                .description("Purged ghost pseudo users")
+                .metadata(json!({
+                    "purged": purged,
+                    "retention_days": retention_days
+                }))
+                .log()
+                .await;
+        }
+        Err(e) => {
+            let _ = SystemLogBuilder::new_with_function(pool, "spawn_game_cleanup")
+                .action(LogAction::Delete)
+                .ceverity(LogCeverity::Warning)
+                .description("Failed to purge ghost pseudo users")
+                .metadata(json!({"error": e.to_string()}))
+                .log()
+                .await;
+        }
+    }
+}
@@ -1,12 +1,15 @@
 use std::collections::HashSet;
 
-use serde::{Deserialize, Serialize};
+use chrono::Utc;
+use serde::{Deserialize, Deserializer, Serialize};
 
 use crate::models::user::Permission;
 
+/// Auth0 publishes a varying number of keys depending on where it is in a
+/// key rotation
 #[derive(Debug, Deserialize, Clone)]
 pub struct Jwks {
-    pub keys: [Jwk; 2],
+    pub keys: Vec<Jwk>,
 }
 
 #[allow(dead_code)]
@@ -24,11 +27,13 @@ pub struct Jwk {
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct Claims {
     gty: Option<String>,
+    #[serde(deserialize_with = "string_or_vec")]
     aud: Vec<String>,
     azp: String,
     exp: i32,
     iat: i32,
     iss: String,
+    #[serde(default)]
     pub scope: String,
     pub sub: String,
     pub permissions: Option<HashSet<Permission>>,
@@ -57,19 +62,71 @@ impl Claims {
         &self.sub
     }
 
+    /// Unix timestamp in seconds
+    pub fn expires_at(&self) -> i64 {
+        self.exp as i64
+    }
+
+    /// Unix timestamp in seconds
+    pub fn issued_at(&self) -> i64 {
+        self.iat as i64
+    }
+
+    /// Checked again after decoding, so a misconfigured validation can not
+    /// let expired tokens through
+    pub fn is_expired(&self, leeway: u64) -> bool {
+        self.expires_at() + (leeway as i64) < Utc::now().timestamp()
+    }
+
+    fn holds(&self, permission: &Permission) -> bool {
+        self.permissions
+            .iter()
+            .flatten()
+            .any(|held| held.implies(permission))
+    }
+
     pub fn missing_permission<I>(&self, required: I) -> Option<HashSet<Permission>>
     where
         I: IntoIterator<Item = Permission>,
     {
-        let required_iter = required.into_iter();
-        let permissions = match &self.permissions {
-            None => return Some(required_iter.collect()),
-            Some(perm) => perm,
-        };
-
         let missing: HashSet<Permission> =
-            required_iter.filter(|p| !permissions.contains(p)).collect();
+            required.into_iter().filter(|p| !self.holds(p)).collect();
 
         (!missing.is_empty()).then_some(missing)
     }
+
+    /// False for an empty `any_of`
+    #[allow(dead_code)]
+    pub fn has_any_permission<I>(&self, any_of: I) -> bool
+    where
+        I: IntoIterator<Item = Permission>,
+    {
+        any_of.into_iter().any(|p| self.holds(&p))
+    }
+
+    /// True for an empty `all_of`
+    pub fn has_all_permissions<I>(&self, all_of: I) -> bool
+    where
+        I: IntoIterator<Item = Permission>,
+    {
+        all_of.into_iter().all(|p| self.holds(&p))
+    }
+}
+
+/// Auth0 sends `aud` as a plain string when the token has a single audience
+fn string_or_vec<'de, D>(deserializer: D) -> Result<Vec<String>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum StringOrVec {
+        One(String),
+        Many(Vec<String>),
+    }
+
+    Ok(match StringOrVec::deserialize(deserializer)? {
+        StringOrVec::One(value) => vec![value],
+        StringOrVec::Many(values) => values,
+    })
 }
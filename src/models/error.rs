@@ -1,13 +1,31 @@
 use std::{collections::HashSet, time::SystemTimeError};
 
-use axum::{http::StatusCode, response::IntoResponse};
+use axum::{Json, http::StatusCode, response::IntoResponse};
+use serde::{Deserialize, Serialize};
+use serde_json::{Value, json};
 use thiserror::Error;
 use tracing::error;
+use utoipa::ToSchema;
 
 use crate::{
     client::gs_client::GSClientError, models::user::Permission, service::key_vault::KeyVaultError,
 };
 
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, ToSchema)]
+pub struct FieldError {
+    pub field: String,
+    pub message: String,
+}
+
+impl FieldError {
+    pub fn new(field: impl Into<String>, message: impl Into<String>) -> Self {
+        Self {
+            field: field.into(),
+            message: message.into(),
+        }
+    }
+}
+
 #[derive(Debug, Error)]
 pub enum ServerError {
     #[error("Sqlx failed: {0}")]
@@ -28,6 +46,9 @@ pub enum ServerError {
     #[error("Not found: {0}")]
     NotFound(String),
 
+    #[error("Conflict: {0}")]
+    Conflict(String),
+
     #[error("Request error: {0}")]
     Request(#[from] reqwest::Error),
 
@@ -45,69 +66,201 @@ pub enum ServerError {
 
     #[error("Failed to create system time: {0}")]
     TimeCreation(#[from] SystemTimeError),
+
+    #[error("Validation failed: {0:?}")]
+    Validation(Vec<FieldError>),
+
+    #[error("Payload too large: {0}")]
+    PayloadTooLarge(String),
+
+    #[error("Unsupported media type: {0}")]
+    UnsupportedMediaType(String),
+}
+
+/// Stable machine readable error codes sent in every error body, clients
+/// should match on these and not on the message
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ErrorCode {
+    Internal,
+    BadRequest,
+    Unauthorized,
+    Forbidden,
+    /// `details.missing` lists the scopes the caller lacks
+    MissingPermission,
+    AccessDenied,
+    NotFound,
+    Conflict,
+    InvalidToken,
+    /// `details.fields` holds one entry per invalid field
+    ValidationFailed,
+    PayloadTooLarge,
+    UnsupportedMediaType,
+    ThirdPartyUnavailable,
+    UpstreamUnavailable,
+}
+
+impl ErrorCode {
+    fn from_status(status: StatusCode) -> Self {
+        match status {
+            StatusCode::UNAUTHORIZED => ErrorCode::Unauthorized,
+            StatusCode::FORBIDDEN => ErrorCode::Forbidden,
+            StatusCode::NOT_FOUND => ErrorCode::NotFound,
+            StatusCode::CONFLICT => ErrorCode::Conflict,
+            StatusCode::PAYLOAD_TOO_LARGE => ErrorCode::PayloadTooLarge,
+            StatusCode::UNSUPPORTED_MEDIA_TYPE => ErrorCode::UnsupportedMediaType,
+            StatusCode::UNPROCESSABLE_ENTITY => ErrorCode::ValidationFailed,
+            status if status.is_server_error() => ErrorCode::Internal,
+            _ => ErrorCode::BadRequest,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct ErrorBody {
+    pub code: ErrorCode,
+    pub message: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub details: Option<serde_json::Value>,
+}
+
+impl ErrorBody {
+    fn new(code: ErrorCode, message: impl Into<String>) -> Option<Self> {
+        Some(Self {
+            code,
+            message: message.into(),
+            details: None,
+        })
+    }
+
+    fn with_details(code: ErrorCode, message: impl Into<String>, details: Value) -> Option<Self> {
+        Some(Self {
+            code,
+            message: message.into(),
+            details: Some(details),
+        })
+    }
+}
+
+impl ServerError {
+    pub fn code(&self) -> ErrorCode {
+        match self {
+            ServerError::Sqlx(_)
+            | ServerError::Internal(_)
+            | ServerError::Json(_)
+            | ServerError::KeyVaultError(_)
+            | ServerError::TimeCreation(_) => ErrorCode::Internal,
+            ServerError::Api(status, _) => ErrorCode::from_status(*status),
+            ServerError::Permission(_) => ErrorCode::MissingPermission,
+            ServerError::AccessDenied => ErrorCode::AccessDenied,
+            ServerError::NotFound(_) => ErrorCode::NotFound,
+            ServerError::Conflict(_) => ErrorCode::Conflict,
+            ServerError::Request(_) => ErrorCode::ThirdPartyUnavailable,
+            ServerError::JwtVerification(_) => ErrorCode::InvalidToken,
+            ServerError::GSClientError(_) => ErrorCode::UpstreamUnavailable,
+            ServerError::Validation(_) => ErrorCode::ValidationFailed,
+            ServerError::PayloadTooLarge(_) => ErrorCode::PayloadTooLarge,
+            ServerError::UnsupportedMediaType(_) => ErrorCode::UnsupportedMediaType,
+        }
+    }
 }
 
 impl IntoResponse for ServerError {
     fn into_response(self) -> axum::response::Response {
-        match self {
+        let code = self.code();
+
+        // Internal failures keep an empty body so no details leak to clients
+        let (status, body) = match self {
             ServerError::Sqlx(e) => {
                 error!("Sqlx failed with error: {:?}", e);
-                (StatusCode::INTERNAL_SERVER_ERROR, String::new())
+                (StatusCode::INTERNAL_SERVER_ERROR, None)
             }
             ServerError::Internal(e) => {
                 error!("Internal server error: {}", e);
-                (StatusCode::INTERNAL_SERVER_ERROR, String::new())
+                (StatusCode::INTERNAL_SERVER_ERROR, None)
             }
             ServerError::Api(sc, msg) => {
                 error!("Api error: {} - {}", sc, msg);
-                (sc, msg)
+                (sc, ErrorBody::new(code, msg))
             }
             ServerError::Permission(missing) => {
                 error!("Missing permission: {:?}", missing);
+                let mut scopes: Vec<&str> = missing.iter().map(Permission::scope).collect();
+                scopes.sort_unstable();
                 (
                     StatusCode::FORBIDDEN,
-                    format!("Missing permission: {:?}", missing),
+                    ErrorBody::with_details(
+                        code,
+                        "Missing permission",
+                        json!({ "missing": scopes }),
+                    ),
                 )
             }
             ServerError::NotFound(e) => {
                 error!("Entity not found: {}", e);
-                (StatusCode::NOT_FOUND, e)
+                (StatusCode::NOT_FOUND, ErrorBody::new(code, e))
+            }
+            ServerError::Conflict(e) => {
+                error!("Conflict: {}", e);
+                (StatusCode::CONFLICT, ErrorBody::new(code, e))
             }
             ServerError::AccessDenied => {
                 error!("Access denied for requesting entity");
-                (StatusCode::FORBIDDEN, String::from("Access denied"))
+                (StatusCode::FORBIDDEN, ErrorBody::new(code, "Access denied"))
             }
             ServerError::Request(e) => {
                 error!("Failed to send request: {}", e);
                 (
                     StatusCode::INTERNAL_SERVER_ERROR,
-                    String::from("Failed to access third party"),
+                    ErrorBody::new(code, "Failed to access third party"),
                 )
             }
             ServerError::JwtVerification(e) => {
                 error!("Failed to verify JWT: {}", e);
-                (StatusCode::UNAUTHORIZED, String::new())
+                (StatusCode::UNAUTHORIZED, ErrorBody::new(code, e))
             }
             ServerError::Json(e) => {
                 error!("Json error: {}", e);
-                (StatusCode::INTERNAL_SERVER_ERROR, String::new())
+                (StatusCode::INTERNAL_SERVER_ERROR, None)
             }
             ServerError::GSClientError(e) => {
                 error!("GSClient error: {}", e);
                 (
                     StatusCode::SERVICE_UNAVAILABLE,
-                    String::from("Upstream service unavailable"),
+                    ErrorBody::new(code, "Upstream service unavailable"),
                 )
             }
             ServerError::KeyVaultError(e) => {
                 error!("KeyVault error: {}", e);
-                (StatusCode::INTERNAL_SERVER_ERROR, String::new())
+                (StatusCode::INTERNAL_SERVER_ERROR, None)
             }
             ServerError::TimeCreation(e) => {
                 error!("Failed to create system time: {:?}", e);
-                (StatusCode::INTERNAL_SERVER_ERROR, String::new())
+                (StatusCode::INTERNAL_SERVER_ERROR, None)
+            }
+            ServerError::Validation(errors) => {
+                error!("Validation failed: {:?}", errors);
+                (
+                    StatusCode::UNPROCESSABLE_ENTITY,
+                    ErrorBody::with_details(code, "Validation failed", json!({ "fields": errors })),
+                )
             }
+            ServerError::PayloadTooLarge(msg) => {
+                error!("Payload too large: {}", msg);
+                (StatusCode::PAYLOAD_TOO_LARGE, ErrorBody::new(code, msg))
+            }
+            ServerError::UnsupportedMediaType(msg) => {
+                error!("Unsupported media type: {}", msg);
+                (
+                    StatusCode::UNSUPPORTED_MEDIA_TYPE,
+                    ErrorBody::new(code, msg),
+                )
+            }
+        };
+
+        match body {
+            Some(body) => (status, Json(body)).into_response(),
+            None => status.into_response(),
         }
-        .into_response()
     }
 }
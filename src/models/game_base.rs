@@ -3,26 +3,109 @@ use std::hash::Hash;
 
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
+use utoipa::{IntoParams, ToSchema};
 use uuid::Uuid;
 
+use crate::{
+    client::gs_client::GameSessionDescriptor,
+    models::{
+        error::{FieldError, ServerError},
+        game_category::CategorySlug,
+        quiz_game::QuizSession,
+        spin_game::SpinSession,
+    },
+};
+
+pub static MAX_NAME_CHARS: usize = 100;
+pub static MAX_DESCRIPTION_CHARS: usize = 500;
+pub static MAX_ENTRY_COUNT: usize = 200;
+pub static MAX_ENTRY_CHARS: usize = 300;
+pub static MAX_BULK_SAVE_COUNT: usize = 50;
+
+pub const ENVELOPE_SCHEMA_VERSION: u8 = 1;
+pub const SUPPORTED_ENVELOPE_VERSIONS: [u8; 1] = [1];
+
 pub trait GameConverter {
     fn to_json_value(&self) -> Result<serde_json::Value, serde_json::Error>;
 }
 
-#[derive(Debug, Serialize, Deserialize, Clone, sqlx::FromRow)]
+pub trait Validate {
+    fn field_errors(&self) -> Vec<FieldError>;
+
+    fn validate(&self) -> Result<(), ServerError> {
+        let errors = self.field_errors();
+        match errors.is_empty() {
+            true => Ok(()),
+            false => Err(ServerError::Validation(errors)),
+        }
+    }
+}
+
+/// Counted after trimming, as the name is stored trimmed
+pub fn check_name(name: &str, errors: &mut Vec<FieldError>) {
+    let length = name.trim().chars().count();
+    if length == 0 || length > MAX_NAME_CHARS {
+        errors.push(FieldError::new(
+            "name",
+            format!("Must be between 1 and {} characters", MAX_NAME_CHARS),
+        ));
+    }
+}
+
+pub fn check_description(description: &Option<String>, errors: &mut Vec<FieldError>) {
+    if description
+        .as_ref()
+        .is_some_and(|d| d.chars().count() > MAX_DESCRIPTION_CHARS)
+    {
+        errors.push(FieldError::new(
+            "description",
+            format!("Can not exceed {} characters", MAX_DESCRIPTION_CHARS),
+        ));
+    }
+}
+
+pub fn check_entries<'a>(
+    field: &str,
+    entries: impl ExactSizeIterator<Item = &'a str>,
+    errors: &mut Vec<FieldError>,
+) {
+    if entries.len() == 0 || entries.len() > MAX_ENTRY_COUNT {
+        errors.push(FieldError::new(
+            field,
+            format!("Must contain between 1 and {} entries", MAX_ENTRY_COUNT),
+        ));
+    }
+
+    for (idx, entry) in entries.enumerate() {
+        let length = entry.trim().chars().count();
+        if length == 0 || length > MAX_ENTRY_CHARS {
+            errors.push(FieldError::new(
+                format!("{}[{}]", field, idx),
+                format!("Must be between 1 and {} characters", MAX_ENTRY_CHARS),
+            ));
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, sqlx::FromRow, ToSchema)]
 pub struct GameBase {
     pub id: Uuid,
     pub name: String,
     pub description: Option<String>,
     pub game_type: GameType,
-    pub category: GameCategory,
+    pub category: CategorySlug,
+    /// Rounds played in the last stored session
     pub iterations: i32,
+    /// Rounds played across every stored session
+    pub total_rounds_played: i64,
     pub times_played: i32,
     pub last_played: DateTime<Utc>,
+    pub visibility: GameVisibility,
 }
 
-#[derive(Debug, Serialize, Deserialize, Hash, Clone, sqlx::Type)]
-#[sqlx(type_name = "game_category", rename_all = "lowercase")]
+/// The categories seeded into `game_category`. Games and queries carry a
+/// `CategorySlug`, this is kept until nothing refers to the old names
+#[derive(Debug, Serialize, Deserialize, Hash, Clone, ToSchema)]
 pub enum GameCategory {
     Casual,
     Random,
@@ -43,7 +126,29 @@ impl fmt::Display for GameCategory {
     }
 }
 
-#[derive(Debug, Serialize, Deserialize, sqlx::Type, PartialEq)]
+/// Public games are listed on the game pages, unlisted games can only be
+/// reached by their id and private games only by their owner and admins
+#[derive(
+    Debug, Serialize, Deserialize, Hash, Clone, Copy, Default, PartialEq, Eq, sqlx::Type, ToSchema,
+)]
+#[sqlx(type_name = "game_visibility", rename_all = "lowercase")]
+pub enum GameVisibility {
+    #[default]
+    Public,
+    Unlisted,
+    Private,
+}
+
+impl GameVisibility {
+    pub fn is_accessible(&self, owner: Option<Uuid>, user_id: Uuid, is_admin: bool) -> bool {
+        match self {
+            GameVisibility::Public | GameVisibility::Unlisted => true,
+            GameVisibility::Private => is_admin || owner == Some(user_id),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, sqlx::Type, PartialEq, ToSchema)]
 #[sqlx(type_name = "gender", rename_all = "lowercase")]
 pub enum Gender {
     #[sqlx(rename = "m")]
@@ -54,7 +159,7 @@ pub enum Gender {
     Unknown,
 }
 
-#[derive(Debug, Serialize, Deserialize, Hash, Clone, sqlx::Type)]
+#[derive(Debug, Serialize, Deserialize, Hash, Clone, PartialEq, Eq, sqlx::Type, ToSchema)]
 #[sqlx(type_name = "game_type", rename_all = "lowercase")]
 pub enum GameType {
     #[serde(rename = "Quiz")]
@@ -64,43 +169,188 @@ pub enum GameType {
 }
 
 impl GameType {
-    pub fn column_name(&self) -> &'static str {
+    pub const ALL: [GameType; 2] = [GameType::Quiz, GameType::Spin];
+
+    /// The value stored in `game_base.game_type`, also used in config keys and hub paths
+    pub fn column_value(&self) -> &'static str {
         match self {
             GameType::Quiz => "quiz",
             GameType::Spin => "spin",
         }
     }
+
+    /// The table holding the type specific half of a game
+    #[allow(dead_code)]
+    pub fn table_name(&self) -> &'static str {
+        match self {
+            GameType::Quiz => "quiz_game",
+            GameType::Spin => "spin_game",
+        }
+    }
 }
 
-#[derive(Debug, Serialize, Deserialize, Hash)]
+#[derive(Debug, Serialize, Deserialize, Hash, ToSchema)]
 pub struct GamePageQuery {
     pub page_num: u16,
     pub game_type: GameType,
-    pub category: Option<GameCategory>,
+    pub category: Option<CategorySlug>,
+    /// Orders by plays in the last 7 days instead of all time plays
+    #[serde(default)]
+    pub trending: bool,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, ToSchema, IntoParams)]
+#[into_params(parameter_in = Query)]
 pub struct SavedGamesPageQuery {
     pub page_num: u8,
+    pub game_type: Option<GameType>,
+    pub category: Option<CategorySlug>,
+    pub sort: Option<SavedGameSort>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+/// Ordering of the saved games page, both variants sort descending
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, Default, PartialEq, ToSchema)]
+pub enum SavedGameSort {
+    #[default]
+    SavedAt,
+    TimesPlayed,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct BulkSaveRequest {
+    pub base_ids: Vec<Uuid>,
+}
+
+impl Validate for BulkSaveRequest {
+    fn field_errors(&self) -> Vec<FieldError> {
+        let mut errors = Vec::new();
+        if self.base_ids.is_empty() || self.base_ids.len() > MAX_BULK_SAVE_COUNT {
+            errors.push(FieldError::new(
+                "base_ids",
+                format!("Must contain 1 to {} game ids", MAX_BULK_SAVE_COUNT),
+            ));
+        }
+        errors
+    }
+}
+
+/// `skipped` counts ids that were not changed, such as games that were
+/// already saved when saving, or not saved when unsaving
+#[derive(Debug, Serialize, Deserialize, PartialEq, ToSchema)]
+pub struct BulkSaveResponse {
+    pub saved: u64,
+    pub skipped: u64,
+}
+
+/// Game card on the game pages, flattened like `SavedGame`
+#[derive(Debug, Serialize, Deserialize, Clone, sqlx::FromRow, ToSchema)]
+pub struct GamePageItem {
+    #[serde(flatten)]
+    #[sqlx(flatten)]
+    pub game: GameBase,
+    /// Number of users that saved the game
+    pub saves: i64,
+    /// None for games made by guests or by deleted users
+    pub creator_name: Option<String>,
+}
+
+/// Flattened when serialized so existing clients keep reading the game fields
+#[derive(Debug, Serialize, Deserialize, sqlx::FromRow, ToSchema)]
+pub struct SavedGame {
+    #[serde(flatten)]
+    #[sqlx(flatten)]
+    pub game: GameBase,
+    pub saved_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct UserGamesPageQuery {
+    pub page_num: u16,
+    pub game_type: Option<GameType>,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
 pub struct InteractiveEnvelope {
     pub game_key: String,
     pub host_id: Uuid,
     pub game_type: GameType,
+    /// Envelopes sent before versioning was introduced are treated as v1
+    #[serde(default = "default_envelope_version")]
+    pub schema_version: u8,
     pub payload: serde_json::Value,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
-pub struct StandaloneEnvelope {
-    pub game_type: GameType,
-    pub payload: serde_json::Value,
+fn default_envelope_version() -> u8 {
+    ENVELOPE_SCHEMA_VERSION
+}
+
+#[derive(Debug)]
+pub enum GameSession {
+    Spin(SpinSession),
+    Quiz(QuizSession),
+}
+
+impl InteractiveEnvelope {
+    /// Deserializes and validates the payload as the declared game type
+    pub fn try_into_session(self) -> Result<GameSession, ServerError> {
+        if !SUPPORTED_ENVELOPE_VERSIONS.contains(&self.schema_version) {
+            return Err(ServerError::Validation(vec![FieldError::new(
+                "schema_version",
+                format!("Unsupported schema version {}", self.schema_version),
+            )]));
+        }
+
+        let payload_error = |e: serde_json::Error| {
+            ServerError::Validation(vec![FieldError::new(
+                "payload",
+                format!(
+                    "Payload is not a valid {} session: {}",
+                    self.game_type.column_value(),
+                    e
+                ),
+            )])
+        };
+
+        match self.game_type {
+            GameType::Spin => {
+                let session: SpinSession =
+                    serde_json::from_value(self.payload).map_err(payload_error)?;
+                session.validate()?;
+                Ok(GameSession::Spin(session))
+            }
+            GameType::Quiz => {
+                let session: QuizSession =
+                    serde_json::from_value(self.payload).map_err(payload_error)?;
+                session.validate()?;
+                Ok(GameSession::Quiz(session))
+            }
+        }
+    }
+}
+
+/// Response of every endpoint that starts a game, tagged by `mode` so clients
+/// can handle standalone and interactive games through one shape
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+#[serde(tag = "mode", rename_all = "lowercase")]
+pub enum GameStartResponse {
+    Standalone { session: serde_json::Value },
+    Interactive(GameSessionDescriptor),
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
 pub struct CreateGameRequest {
     pub name: String,
     pub description: Option<String>,
-    pub category: Option<GameCategory>,
+    pub category: Option<CategorySlug>,
+    #[serde(default)]
+    pub visibility: Option<GameVisibility>,
+}
+
+impl Validate for CreateGameRequest {
+    fn field_errors(&self) -> Vec<FieldError> {
+        let mut errors = Vec::new();
+        check_name(&self.name, &mut errors);
+        check_description(&self.description, &mut errors);
+        errors
+    }
 }
@@ -0,0 +1,179 @@
+use core::fmt;
+use std::{collections::HashMap, sync::Arc};
+
+use serde::{Deserialize, Deserializer, Serialize};
+use sqlx::{Pool, Postgres, types::Json};
+use tokio::sync::RwLock;
+use utoipa::{IntoParams, ToSchema};
+
+use crate::{
+    db::game_category::{delete_category, list_categories, upsert_category},
+    models::{
+        error::{FieldError, ServerError},
+        game_base::{GameCategory, Validate},
+    },
+    service::util::is_valid_locale,
+};
+
+const MAX_SLUG_LEN: usize = 30;
+
+/// Key of a row in `game_category`. Stored as text so categories can be added
+/// without a deploy, and lowercased when read so the old enum names still match
+#[derive(Debug, Serialize, Clone, Hash, PartialEq, Eq, PartialOrd, Ord, sqlx::Type, ToSchema)]
+#[serde(transparent)]
+#[sqlx(transparent)]
+pub struct CategorySlug(String);
+
+impl CategorySlug {
+    pub fn new(slug: impl Into<String>) -> Self {
+        Self(slug.into().to_lowercase())
+    }
+
+    /// Mirrors the `game_category_slug_format` check constraint
+    pub fn is_valid(&self) -> bool {
+        (1..=MAX_SLUG_LEN).contains(&self.0.len())
+            && self
+                .0
+                .chars()
+                .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_' || c == '-')
+    }
+}
+
+impl<'de> Deserialize<'de> for CategorySlug {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        String::deserialize(deserializer).map(Self::new)
+    }
+}
+
+impl From<GameCategory> for CategorySlug {
+    fn from(category: GameCategory) -> Self {
+        Self::new(category.to_string())
+    }
+}
+
+impl fmt::Display for CategorySlug {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, ToSchema)]
+pub struct Category {
+    pub slug: CategorySlug,
+    /// Keyed by lowercase language tag, e.g. `nb` or `en`
+    #[schema(value_type = HashMap<String, String>)]
+    pub display_name: Json<HashMap<String, String>>,
+    /// Lower number is listed first
+    #[serde(default)]
+    pub sort_order: i16,
+    /// Inactive categories are hidden from the list and can not be picked for
+    /// new games, existing games keep them
+    pub active: bool,
+}
+
+impl Validate for Category {
+    fn field_errors(&self) -> Vec<FieldError> {
+        let mut errors = Vec::new();
+        if !self.slug.is_valid() {
+            errors.push(FieldError::new(
+                "slug",
+                "Must be 1-30 lowercase letters, digits, underscores or dashes",
+            ));
+        }
+
+        let valid_names = self
+            .display_name
+            .iter()
+            .all(|(locale, name)| is_valid_locale(locale) && !name.trim().is_empty());
+        if self.display_name.is_empty() || !valid_names {
+            errors.push(FieldError::new(
+                "display_name",
+                "Must have a name for at least one locale, keyed by lowercase language tag",
+            ));
+        }
+
+        errors
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct CategoryQuery {
+    /// Admins only
+    #[serde(default)]
+    pub include_inactive: bool,
+}
+
+/// The categories are read on every filtered game page request, so they are
+/// kept in memory and written through to the table
+#[derive(Debug, Clone)]
+pub struct CategoryManager {
+    pool: Pool<Postgres>,
+    categories: Arc<RwLock<Vec<Category>>>,
+}
+
+impl CategoryManager {
+    pub async fn load(pool: &Pool<Postgres>) -> Result<Self, ServerError> {
+        let categories = list_categories(pool).await?;
+
+        Ok(Self {
+            pool: pool.clone(),
+            categories: Arc::new(RwLock::new(categories)),
+        })
+    }
+
+    pub async fn update(&self, update: Category) -> Result<Category, ServerError> {
+        // Hold the lock across the write so the cached categories and the rows cannot drift
+        let mut lock = self.categories.write().await;
+        let category = upsert_category(&self.pool, &update).await?;
+
+        match lock.iter_mut().find(|c| c.slug == category.slug) {
+            Some(existing) => *existing = category.clone(),
+            None => lock.push(category.clone()),
+        }
+        lock.sort_by(|a, b| (a.sort_order, &a.slug).cmp(&(b.sort_order, &b.slug)));
+
+        Ok(category)
+    }
+
+    pub async fn remove(&self, slug: &CategorySlug) -> Result<(), ServerError> {
+        let mut lock = self.categories.write().await;
+        delete_category(&self.pool, slug).await?;
+        lock.retain(|c| c.slug != *slug);
+        Ok(())
+    }
+
+    pub async fn list(&self, include_inactive: bool) -> Vec<Category> {
+        let lock = self.categories.read().await;
+        lock.iter()
+            .filter(|c| include_inactive || c.active)
+            .cloned()
+            .collect()
+    }
+
+    pub async fn slugs(&self) -> Vec<CategorySlug> {
+        let lock = self.categories.read().await;
+        lock.iter().map(|c| c.slug.clone()).collect()
+    }
+
+    /// Inactive categories may still be filtered on, since games keep them
+    pub async fn require_known(&self, slug: &CategorySlug) -> Result<(), ServerError> {
+        let lock = self.categories.read().await;
+        match lock.iter().any(|c| c.slug == *slug) {
+            true => Ok(()),
+            false => Err(unknown_category()),
+        }
+    }
+
+    pub async fn require_active(&self, slug: &CategorySlug) -> Result<(), ServerError> {
+        let lock = self.categories.read().await;
+        match lock.iter().any(|c| c.slug == *slug && c.active) {
+            true => Ok(()),
+            false => Err(unknown_category()),
+        }
+    }
+}
+
+fn unknown_category() -> ServerError {
+    ServerError::Validation(vec![FieldError::new("category", "Unknown category")])
+}
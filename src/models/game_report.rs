@@ -0,0 +1,69 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use utoipa::{IntoParams, ToSchema};
+use uuid::Uuid;
+
+use crate::models::{
+    error::FieldError,
+    game_base::{GameType, Validate, check_description},
+};
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, sqlx::Type, ToSchema)]
+#[sqlx(type_name = "report_reason", rename_all = "lowercase")]
+pub enum ReportReason {
+    Offensive,
+    Spam,
+    Other,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct CreateReportRequest {
+    pub reason: ReportReason,
+    pub description: Option<String>,
+}
+
+impl Validate for CreateReportRequest {
+    fn field_errors(&self) -> Vec<FieldError> {
+        let mut errors = Vec::new();
+        check_description(&self.description, &mut errors);
+        errors
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub enum ReportOutcome {
+    /// The subject has already reported this game
+    Duplicate,
+    Created,
+    /// The report pushed the game over the threshold and it is now hidden
+    Hidden(GameType),
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, sqlx::FromRow, ToSchema)]
+pub struct ReportedGame {
+    pub base_id: Uuid,
+    pub name: String,
+    pub game_type: GameType,
+    pub hidden: bool,
+    pub report_count: i64,
+    pub last_reported: DateTime<Utc>,
+}
+
+#[derive(Debug, Serialize, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct ReportsPageQuery {
+    pub page_num: u16,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, ToSchema)]
+pub enum ModerateAction {
+    /// Dismisses the reports and makes the game visible again
+    Clear,
+    /// Keeps the game hidden and resolves its reports
+    Hide,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct ModerateRequest {
+    pub action: ModerateAction,
+}
@@ -1,46 +1,181 @@
 use core::fmt;
-use std::collections::HashMap;
+use std::{
+    collections::HashMap,
+    sync::{Arc, RwLock},
+};
 
 use once_cell::sync::Lazy;
 use serde::{Deserialize, Serialize};
-use tokio::sync::Mutex;
+use sqlx::{Pool, Postgres};
+use tokio::sync::RwLock as AsyncRwLock;
+use utoipa::ToSchema;
 use uuid::Uuid;
 
-pub static INTEGRATION_NAMES: Lazy<Mutex<HashMap<String, IntegrationName>>> =
-    Lazy::new(|| Mutex::new(HashMap::new()));
+use crate::{
+    db::integration::list_integrations,
+    models::{
+        error::{FieldError, ServerError},
+        game_base::Validate,
+    },
+};
 
-pub static INTEGRATION_IDS: Lazy<Mutex<HashMap<IntegrationName, Uuid>>> =
-    Lazy::new(|| Mutex::new(HashMap::new()));
+const MAX_INTEGRATION_NAME_LEN: usize = 40;
 
-#[derive(Debug, Serialize, Deserialize, sqlx::FromRow)]
+#[deprecated(note = "Look up integrations through `AppState::get_integrations`")]
+pub static INTEGRATION_NAMES: Lazy<AsyncRwLock<HashMap<String, IntegrationName>>> =
+    Lazy::new(|| AsyncRwLock::new(HashMap::new()));
+
+#[deprecated(note = "Look up integrations through `AppState::get_integrations`")]
+pub static INTEGRATION_IDS: Lazy<AsyncRwLock<HashMap<IntegrationName, Uuid>>> =
+    Lazy::new(|| AsyncRwLock::new(HashMap::new()));
+
+/// Replaces both deprecated maps. Fetch before calling, the locks are only
+/// held for the swap, and both are taken before either map is written
+#[allow(deprecated)]
+pub async fn swap_integrations(
+    names: HashMap<String, IntegrationName>,
+    ids: HashMap<IntegrationName, Uuid>,
+) {
+    let mut names_lock = INTEGRATION_NAMES.write().await;
+    let mut ids_lock = INTEGRATION_IDS.write().await;
+    *names_lock = names;
+    *ids_lock = ids;
+}
+
+#[derive(Debug, Serialize, Deserialize, sqlx::FromRow, ToSchema)]
 pub struct Integration {
     pub id: Uuid,
     pub subject: String,
     pub name: IntegrationName,
+    pub url: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct CreateIntegrationRequest {
+    pub subject: String,
+    pub name: IntegrationName,
+    pub url: Option<String>,
+}
+
+impl Validate for CreateIntegrationRequest {
+    fn field_errors(&self) -> Vec<FieldError> {
+        let mut errors = Vec::new();
+        if !self.name.is_valid() {
+            errors.push(FieldError::new(
+                "name",
+                "Must be 1-40 lowercase letters, digits or underscores",
+            ));
+        }
+
+        errors
+    }
+}
+
+/// Name of a registered machine client. Stored as text so new integrations
+/// can be registered without a migration, the known ones are kept as constants
+#[derive(Debug, Serialize, Deserialize, Clone, Hash, PartialEq, Eq, sqlx::Type, ToSchema)]
+#[serde(transparent)]
+#[sqlx(transparent)]
+pub struct IntegrationName(String);
+
+impl IntegrationName {
+    pub const AUTH0: &str = "auth0";
+    pub const SESSION: &str = "session";
+    pub const STRIPE: &str = "stripe";
+
+    pub fn new(name: impl Into<String>) -> Self {
+        Self(name.into())
+    }
+
+    /// Mirrors the `integration_name_format` check constraint
+    pub fn is_valid(&self) -> bool {
+        (1..=MAX_INTEGRATION_NAME_LEN).contains(&self.0.len())
+            && self
+                .0
+                .chars()
+                .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_')
+    }
+}
+
+impl From<&str> for IntegrationName {
+    fn from(name: &str) -> Self {
+        Self::new(name)
+    }
 }
 
-#[derive(Debug, Serialize, Deserialize, Clone, Hash, PartialEq, Eq, sqlx::Type)]
-#[sqlx(type_name = "integration_name", rename_all = "lowercase")]
-pub enum IntegrationName {
-    Auth0,
-    Session,
+impl PartialEq<&str> for IntegrationName {
+    fn eq(&self, other: &&str) -> bool {
+        self.0 == *other
+    }
 }
 
 impl fmt::Display for IntegrationName {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        match self {
-            IntegrationName::Auth0 => write!(f, "auth0"),
-            IntegrationName::Session => write!(f, "game_session"),
+        write!(f, "{}", self.0)
+    }
+}
+
+#[derive(Debug, Default)]
+struct IntegrationSnapshot {
+    names: HashMap<String, IntegrationName>,
+    ids: HashMap<IntegrationName, Uuid>,
+}
+
+impl IntegrationSnapshot {
+    fn from_integrations(integrations: &[Integration]) -> Self {
+        Self {
+            names: integrations
+                .iter()
+                .map(|i| (i.subject.clone(), i.name.clone()))
+                .collect(),
+            ids: integrations
+                .iter()
+                .map(|i| (i.name.clone(), i.id))
+                .collect(),
         }
     }
 }
 
-impl IntegrationName {
-    pub async fn from_subject(
-        subject: &str,
-        integrations: &Mutex<HashMap<String, IntegrationName>>,
-    ) -> Option<IntegrationName> {
-        let lock = integrations.lock().await;
-        lock.get(subject).cloned()
+/// Registered integrations by subject and name. Readers take the current
+/// snapshot and a reload swaps in a new one, so lookups never see a
+/// half-updated pair and never wait on the database
+#[derive(Debug, Default)]
+pub struct IntegrationRegistry {
+    current: RwLock<Arc<IntegrationSnapshot>>,
+}
+
+impl IntegrationRegistry {
+    pub async fn load(pool: &Pool<Postgres>) -> Result<Self, ServerError> {
+        let registry = Self::default();
+        registry.reload(pool).await?;
+        Ok(registry)
+    }
+
+    pub async fn reload(&self, pool: &Pool<Postgres>) -> Result<(), ServerError> {
+        let integrations = list_integrations(pool).await?;
+        let snapshot = IntegrationSnapshot::from_integrations(&integrations);
+
+        // Keep the deprecated statics in sync until nothing reads them
+        swap_integrations(snapshot.names.clone(), snapshot.ids.clone()).await;
+
+        let mut current = self.current.write().unwrap_or_else(|e| e.into_inner());
+        *current = Arc::new(snapshot);
+
+        Ok(())
+    }
+
+    fn snapshot(&self) -> Arc<IntegrationSnapshot> {
+        self.current
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .clone()
+    }
+
+    pub fn name_for_subject(&self, subject: &str) -> Option<IntegrationName> {
+        self.snapshot().names.get(subject).cloned()
+    }
+
+    pub fn ids(&self) -> HashMap<IntegrationName, Uuid> {
+        self.snapshot().ids.clone()
     }
 }
@@ -0,0 +1,72 @@
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+use utoipa::{IntoParams, ToSchema};
+use uuid::Uuid;
+
+use crate::models::{error::FieldError, game_base::Validate};
+
+/// Matches the `VARCHAR(5)` word columns, keys are read aloud so they stay short
+const MAX_WORD_CHARS: usize = 5;
+
+/// Which half of a "prefix suffix" game key a word is used for
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, sqlx::Type, ToSchema)]
+#[serde(rename_all = "lowercase")]
+#[sqlx(type_name = "text", rename_all = "lowercase")]
+pub enum WordSlot {
+    Prefix,
+    Suffix,
+}
+
+impl WordSlot {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            WordSlot::Prefix => "prefix",
+            WordSlot::Suffix => "suffix",
+        }
+    }
+}
+
+impl fmt::Display for WordSlot {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, sqlx::FromRow, ToSchema)]
+pub struct Word {
+    pub id: Uuid,
+    pub slot: WordSlot,
+    pub word: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct CreateWordRequest {
+    pub slot: WordSlot,
+    pub word: String,
+}
+
+impl Validate for CreateWordRequest {
+    fn field_errors(&self) -> Vec<FieldError> {
+        let mut errors = Vec::new();
+        let length = self.word.chars().count();
+        let lowercase = self
+            .word
+            .chars()
+            .all(|c| c.is_alphabetic() && c.is_lowercase());
+        if length == 0 || length > MAX_WORD_CHARS || !lowercase {
+            errors.push(FieldError::new(
+                "word",
+                format!("Must be 1-{} lowercase letters", MAX_WORD_CHARS),
+            ));
+        }
+
+        errors
+    }
+}
+
+#[derive(Debug, Default, Serialize, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct WordQuery {
+    pub slot: Option<WordSlot>,
+}
@@ -2,9 +2,13 @@ pub mod app_state;
 pub mod auth;
 pub mod error;
 pub mod game_base;
+pub mod game_category;
+pub mod game_report;
 pub mod integration;
+pub mod key_vault;
 pub mod popup_manager;
 pub mod quiz_game;
+pub mod request_context;
 pub mod spin_game;
 pub mod system_log;
 pub mod user;
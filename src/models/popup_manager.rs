@@ -1,9 +1,22 @@
-use std::sync::Arc;
+use std::{collections::HashMap, sync::Arc};
 
+use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
+use sqlx::{Pool, Postgres, types::Json};
 use tokio::sync::RwLock;
+use utoipa::{IntoParams, ToSchema};
+use uuid::Uuid;
 
-#[derive(Debug, Serialize, Deserialize, Clone)]
+use crate::{
+    db::popup_manager::{delete_popup, list_popups, upsert_popup},
+    models::{
+        error::{FieldError, ServerError},
+        game_base::Validate,
+    },
+    service::util::is_valid_locale,
+};
+
+#[derive(Debug, Serialize, Deserialize, Clone, ToSchema)]
 pub struct PagedResponse<T> {
     items: Vec<T>,
     has_next: bool,
@@ -13,39 +26,219 @@ impl<T> PagedResponse<T> {
     pub fn new(items: Vec<T>, has_next: bool) -> Self {
         Self { items, has_next }
     }
+
+    #[allow(dead_code)]
+    pub fn items(&self) -> &[T] {
+        &self.items
+    }
+
+    #[allow(dead_code)]
+    pub fn has_next(&self) -> bool {
+        self.has_next
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, ToSchema)]
+pub struct PopupContent {
+    pub heading: String,
+    pub paragraph: String,
+}
+
+impl PopupContent {
+    pub fn new(heading: impl Into<String>, paragraph: impl Into<String>) -> Self {
+        Self {
+            heading: heading.into(),
+            paragraph: paragraph.into(),
+        }
+    }
 }
 
-#[derive(Debug, Serialize, Deserialize, Clone)]
+/// Popups are ordered by priority, which is stored as SMALLINT
+pub static MAX_POPUP_PRIORITY: i16 = u8::MAX as i16;
+
+#[derive(Debug, Serialize, Deserialize, Clone, ToSchema)]
 pub struct ClientPopup {
+    #[serde(default = "Uuid::new_v4")]
+    pub popup_id: Uuid,
+    /// Keyed by lowercase language tag, e.g. `nb` or `en-gb`
+    #[schema(value_type = HashMap<String, PopupContent>)]
+    pub content: Json<HashMap<String, PopupContent>>,
+    /// Served when none of the requested locales has content, must be in `content`
+    pub default_locale: String,
+    pub active: bool,
+    #[serde(default)]
+    pub active_from: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub active_until: Option<DateTime<Utc>>,
+    /// Lower number means higher priority
+    #[serde(default)]
+    pub priority: i16,
+}
+
+impl Default for ClientPopup {
+    fn default() -> Self {
+        Self {
+            popup_id: Uuid::new_v4(),
+            content: Json(HashMap::from([
+                (
+                    "nb".to_string(),
+                    PopupContent::new("Velkommen", "Takk for at du har lastet ned appen vår!"),
+                ),
+                (
+                    "en".to_string(),
+                    PopupContent::new("Welcome", "Thanks for downloading our app!"),
+                ),
+            ])),
+            default_locale: "nb".to_string(),
+            active: false,
+            active_from: None,
+            active_until: None,
+            priority: MAX_POPUP_PRIORITY,
+        }
+    }
+}
+
+impl ClientPopup {
+    pub fn is_visible(&self, now: DateTime<Utc>) -> bool {
+        let started = self.active_from.is_none_or(|from| from <= now);
+        let ended = self.active_until.is_some_and(|until| until < now);
+
+        self.active && started && !ended
+    }
+
+    /// Picks the first requested locale with content, trying the language
+    /// without its region before moving on, e.g. `en-US` then `en`. Falls
+    /// back to the default locale
+    pub fn localize(&self, requested: &[String]) -> LocalizedPopup {
+        let locale = requested
+            .iter()
+            .map(|locale| locale.to_lowercase())
+            .flat_map(|locale| {
+                let language = locale.split('-').next().map(str::to_string);
+                [Some(locale), language]
+            })
+            .flatten()
+            .find(|locale| self.content.contains_key(locale))
+            .unwrap_or_else(|| self.default_locale.clone());
+
+        let content = self
+            .content
+            .get(&locale)
+            .cloned()
+            .unwrap_or_else(|| PopupContent::new("", ""));
+
+        LocalizedPopup {
+            popup_id: self.popup_id,
+            locale,
+            heading: content.heading,
+            paragraph: content.paragraph,
+            active: self.active,
+            active_from: self.active_from,
+            active_until: self.active_until,
+            priority: self.priority,
+        }
+    }
+}
+
+impl Validate for ClientPopup {
+    fn field_errors(&self) -> Vec<FieldError> {
+        let mut errors = Vec::new();
+
+        if !self.content.keys().all(|locale| is_valid_locale(locale)) {
+            errors.push(FieldError::new(
+                "content",
+                "Locales must be lowercase language tags, e.g. `nb` or `en-gb`",
+            ));
+        }
+
+        if !self.content.contains_key(&self.default_locale) {
+            errors.push(FieldError::new(
+                "default_locale",
+                "Must have an entry in content",
+            ));
+        }
+
+        if !(0..=MAX_POPUP_PRIORITY).contains(&self.priority) {
+            errors.push(FieldError::new(
+                "priority",
+                format!("Must be between 0 and {}", MAX_POPUP_PRIORITY),
+            ));
+        }
+
+        errors
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct PopupQuery {
+    pub locale: Option<String>,
+}
+
+/// A popup with the content of a single locale, as served to clients
+#[derive(Debug, Serialize, Deserialize, Clone, ToSchema)]
+pub struct LocalizedPopup {
+    pub popup_id: Uuid,
+    pub locale: String,
     pub heading: String,
     pub paragraph: String,
     pub active: bool,
+    pub active_from: Option<DateTime<Utc>>,
+    pub active_until: Option<DateTime<Utc>>,
+    pub priority: i16,
 }
 
 #[derive(Debug, Clone)]
 pub struct PopupManager {
-    popup: Arc<RwLock<ClientPopup>>,
+    pool: Pool<Postgres>,
+    popups: Arc<RwLock<Vec<ClientPopup>>>,
 }
 
 impl PopupManager {
-    pub fn new() -> Self {
-        Self {
-            popup: Arc::new(RwLock::new(ClientPopup {
-                heading: "Velkommen".to_string(),
-                paragraph: "Takk for at du har lastet ned appen vår!".to_string(),
-                active: false,
-            })),
+    pub async fn load(pool: &Pool<Postgres>) -> Result<Self, ServerError> {
+        let popups = list_popups(pool).await?;
+
+        Ok(Self {
+            pool: pool.clone(),
+            popups: Arc::new(RwLock::new(popups)),
+        })
+    }
+
+    pub async fn update(&self, update: ClientPopup) -> Result<ClientPopup, ServerError> {
+        // Hold the lock across the write so the cached popups and the rows cannot drift
+        let mut lock = self.popups.write().await;
+        let popup = upsert_popup(&self.pool, &update).await?;
+
+        match lock.iter_mut().find(|p| p.popup_id == popup.popup_id) {
+            Some(existing) => *existing = popup.clone(),
+            None => lock.push(popup.clone()),
         }
+        lock.sort_by_key(|p| p.priority);
+
+        Ok(popup)
     }
 
-    pub async fn update(&self, update: ClientPopup) -> ClientPopup {
-        let mut lock = self.popup.write().await;
-        *lock = update.clone();
-        update
+    pub async fn remove(&self, popup_id: Uuid) -> Result<(), ServerError> {
+        let mut lock = self.popups.write().await;
+        delete_popup(&self.pool, popup_id).await?;
+        lock.retain(|p| p.popup_id != popup_id);
+        Ok(())
     }
 
+    /// Returns the highest priority popup that is currently visible, or the
+    /// inactive default when there is nothing to show
     pub async fn read(&self) -> ClientPopup {
-        let lock = self.popup.read().await;
+        let lock = self.popups.read().await;
+        let now = Utc::now();
+
+        lock.iter()
+            .find(|p| p.is_visible(now))
+            .cloned()
+            .unwrap_or_default()
+    }
+
+    pub async fn list(&self) -> Vec<ClientPopup> {
+        let lock = self.popups.read().await;
         lock.clone()
     }
 }
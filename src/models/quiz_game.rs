@@ -1,7 +1,23 @@
 use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
 use uuid::Uuid;
 
-use crate::models::game_base::{CreateGameRequest, GameCategory, GameConverter};
+use crate::models::{
+    error::FieldError,
+    game_base::{
+        CreateGameRequest, GameCategory, GameConverter, GameVisibility, MAX_ENTRY_CHARS, Validate,
+        check_description, check_entries, check_name,
+    },
+    game_category::CategorySlug,
+};
+
+/// Payloads without a schema version carry questions as plain strings
+pub static LEGACY_QUIZ_SCHEMA_VERSION: i16 = 1;
+pub static QUIZ_SCHEMA_VERSION: i16 = 2;
+
+fn legacy_schema_version() -> i16 {
+    LEGACY_QUIZ_SCHEMA_VERSION
+}
 
 impl GameConverter for QuizSession {
     fn to_json_value(&self) -> Result<serde_json::Value, serde_json::Error> {
@@ -9,28 +25,121 @@ impl GameConverter for QuizSession {
     }
 }
 
-#[derive(Debug, Serialize, Deserialize, sqlx::FromRow)]
+impl Validate for QuizSession {
+    fn field_errors(&self) -> Vec<FieldError> {
+        let mut errors = Vec::new();
+        check_name(&self.name, &mut errors);
+        check_description(&self.description, &mut errors);
+
+        let texts = self.questions.iter().map(|q| q.text.as_str());
+        check_entries("questions", texts, &mut errors);
+
+        for (idx, question) in self.questions.iter().enumerate() {
+            if question
+                .options
+                .iter()
+                .any(|o| o.chars().count() > MAX_ENTRY_CHARS)
+            {
+                errors.push(FieldError::new(
+                    format!("questions[{}].options", idx),
+                    format!("Options can not exceed {} characters", MAX_ENTRY_CHARS),
+                ));
+            }
+        }
+
+        errors
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, ToSchema)]
+#[serde(from = "QuizQuestionFormat")]
+pub struct QuizQuestion {
+    pub id: Uuid,
+    pub text: String,
+    pub options: Vec<String>,
+    pub correct_index: Option<i16>,
+    pub seconds: Option<i16>,
+}
+
+impl QuizQuestion {
+    pub fn from_text(text: impl Into<String>) -> Self {
+        Self {
+            id: Uuid::new_v4(),
+            text: text.into(),
+            options: vec![],
+            correct_index: None,
+            seconds: None,
+        }
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum QuizQuestionFormat {
+    Legacy(String),
+    Structured {
+        #[serde(default = "Uuid::new_v4")]
+        id: Uuid,
+        text: String,
+        #[serde(default)]
+        options: Vec<String>,
+        #[serde(default)]
+        correct_index: Option<i16>,
+        #[serde(default)]
+        seconds: Option<i16>,
+    },
+}
+
+impl From<QuizQuestionFormat> for QuizQuestion {
+    fn from(format: QuizQuestionFormat) -> Self {
+        match format {
+            QuizQuestionFormat::Legacy(text) => QuizQuestion::from_text(text),
+            QuizQuestionFormat::Structured {
+                id,
+                text,
+                options,
+                correct_index,
+                seconds,
+            } => QuizQuestion {
+                id,
+                text,
+                options,
+                correct_index,
+                seconds,
+            },
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
 pub struct QuizSession {
+    #[serde(default = "legacy_schema_version")]
+    pub schema_version: i16,
     pub base_id: Uuid,
     pub quiz_id: Uuid,
     pub name: String,
     pub description: Option<String>,
-    pub category: GameCategory,
+    pub category: CategorySlug,
+    #[serde(default)]
+    pub visibility: GameVisibility,
     pub iterations: i32,
     pub current_iteration: i32,
-    pub questions: Vec<String>,
+    pub questions: Vec<QuizQuestion>,
     pub times_played: i32,
 }
 
 impl QuizSession {
     pub fn from_create_request(request: CreateGameRequest) -> Self {
         Self {
+            schema_version: QUIZ_SCHEMA_VERSION,
             base_id: Uuid::new_v4(),
             quiz_id: Uuid::new_v4(),
             name: request.name,
             description: request.description,
-            state: QuizGameState::Initialized,
-            category: request.category.unwrap_or(GameCategory::Default),
+            category: request
+                .category
+                .unwrap_or_else(|| GameCategory::Default.into()),
+            visibility: request.visibility.unwrap_or_default(),
             iterations: 0,
             current_iteration: 0,
             questions: vec![],
@@ -0,0 +1,80 @@
+use axum::{
+    extract::FromRequestParts,
+    http::{StatusCode, request::Parts},
+};
+use uuid::Uuid;
+
+use crate::models::{
+    auth::Claims,
+    error::ServerError,
+    user::{Permission, SubjectId},
+};
+
+/// Who is calling and with which claims, attached by `auth_mw` and `webhook_mw`
+#[derive(Debug, Clone)]
+pub struct RequestContext {
+    pub subject: SubjectId,
+    pub claims: Claims,
+    pub request_id: Uuid,
+}
+
+impl RequestContext {
+    pub fn new(subject: SubjectId, claims: Claims, request_id: Uuid) -> Self {
+        Self {
+            subject,
+            claims,
+            request_id,
+        }
+    }
+
+    /// Id of a pseudo or base user, integrations are denied
+    pub fn require_user(&self) -> Result<Uuid, ServerError> {
+        match self.subject {
+            SubjectId::PseudoUser(id) | SubjectId::BaseUser(id) => Ok(id),
+            SubjectId::Integration(_) => Err(ServerError::AccessDenied),
+        }
+    }
+
+    pub fn require_base_user(&self) -> Result<Uuid, ServerError> {
+        match self.subject {
+            SubjectId::BaseUser(id) => Ok(id),
+            _ => Err(ServerError::AccessDenied),
+        }
+    }
+
+    /// Users are let through as is, integrations need `permission`
+    pub fn require_user_or_permission(&self, permission: Permission) -> Result<(), ServerError> {
+        match self.subject {
+            SubjectId::PseudoUser(_) | SubjectId::BaseUser(_) => Ok(()),
+            SubjectId::Integration(_) => self.require_permission(permission),
+        }
+    }
+
+    pub fn require_permission(&self, permission: Permission) -> Result<(), ServerError> {
+        match self.claims.missing_permission([permission]) {
+            Some(missing) => Err(ServerError::Permission(missing)),
+            None => Ok(()),
+        }
+    }
+
+    pub fn has_permission(&self, permission: Permission) -> bool {
+        self.claims.has_all_permissions([permission])
+    }
+}
+
+impl<S: Send + Sync> FromRequestParts<S> for RequestContext {
+    type Rejection = ServerError;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        parts
+            .extensions
+            .get::<RequestContext>()
+            .cloned()
+            .ok_or_else(|| {
+                ServerError::Api(
+                    StatusCode::UNAUTHORIZED,
+                    "Request is not authenticated".into(),
+                )
+            })
+    }
+}
@@ -1,8 +1,24 @@
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
+use sqlx::types::Json;
 use uuid::Uuid;
 
-use crate::models::game_base::{CreateGameRequest, GameCategory, GameConverter};
+use crate::models::{
+    error::FieldError,
+    game_base::{
+        CreateGameRequest, GameCategory, GameConverter, GameVisibility, Validate,
+        check_description, check_entries, check_name,
+    },
+    game_category::CategorySlug,
+};
+
+/// Payloads without a schema version carry rounds as plain strings
+pub static LEGACY_SPIN_SCHEMA_VERSION: i16 = 1;
+pub static SPIN_SCHEMA_VERSION: i16 = 2;
+
+fn legacy_schema_version() -> i16 {
+    LEGACY_SPIN_SCHEMA_VERSION
+}
 
 impl GameConverter for SpinSession {
     fn to_json_value(&self) -> Result<serde_json::Value, serde_json::Error> {
@@ -10,6 +26,82 @@ impl GameConverter for SpinSession {
     }
 }
 
+impl Validate for SpinSession {
+    fn field_errors(&self) -> Vec<FieldError> {
+        let mut errors = Vec::new();
+        check_name(&self.name, &mut errors);
+        check_description(&self.description, &mut errors);
+
+        let rounds = self.rounds.iter().map(|r| r.text.as_str());
+        check_entries("rounds", rounds, &mut errors);
+
+        for (idx, round) in self.rounds.iter().enumerate() {
+            if round.weight == 0 {
+                errors.push(FieldError::new(
+                    format!("rounds[{}].weight", idx),
+                    "Must be at least 1",
+                ));
+            }
+        }
+
+        errors
+    }
+}
+
+fn default_weight() -> u8 {
+    1
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(from = "SpinRoundFormat")]
+pub struct SpinRound {
+    pub text: String,
+    /// Relative chance of the round being picked
+    pub weight: u8,
+    /// The previous chooser can not be picked for this round
+    pub exclude_repeat: bool,
+}
+
+impl SpinRound {
+    pub fn from_text(text: impl Into<String>) -> Self {
+        Self {
+            text: text.into(),
+            weight: default_weight(),
+            exclude_repeat: false,
+        }
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum SpinRoundFormat {
+    Legacy(String),
+    Structured {
+        text: String,
+        #[serde(default = "default_weight")]
+        weight: u8,
+        #[serde(default)]
+        exclude_repeat: bool,
+    },
+}
+
+impl From<SpinRoundFormat> for SpinRound {
+    fn from(format: SpinRoundFormat) -> Self {
+        match format {
+            SpinRoundFormat::Legacy(text) => SpinRound::from_text(text),
+            SpinRoundFormat::Structured {
+                text,
+                weight,
+                exclude_repeat,
+            } => SpinRound {
+                text,
+                weight,
+                exclude_repeat,
+            },
+        }
+    }
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct SpinGamePlayer {
     pub user_id: Uuid,
@@ -23,34 +115,37 @@ pub struct SpinGame {
     pub base_id: Uuid,
     pub name: String,
     pub description: Option<String>,
-    pub state: SpinGameState,
-    pub category: GameCategory,
+    pub category: CategorySlug,
+    pub visibility: GameVisibility,
     pub iterations: i32,
     pub times_played: i32,
     pub last_played: DateTime<Utc>,
-    pub rounds: Vec<String>,
+    pub rounds: Json<Vec<SpinRound>>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct SpinSession {
+    #[serde(default = "legacy_schema_version")]
+    pub schema_version: i16,
     pub spin_id: Uuid,
     pub base_id: Uuid,
     pub host_id: Uuid,
     pub name: String,
     pub description: Option<String>,
-    pub category: GameCategory,
+    pub category: CategorySlug,
+    #[serde(default)]
+    pub visibility: GameVisibility,
     pub iterations: i32,
     pub times_played: i32,
     pub last_played: DateTime<Utc>,
-    pub rounds: Vec<String>,
+    pub rounds: Vec<SpinRound>,
     pub players: Vec<SpinGamePlayer>,
 }
 
-
 #[derive(Debug, Serialize, Deserialize)]
 pub enum SpinGameState {
     Initialized,
-    Started
+    Started,
 }
 
 impl SpinSession {
@@ -61,12 +156,16 @@ impl SpinSession {
         };
 
         Self {
+            schema_version: SPIN_SCHEMA_VERSION,
             spin_id: Uuid::new_v4(),
             base_id: Uuid::new_v4(),
             host_id: user_id,
             name: request.name,
             description: request.description,
-            category: request.category.unwrap_or_else(|| GameCategory::Default),
+            category: request
+                .category
+                .unwrap_or_else(|| GameCategory::Default.into()),
+            visibility: request.visibility.unwrap_or_default(),
             iterations: 0,
             times_played: 0,
             last_played: Utc::now(),
@@ -82,16 +181,18 @@ impl SpinSession {
         };
 
         Self {
+            schema_version: SPIN_SCHEMA_VERSION,
             spin_id: game.spin_id,
             base_id: game.base_id,
             host_id: user_id,
             name: game.name,
             description: game.description,
             category: game.category,
+            visibility: game.visibility,
             iterations: game.iterations,
             times_played: game.times_played,
             last_played: game.last_played,
-            rounds: game.rounds,
+            rounds: game.rounds.0,
             players: vec![player],
         }
     }
@@ -1,9 +1,12 @@
 use core::fmt;
+use std::collections::HashMap;
 
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
+use utoipa::{IntoParams, ToSchema};
+use uuid::Uuid;
 
-#[derive(Debug, Serialize, Deserialize, sqlx::FromRow)]
+#[derive(Debug, Serialize, Deserialize, sqlx::FromRow, ToSchema)]
 pub struct SystemLog {
     pub id: i64,
     pub subject_id: String,
@@ -13,10 +16,24 @@ pub struct SystemLog {
     pub function: String,
     pub description: String,
     pub metadata: Option<serde_json::Value>,
-    pub create_at: DateTime<Utc>,
+    pub correlation_id: Option<Uuid>,
+    pub created_at: DateTime<Utc>,
 }
 
-#[derive(Debug, Serialize, Deserialize, sqlx::Type)]
+/// A log entry as written, the id and timestamp are set on insert
+#[derive(Debug)]
+pub struct NewSystemLog {
+    pub subject_id: String,
+    pub subject_type: SubjectType,
+    pub action: LogAction,
+    pub ceverity: LogCeverity,
+    pub function: String,
+    pub description: String,
+    pub metadata: Option<serde_json::Value>,
+    pub correlation_id: Option<Uuid>,
+}
+
+#[derive(Debug, Serialize, Deserialize, sqlx::Type, Clone, Copy, ToSchema)]
 #[sqlx(type_name = "log_ceverity", rename_all = "lowercase")]
 pub enum LogCeverity {
     Critical,
@@ -34,7 +51,7 @@ impl fmt::Display for LogCeverity {
     }
 }
 
-#[derive(Debug, Serialize, Deserialize, sqlx::Type)]
+#[derive(Debug, Serialize, Deserialize, sqlx::Type, Clone, Copy, PartialEq, Eq, Hash, ToSchema)]
 #[sqlx(type_name = "log_action", rename_all = "lowercase")]
 pub enum LogAction {
     Create,
@@ -58,7 +75,7 @@ impl fmt::Display for LogAction {
     }
 }
 
-#[derive(Debug, Serialize, Deserialize, sqlx::Type)]
+#[derive(Debug, Serialize, Deserialize, sqlx::Type, Clone, Copy, PartialEq, Eq, Hash, ToSchema)]
 #[sqlx(type_name = "subject_type", rename_all = "lowercase")]
 pub enum SubjectType {
     #[sqlx(rename = "registered_user")]
@@ -80,26 +97,87 @@ impl fmt::Display for SubjectType {
     }
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
 pub struct SyslogPageQuery {
     pub page_num: u16,
     pub subject_type: Option<SubjectType>,
     pub action: Option<LogAction>,
     pub ceverity: Option<LogCeverity>,
+    pub from: Option<DateTime<Utc>>,
+    pub to: Option<DateTime<Utc>>,
+    pub search: Option<String>,
+    pub correlation_id: Option<Uuid>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
 pub struct CreateSyslogRequest {
     pub action: Option<LogAction>,
     pub ceverity: Option<LogCeverity>,
     pub description: Option<String>,
     pub function: Option<String>,
     pub metadata: Option<serde_json::Value>,
+    /// Ties the log to the request that caused it, e.g. the `traceparent`
+    /// trace id forwarded to the integration
+    pub correlation_id: Option<Uuid>,
+}
+
+pub static MAX_SYSLOG_BATCH_SIZE: usize = 100;
+
+impl CreateSyslogRequest {
+    pub fn validate(&self) -> Result<(), String> {
+        match &self.description {
+            None => return Err("Description is required".into()),
+            Some(d) if d.trim().is_empty() => return Err("Description is required".into()),
+            Some(d) if d.chars().count() > 512 => {
+                return Err("Description can not exceed 512 characters".into());
+            }
+            _ => {}
+        }
+
+        if self
+            .function
+            .as_ref()
+            .is_some_and(|f| f.chars().count() > 50)
+        {
+            return Err("Function can not exceed 50 characters".into());
+        }
+
+        Ok(())
+    }
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct BatchSyslogResult {
+    pub index: usize,
+    pub created: bool,
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
 pub struct LogCategoryCount {
     pub info: i64,
     pub warning: i64,
     pub critical: i64,
 }
+
+#[derive(Debug, Serialize, Deserialize, Default, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct LogStatsQuery {
+    pub from: Option<DateTime<Utc>>,
+    pub to: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Serialize, Deserialize, sqlx::FromRow, ToSchema)]
+pub struct FunctionCount {
+    pub function: String,
+    pub count: i64,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct LogStats {
+    pub ceverity: LogCategoryCount,
+    pub action: HashMap<LogAction, i64>,
+    pub subject_type: HashMap<SubjectType, i64>,
+    pub top_functions: Vec<FunctionCount>,
+}
@@ -1,15 +1,25 @@
 use chrono::{DateTime, NaiveDate, Utc};
 use serde::{Deserialize, Serialize};
+use utoipa::{IntoParams, ToSchema};
 use uuid::Uuid;
 
-use crate::models::{game_base::Gender, integration::IntegrationName};
+use crate::models::{
+    error::ServerError,
+    game_base::Gender,
+    integration::IntegrationName,
+    system_log::{LogCategoryCount, SubjectType},
+};
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
 pub struct ListUsersQuery {
     pub page_num: u8,
+    pub created_after: Option<DateTime<Utc>>,
+    pub created_before: Option<DateTime<Utc>>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
 pub struct EnsureUserQuery {
     pub pseudo_id: Option<Uuid>,
 }
@@ -20,20 +30,107 @@ pub enum Permission {
     ReadAdmin,
     #[serde(rename(deserialize = "write:admin"))]
     WriteAdmin,
+    #[serde(rename(deserialize = "read:game"))]
+    ReadGame,
     #[serde(rename(deserialize = "write:game"))]
     WriteGame,
     #[serde(rename(deserialize = "write:system_log"))]
     WriteSystemLog,
+    #[serde(rename(deserialize = "write:billing"))]
+    WriteBilling,
+    /// Catch-all that implies every other permission, not granted to any role yet
+    #[serde(rename(deserialize = "super:admin"))]
+    SuperAdmin,
 }
 
-#[derive(Debug, Serialize, Deserialize, Clone)]
+impl Permission {
+    #[allow(dead_code)]
+    pub const ALL: [Permission; 7] = [
+        Permission::ReadAdmin,
+        Permission::WriteAdmin,
+        Permission::ReadGame,
+        Permission::WriteGame,
+        Permission::WriteSystemLog,
+        Permission::WriteBilling,
+        Permission::SuperAdmin,
+    ];
+
+    /// The Auth0 scope name of the permission
+    pub fn scope(&self) -> &'static str {
+        match self {
+            Permission::ReadAdmin => "read:admin",
+            Permission::WriteAdmin => "write:admin",
+            Permission::ReadGame => "read:game",
+            Permission::WriteGame => "write:game",
+            Permission::WriteSystemLog => "write:system_log",
+            Permission::WriteBilling => "write:billing",
+            Permission::SuperAdmin => "super:admin",
+        }
+    }
+
+    /// Whether holding this permission also grants `other`, so admin roles
+    /// in Auth0 do not need every scope assigned
+    pub fn implies(&self, other: &Permission) -> bool {
+        if self == other {
+            return true;
+        }
+
+        match self {
+            Permission::SuperAdmin => true,
+            Permission::WriteAdmin => matches!(
+                other,
+                Permission::ReadAdmin
+                    | Permission::ReadGame
+                    | Permission::WriteGame
+                    | Permission::WriteSystemLog
+            ),
+            Permission::WriteGame => matches!(other, Permission::ReadGame),
+            Permission::ReadAdmin
+            | Permission::ReadGame
+            | Permission::WriteSystemLog
+            | Permission::WriteBilling => false,
+        }
+    }
+}
+
+/// Serialized as `{ "type": "base_user", "id": "..." }`
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(tag = "type", content = "id", rename_all = "snake_case")]
 pub enum SubjectId {
     PseudoUser(Uuid),
     BaseUser(Uuid),
     Integration(IntegrationName),
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+impl From<&SubjectId> for SubjectType {
+    fn from(subject: &SubjectId) -> Self {
+        match subject {
+            SubjectId::PseudoUser(_) => SubjectType::GuestUser,
+            SubjectId::BaseUser(_) => SubjectType::RegisteredUser,
+            SubjectId::Integration(_) => SubjectType::Integration,
+        }
+    }
+}
+
+impl SubjectId {
+    /// The user id, or the integration name
+    pub fn id_string(&self) -> String {
+        match self {
+            SubjectId::PseudoUser(id) | SubjectId::BaseUser(id) => id.to_string(),
+            SubjectId::Integration(name) => name.to_string(),
+        }
+    }
+
+    /// Denies every subject except the listed integrations
+    pub fn require_integration(&self, allowed: &[&str]) -> Result<&IntegrationName, ServerError> {
+        match self {
+            SubjectId::Integration(name) if allowed.iter().any(|a| name == a) => Ok(name),
+            _ => Err(ServerError::AccessDenied),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
 pub struct Auth0User {
     #[serde(rename = "user_id")]
     pub auth0_id: String,
@@ -50,6 +147,11 @@ pub struct Auth0User {
     pub family_name: Option<String>,
 }
 
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct Auth0DeleteEvent {
+    pub user_id: String,
+}
+
 #[derive(Debug, Serialize, Deserialize, sqlx::Type)]
 #[sqlx(type_name = "user_type", rename_all = "lowercase")]
 pub enum UserType {
@@ -64,9 +166,11 @@ pub enum UserType {
 pub struct PseudoUser {
     pub id: Uuid,
     pub last_active: DateTime<Utc>,
+    /// Set when the pseudo user belongs to a registered user
+    pub base_user_id: Option<Uuid>,
 }
 
-#[derive(Debug, Serialize, Deserialize, sqlx::FromRow)]
+#[derive(Debug, Serialize, Deserialize, sqlx::FromRow, ToSchema)]
 pub struct BaseUser {
     pub id: Uuid,
     pub username: String,
@@ -79,16 +183,17 @@ pub struct BaseUser {
     pub given_name: Option<String>,
     pub created_at: DateTime<Utc>,
     pub birth_date: Option<NaiveDate>,
+    pub last_login_at: Option<DateTime<Utc>>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
 #[serde(tag = "role", content = "user")]
 pub enum UserRole {
     Admin(BaseUser),
     BaseUser(BaseUser),
 }
 
-#[derive(Debug, Serialize, Deserialize, Default, PartialEq)]
+#[derive(Debug, Serialize, Deserialize, Default, PartialEq, ToSchema)]
 pub struct PatchUserRequest {
     pub username: Option<String>,
     pub gender: Option<Gender>,
@@ -97,22 +202,34 @@ pub struct PatchUserRequest {
     pub birth_date: Option<NaiveDate>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
 pub struct ActivityStats {
     pub total_game_count: i64,
+    /// Registered base users only, same as `base_user_count` and kept for
+    /// existing clients
     pub total_user_count: i64,
+    pub base_user_count: i64,
+    /// Every pseudo user, which includes the ones backing a base user
+    pub pseudo_user_count: i64,
+    /// Base users that signed in during the last 7 days
+    pub recently_logged_in_count: i64,
     pub recent: RecentUserStats,
     pub average: AverageUserStats,
+    pub log_counts: LogCategoryCount,
 }
 
-#[derive(Debug, Serialize, Deserialize, sqlx::FromRow)]
+/// Active pseudo users, where the `*_users` counts only include anonymous
+/// guests and registered users are counted separately
+#[derive(Debug, Serialize, Deserialize, sqlx::FromRow, ToSchema)]
 pub struct RecentUserStats {
     pub this_month_users: i64,
     pub this_week_users: i64,
     pub todays_users: i64,
+    pub this_month_registered_users: i64,
+    pub this_week_registered_users: i64,
 }
 
-#[derive(Debug, Serialize, Deserialize, sqlx::FromRow)]
+#[derive(Debug, Serialize, Deserialize, sqlx::FromRow, ToSchema)]
 pub struct AverageUserStats {
     pub avg_month_users: f64,
     pub avg_week_users: f64,
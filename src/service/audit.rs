@@ -0,0 +1,58 @@
+use serde_json::json;
+use sqlx::{Pool, Postgres};
+
+use crate::{
+    models::{
+        error::ServerError,
+        system_log::{LogAction, LogCeverity},
+        user::SubjectId,
+    },
+    service::{system_log_builder::SystemLogBuilder, trace_context::TraceContext},
+};
+
+/// Writes an audit entry to the system log for the outcome of a mutation,
+/// the write is fire-and-forget and the result is passed through unchanged
+pub trait Audited: Sized {
+    fn audited(
+        self,
+        pool: &Pool<Postgres>,
+        subject: &SubjectId,
+        action: LogAction,
+        function: &str,
+        metadata: serde_json::Value,
+    ) -> Self;
+}
+
+impl<T> Audited for Result<T, ServerError> {
+    fn audited(
+        self,
+        pool: &Pool<Postgres>,
+        subject: &SubjectId,
+        action: LogAction,
+        function: &str,
+        metadata: serde_json::Value,
+    ) -> Self {
+        let (ceverity, description, outcome) = match &self {
+            Ok(_) => (LogCeverity::Info, "Audit: game mutation", json!("success")),
+            Err(e) => (
+                LogCeverity::Warning,
+                "Audit: game mutation failed",
+                json!(e.to_string()),
+            ),
+        };
+
+        let mut builder = SystemLogBuilder::new_with_function(pool, function)
+            .subject(subject.clone())
+            .action(action)
+            .ceverity(ceverity)
+            .description(description)
+            .metadata(json!({ "outcome": outcome, "details": metadata }));
+
+        if let Some(cx) = TraceContext::current() {
+            builder = builder.correlation_id(cx.correlation_id());
+        }
+
+        builder.log_async();
+        self
+    }
+}
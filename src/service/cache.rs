@@ -1,10 +1,14 @@
 use std::{
     hash::{DefaultHasher, Hash, Hasher},
-    sync::Arc,
+    sync::{
+        Arc,
+        atomic::{AtomicU64, Ordering},
+    },
     time::{SystemTime, UNIX_EPOCH},
 };
 
 use dashmap::DashMap;
+use serde::Serialize;
 use tokio::{task::JoinHandle, time};
 use tracing::error;
 
@@ -37,10 +41,20 @@ impl<T: Clone + Sync + 'static> CacheEntry<T> {
     }
 }
 
+#[derive(Debug, Clone, Serialize)]
+pub struct CacheStats {
+    pub hits: u64,
+    pub misses: u64,
+    pub hit_rate: f64,
+    pub current_entries: usize,
+}
+
 #[derive(Debug)]
 pub struct GustCache<T: Clone + Send + Sync + 'static> {
     cache: Arc<DashMap<u64, CacheEntry<T>>>,
     ttl: u64,
+    hits: AtomicU64,
+    misses: AtomicU64,
     cleanup_task: Option<JoinHandle<()>>,
     eviction_task: Option<JoinHandle<()>>,
 }
@@ -54,6 +68,8 @@ impl<T: Clone + Send + Sync> GustCache<T> {
         let mut cache = Self {
             cache: Arc::new(DashMap::new()),
             ttl: ttl_secs,
+            hits: AtomicU64::new(0),
+            misses: AtomicU64::new(0),
             cleanup_task: None,
             eviction_task: None,
         };
@@ -75,10 +91,12 @@ impl<T: Clone + Send + Sync> GustCache<T> {
 
             if entry.timestamp + self.ttl > now {
                 entry.timestamp = now;
+                self.hits.fetch_add(1, Ordering::Relaxed);
                 return Ok(entry.value.clone());
             }
         };
 
+        self.misses.fetch_add(1, Ordering::Relaxed);
         let data = on_failure().await?;
         let cache_entry = CacheEntry::new(data.clone())?;
         self.cache.insert(key, cache_entry);
@@ -86,6 +104,36 @@ impl<T: Clone + Send + Sync> GustCache<T> {
         Ok(data)
     }
 
+    /// Removes the entry for `key` so the next `get_or` runs the loader again,
+    /// returns false if nothing was cached for the key
+    pub fn invalidate<K: Hash>(&self, key: &K) -> bool {
+        self.cache.remove(&generate_hash(key)).is_some()
+    }
+
+    pub fn stats(&self) -> CacheStats {
+        let hits = self.hits.load(Ordering::Relaxed);
+        let misses = self.misses.load(Ordering::Relaxed);
+        let hit_rate = match hits + misses {
+            0 => 0.0,
+            total => hits as f64 / total as f64,
+        };
+
+        CacheStats {
+            hits,
+            misses,
+            hit_rate,
+            current_entries: self.cache.len(),
+        }
+    }
+
+    /// Drops every entry and resets the hit/miss counters
+    #[allow(dead_code)]
+    pub fn clear(&self) {
+        self.cache.clear();
+        self.hits.store(0, Ordering::Relaxed);
+        self.misses.store(0, Ordering::Relaxed);
+    }
+
     fn spawn_cleanup(&mut self) {
         let interval_seconds = (self.ttl / 2) + 1;
         let interval = time::Duration::from_secs(interval_seconds);
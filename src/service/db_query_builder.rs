@@ -1,18 +1,40 @@
-use core::fmt;
-use sqlx::Postgres;
+use sqlx::{Encode, Postgres, Type};
 use tracing::debug;
+use uuid::Uuid;
+
+use crate::models::error::{FieldError, ServerError};
 
 pub struct DBQueryBuilder<'a> {
     builder: sqlx::QueryBuilder<'a, Postgres>,
     where_used: bool,
+    order_used: bool,
+    safe_columns: &'static [&'static str],
+}
+
+/// Escapes the LIKE wildcards so user input is matched literally
+fn escape_like(term: &str) -> String {
+    term.replace('\\', "\\\\")
+        .replace('%', "\\%")
+        .replace('_', "\\_")
 }
 
 #[allow(dead_code, unused_variables)]
 impl<'a> DBQueryBuilder<'a> {
-    pub fn select(base: &str) -> Self {
+    /// `safe_columns` is the allowlist of columns that can be pushed into the
+    /// query as identifiers, e.g. for ordering
+    pub fn select(base: &str, safe_columns: &'static [&'static str]) -> Self {
         Self {
-            builder: sqlx::QueryBuilder::new(base),
+            builder: sqlx::QueryBuilder::new(format!("SELECT {base}")),
             where_used: false,
+            order_used: false,
+            safe_columns,
+        }
+    }
+
+    pub fn update(table: &str) -> UpdateQueryBuilder<'a> {
+        UpdateQueryBuilder {
+            builder: sqlx::QueryBuilder::new(format!("UPDATE {table} SET ")),
+            set_count: 0,
         }
     }
 
@@ -22,71 +44,140 @@ impl<'a> DBQueryBuilder<'a> {
         self
     }
 
-    pub fn r#where<T>(mut self, field: &str, value: &T) -> Self
+    /// `on_condition` is pushed verbatim and is not parameterised, it must
+    /// only ever be a trusted constant and never contain user input
+    pub fn inner_join(mut self, table: &str, on_condition: &str) -> Self {
+        self.builder
+            .push(format!(" INNER JOIN {table} ON {on_condition}"));
+        self
+    }
+
+    /// `on_condition` is pushed verbatim and is not parameterised, it must
+    /// only ever be a trusted constant and never contain user input
+    pub fn left_join(mut self, table: &str, on_condition: &str) -> Self {
+        self.builder
+            .push(format!(" LEFT JOIN {table} ON {on_condition}"));
+        self
+    }
+
+    fn push_condition(&mut self, condition: &str) {
+        match self.where_used {
+            true => self.builder.push(format!(" AND {condition}")),
+            false => self.builder.push(format!(" WHERE {condition}")),
+        };
+        self.where_used = true;
+    }
+
+    pub fn where_null(mut self, field: &str) -> Self {
+        self.push_condition(&format!("{field} IS NULL"));
+        self
+    }
+
+    pub fn where_not_null(mut self, field: &str) -> Self {
+        self.push_condition(&format!("{field} IS NOT NULL"));
+        self
+    }
+
+    pub fn r#where<T>(mut self, field: &str, value: T) -> Self
     where
-        T: fmt::Display,
+        T: 'a + Encode<'a, Postgres> + Type<Postgres>,
     {
-        match self.where_used {
-            true => {
-                self.builder.push(format!(" AND {field} = "));
-                self.builder.push_bind(value.to_string());
-            }
-            false => {
-                self.builder.push(format!(" WHERE {field} = "));
-                self.builder.push_bind(value.to_string());
-                self.where_used = true;
-            }
+        self.push_condition(&format!("{field} = "));
+        self.builder.push_bind(value);
+        self
+    }
+
+    pub fn where_opt<T>(mut self, field: &str, value: Option<T>) -> Self
+    where
+        T: 'a + Encode<'a, Postgres> + Type<Postgres>,
+    {
+        if let Some(value) = value {
+            self.push_condition(&format!("{field} = "));
+            self.builder.push_bind(value);
+        }
+
+        self
+    }
+
+    pub fn where_gte_opt<T>(mut self, field: &str, value: Option<T>) -> Self
+    where
+        T: 'a + Encode<'a, Postgres> + Type<Postgres>,
+    {
+        if let Some(value) = value {
+            self.push_condition(&format!("{field} >= "));
+            self.builder.push_bind(value);
         }
 
         self
     }
 
-    pub fn where_opt<T>(mut self, field: &str, value: &Option<T>) -> Self
+    pub fn where_lte_opt<T>(mut self, field: &str, value: Option<T>) -> Self
     where
-        T: fmt::Display,
+        T: 'a + Encode<'a, Postgres> + Type<Postgres>,
     {
         if let Some(value) = value {
-            match self.where_used {
-                true => {
-                    self.builder.push(format!(" AND {field} = "));
-                    self.builder.push_bind(value.to_string());
-                }
-                false => {
-                    self.builder.push(format!(" WHERE {field} = "));
-                    self.builder.push_bind(value.to_string());
-                    self.where_used = true;
-                }
+            self.push_condition(&format!("{field} <= "));
+            self.builder.push_bind(value);
+        }
+
+        self
+    }
+
+    /// Case insensitive substring match of `term` against any of the given fields
+    pub fn where_ilike_any_opt(mut self, fields: &[&str], term: Option<&str>) -> Self {
+        let Some(term) = term.filter(|t| !t.trim().is_empty()) else {
+            return self;
+        };
+
+        let pattern = format!("%{}%", escape_like(term.trim()));
+        self.push_condition("(");
+
+        for (idx, field) in fields.iter().enumerate() {
+            if idx > 0 {
+                self.builder.push(" OR ");
             }
+            self.builder.push(format!("{field} ILIKE "));
+            self.builder.push_bind(pattern.clone());
         }
 
+        self.builder.push(")");
         self
     }
 
-    pub fn order_asc(mut self, field: &'a str) -> Self {
-        self.builder.push(" ORDER BY ");
-        self.builder.push_bind(field);
-        self.builder.push(" ASC ");
+    /// Order columns are constants in the db layer, so a column outside the
+    /// allowlist is a bug and panics instead of silently dropping the ordering
+    fn push_order(&mut self, field: &str, direction: &str) {
+        assert!(
+            self.safe_columns.contains(&field),
+            "Ordering on column not in allowlist: {field}"
+        );
+
+        match self.order_used {
+            true => self.builder.push(format!(", {field} {direction}")),
+            false => self.builder.push(format!(" ORDER BY {field} {direction}")),
+        };
+        self.order_used = true;
+    }
+
+    pub fn order_asc(mut self, field: &str) -> Self {
+        self.push_order(field, "ASC");
         self
     }
 
-    pub fn order_desc(mut self, field: &'a str) -> Self {
-        self.builder.push(" ORDER BY ");
-        self.builder.push_bind(field);
-        self.builder.push(" DESC ");
+    pub fn order_desc(mut self, field: &str) -> Self {
+        self.push_order(field, "DESC");
         self
     }
 
-    pub fn limit(mut self, limit: impl Into<usize>) -> Self {
-        let limit = limit.into();
+    pub fn limit(mut self, limit: impl Into<i64>) -> Self {
         self.builder.push(" LIMIT ");
-        self.builder.push_bind(limit.to_string());
+        self.builder.push_bind(limit.into());
         self
     }
 
-    pub fn offset(mut self, offset: impl Into<usize>) -> Self {
-        let offset = offset.into();
+    pub fn offset(mut self, offset: impl Into<i64>) -> Self {
         self.builder.push(" OFFSET ");
-        self.builder.push_bind(offset.to_string());
+        self.builder.push_bind(offset.into());
         self
     }
 
@@ -97,3 +188,58 @@ impl<'a> DBQueryBuilder<'a> {
         self.builder
     }
 }
+
+pub struct UpdateQueryBuilder<'a> {
+    builder: sqlx::QueryBuilder<'a, Postgres>,
+    set_count: usize,
+}
+
+impl<'a> UpdateQueryBuilder<'a> {
+    pub fn set<T>(mut self, field: &str, value: T) -> Self
+    where
+        T: 'a + Encode<'a, Postgres> + Type<Postgres>,
+    {
+        if self.set_count > 0 {
+            self.builder.push(", ");
+        }
+
+        self.builder.push(format!("{field} = "));
+        self.builder.push_bind(value);
+        self.set_count += 1;
+        self
+    }
+
+    pub fn set_if_some<T>(self, field: &str, value: Option<T>) -> Self
+    where
+        T: 'a + Encode<'a, Postgres> + Type<Postgres>,
+    {
+        match value {
+            Some(value) => self.set(field, value),
+            None => self,
+        }
+    }
+
+    pub fn where_id(mut self, id: Uuid) -> Self {
+        self.builder.push(" WHERE id = ");
+        self.builder.push_bind(id);
+        self
+    }
+
+    pub fn returning(mut self, columns: &str) -> Self {
+        self.builder.push(format!(" RETURNING {columns}"));
+        self
+    }
+
+    /// Fails if nothing was set, as the query would not be valid SQL
+    pub fn build(self) -> Result<sqlx::QueryBuilder<'a, Postgres>, ServerError> {
+        if self.set_count == 0 {
+            return Err(ServerError::Validation(vec![FieldError::new(
+                "body",
+                "No fields to update",
+            )]));
+        }
+
+        debug!("Built query: {}", self.builder.sql());
+        Ok(self.builder)
+    }
+}
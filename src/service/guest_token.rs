@@ -0,0 +1,83 @@
+use axum::http::{HeaderMap, HeaderValue, StatusCode};
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+use tracing::warn;
+use uuid::Uuid;
+
+use crate::{config::config::GuestConfig, models::error::ServerError, service::util::to_uuid};
+
+/// Response header carrying the signed token for a pseudo id
+pub static GUEST_TOKEN_HEADER: &str = "X-Guest-Token";
+
+/// Signs a pseudo id as `<uuid>.<hex hmac>`, so the guest can later prove
+/// the id was handed out by us and not made up
+pub fn sign_guest_id(pseudo_id: Uuid, key: &str) -> Result<String, ServerError> {
+    let signature = guest_mac(pseudo_id, key)?.finalize().into_bytes();
+    Ok(format!("{}.{}", pseudo_id, hex::encode(signature)))
+}
+
+/// Headers handing the signed token to the client, empty when no signing key
+/// is configured. The body keeps returning the bare id for older clients
+pub fn guest_token_headers(
+    pseudo_id: Uuid,
+    config: &GuestConfig,
+) -> Result<HeaderMap, ServerError> {
+    let mut headers = HeaderMap::new();
+    if let Some(key) = &config.signing_key {
+        let token = sign_guest_id(pseudo_id, key)?;
+        let value = HeaderValue::from_str(&token)
+            .map_err(|e| ServerError::Internal(format!("Invalid guest token header: {}", e)))?;
+        headers.insert(GUEST_TOKEN_HEADER, value);
+    }
+
+    Ok(headers)
+}
+
+/// Reads the pseudo id from a guest header, either a bare uuid or a signed
+/// token. Bare uuids are only accepted until `enforce_signature` is turned on
+pub fn verify_guest_token(header: &str, config: &GuestConfig) -> Result<Uuid, ServerError> {
+    let (id, signature) = match header.split_once('.') {
+        Some((id, signature)) => (id, Some(signature)),
+        None => (header, None),
+    };
+
+    let pseudo_id = to_uuid(id)?;
+    let key = config.signing_key.as_deref();
+
+    match (signature, key) {
+        (Some(signature), Some(key)) => {
+            let Ok(signature) = hex::decode(signature) else {
+                return Err(invalid_signature(pseudo_id));
+            };
+
+            guest_mac(pseudo_id, key)?
+                .verify_slice(&signature)
+                .map_err(|_| invalid_signature(pseudo_id))?;
+
+            Ok(pseudo_id)
+        }
+        _ if config.enforce_signature => Err(ServerError::Api(
+            StatusCode::UNAUTHORIZED,
+            "Guest token must be signed".into(),
+        )),
+        _ => Ok(pseudo_id),
+    }
+}
+
+fn guest_mac(pseudo_id: Uuid, key: &str) -> Result<Hmac<Sha256>, ServerError> {
+    let mut mac = Hmac::<Sha256>::new_from_slice(key.as_bytes())
+        .map_err(|e| ServerError::Internal(format!("Invalid guest signing key: {}", e)))?;
+    mac.update(pseudo_id.to_string().as_bytes());
+    Ok(mac)
+}
+
+fn invalid_signature(pseudo_id: Uuid) -> ServerError {
+    warn!(
+        "Rejected guest token with invalid signature for {}",
+        pseudo_id
+    );
+    ServerError::Api(
+        StatusCode::UNAUTHORIZED,
+        "Invalid guest token signature".into(),
+    )
+}
@@ -0,0 +1,56 @@
+use std::{
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use dashmap::{DashMap, mapref::entry::Entry};
+
+// 5 minutes
+pub static IDEMPOTENCY_TTL: Duration = Duration::from_secs(300);
+
+#[derive(Debug, Clone)]
+pub struct IdempotencyVault {
+    seen: Arc<DashMap<String, Instant>>,
+    ttl: Duration,
+}
+
+impl IdempotencyVault {
+    pub fn from_ttl(ttl: Duration) -> Self {
+        Self {
+            seen: Arc::new(DashMap::new()),
+            ttl,
+        }
+    }
+
+    /// Records the key and returns false, or returns true if it was already
+    /// recorded within the ttl
+    pub fn check_and_insert(&self, key: &str) -> bool {
+        let now = Instant::now();
+
+        match self.seen.entry(key.to_string()) {
+            Entry::Occupied(mut entry) => {
+                if now.duration_since(*entry.get()) < self.ttl {
+                    return true;
+                }
+                entry.insert(now);
+                false
+            }
+            Entry::Vacant(entry) => {
+                entry.insert(now);
+                false
+            }
+        }
+    }
+
+    /// Forgets a key so a failed delivery can be retried
+    pub fn remove(&self, key: &str) {
+        self.seen.remove(key);
+    }
+
+    pub fn prune(&self) -> usize {
+        let before = self.seen.len();
+        let ttl = self.ttl;
+        self.seen.retain(|_, seen_at| seen_at.elapsed() < ttl);
+        before - self.seen.len()
+    }
+}
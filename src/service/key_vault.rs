@@ -1,116 +1,246 @@
 use std::{
-    sync::Arc,
+    cell::RefCell,
+    sync::{Arc, RwLock},
     time::{Duration, SystemTime, SystemTimeError, UNIX_EPOCH},
 };
 
-use dashmap::DashMap;
+use axum::http::StatusCode;
+use chrono::{DateTime, Utc};
+use dashmap::{DashMap, mapref::entry::Entry};
 use rand::{Rng, SeedableRng};
 use rand_chacha::ChaCha8Rng;
+use serde::Serialize;
 use serde_json::json;
 use sqlx::{Pool, Postgres};
-use tracing::{debug, error};
+use tracing::{debug, error, warn};
 
 use crate::{
     db::key_vault::get_word_sets,
-    models::system_log::{LogAction, LogCeverity},
+    models::{
+        error::ServerError,
+        system_log::{LogAction, LogCeverity},
+    },
     service::system_log_builder::SystemLogBuilder,
 };
 
+/// Splits a key word in its "prefix suffix" form into the pair the vault is
+/// keyed on
+pub fn parse_game_key(key_word: &str) -> Result<(String, String), ServerError> {
+    match key_word.split_once(' ') {
+        Some((prefix, suffix))
+            if !prefix.is_empty() && !suffix.is_empty() && !suffix.contains(' ') =>
+        {
+            Ok((prefix.to_string(), suffix.to_string()))
+        }
+        _ => Err(ServerError::Api(
+            StatusCode::BAD_REQUEST,
+            "Key word in invalid format".into(),
+        )),
+    }
+}
+
 #[derive(Debug, thiserror::Error)]
 pub enum KeyVaultError {
     #[error("No more available words")]
     FullCapasity,
 
+    #[error("Word lists can not be empty")]
+    EmptyWords,
+
     #[error("Failed to load words: {0}")]
     Database(#[from] sqlx::Error),
 
-    #[error("Word sets differ in length")]
-    IncompatibleLength,
-
     #[error("Failed to get created at time: {0}")]
     TimeError(#[from] SystemTimeError),
 }
 
+thread_local! {
+    // Seeded once per thread, seeding from the OS for every key attempt
+    // showed up as a hot spot under concurrent key creation
+    static RNG: RefCell<ChaCha8Rng> = RefCell::new(ChaCha8Rng::from_os_rng());
+}
+
+/// Keys not freed by their game within this long are freed by the cleanup loop
+pub const KEY_TTL: Duration = Duration::from_secs(3600);
+
+/// Share of possible keys in use before the cleanup loop logs a warning
+pub const VAULT_UTILISATION_WARNING: f32 = 0.8;
+
+/// Used when the word tables are empty, e.g. against a fresh local database
+static FALLBACK_PREFIXES: [&str; 10] = [
+    "glad", "rask", "sur", "stor", "kul", "vill", "rolig", "sint", "snill", "lat",
+];
+static FALLBACK_SUFFIXES: [&str; 10] = [
+    "ulv", "elg", "rev", "hare", "katt", "hund", "ugle", "gris", "mus", "laks",
+];
+
+#[derive(Debug, Default)]
+struct WordSets {
+    prefix: Vec<String>,
+    suffix: Vec<String>,
+}
+
+impl WordSets {
+    fn capacity(&self) -> usize {
+        self.prefix.len() * self.suffix.len()
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct KeyVaultStats {
+    pub active_keys: usize,
+    pub capacity: usize,
+    pub utilisation_pct: f32,
+}
+
+/// An empty word pool cannot hand out keys, so it counts as full
+fn utilisation(active_keys: usize, capacity: usize) -> f32 {
+    match capacity {
+        0 => 1.0,
+        capacity => active_keys as f32 / capacity as f32,
+    }
+}
+
 pub struct KeyVault {
-    word_count: u8,
     active_keys: Arc<DashMap<(String, String), u64>>,
-    prefix_words: Arc<Vec<String>>,
-    suffix_words: Arc<Vec<String>>,
+    words: Arc<RwLock<Arc<WordSets>>>,
 }
 
 impl KeyVault {
-    pub async fn load_words(pool: &Pool<Postgres>) -> Result<Self, KeyVaultError> {
-        let (db_prefix, db_suffix) = get_word_sets(pool).await?;
-
-        if db_prefix.len() != db_suffix.len() {
-            return Err(KeyVaultError::IncompatibleLength);
+    /// The prefix and suffix of a key are drawn independently, so the lists
+    /// may differ in length and the capacity is their product. Admins add
+    /// words to one list at a time, so equal lengths can not be required
+    pub fn from_words(prefix: Vec<String>, suffix: Vec<String>) -> Result<Self, KeyVaultError> {
+        if prefix.is_empty() || suffix.is_empty() {
+            return Err(KeyVaultError::EmptyWords);
         }
 
-        let vault = Self {
-            word_count: db_prefix.len() as u8,
+        Ok(Self {
             active_keys: Arc::new(DashMap::new()),
-            prefix_words: Arc::new(Vec::from(db_prefix)),
-            suffix_words: Arc::new(Vec::from(db_suffix)),
-        };
+            words: Arc::new(RwLock::new(Arc::new(WordSets { prefix, suffix }))),
+        })
+    }
 
+    /// Falls back to a small built-in word list when the tables are empty
+    pub async fn load_words(pool: &Pool<Postgres>) -> Result<Self, KeyVaultError> {
+        let (mut prefix, mut suffix) = get_word_sets(pool).await?;
+
+        if prefix.is_empty() || suffix.is_empty() {
+            warn!("Word tables are empty, using the built-in words");
+            SystemLogBuilder::new_with_function(pool, "load_words")
+                .action(LogAction::Read)
+                .ceverity(LogCeverity::Warning)
+                .description("Word tables are empty, using the built-in words")
+                .log_async();
+
+            prefix = FALLBACK_PREFIXES.map(String::from).to_vec();
+            suffix = FALLBACK_SUFFIXES.map(String::from).to_vec();
+        }
+
+        let vault = Self::from_words(prefix, suffix)?;
         vault.spawn_vault_cleanup(pool);
         Ok(vault)
     }
 
-    pub fn key_active(&self, key: &(String, String)) -> bool {
-        self.active_keys.contains_key(&key)
+    /// Swaps in the current word pool, keys handed out before the reload stay active
+    pub async fn reload_words(&self, pool: &Pool<Postgres>) -> Result<(), KeyVaultError> {
+        let (prefix, suffix) = get_word_sets(pool).await?;
+        let mut words = self.words.write().unwrap_or_else(|e| e.into_inner());
+        *words = Arc::new(WordSets { prefix, suffix });
+
+        Ok(())
+    }
+
+    fn words(&self) -> Arc<WordSets> {
+        self.words.read().unwrap_or_else(|e| e.into_inner()).clone()
+    }
+
+    /// None when the key is not handed out
+    pub fn key_created_at(&self, key: &(String, String)) -> Option<DateTime<Utc>> {
+        let created_at = *self.active_keys.get(key)?;
+        DateTime::from_timestamp(created_at as i64, 0)
     }
 
     pub fn remove_key(&self, key: (String, String)) {
         self.active_keys.remove(&key);
     }
 
-    fn random_idx(&self) -> Result<(usize, usize), KeyVaultError> {
-        let mut rng = ChaCha8Rng::from_os_rng();
-        let prefix_idx = rng.random_range(0..self.word_count as usize);
-        let suffix_idx = rng.random_range(0..self.word_count as usize);
+    /// Frees a key in its "prefix suffix" form
+    pub fn release_key(&self, key_word: &str) {
+        if let Ok(key) = parse_game_key(key_word) {
+            self.remove_key(key);
+        }
+    }
+
+    pub fn active_key_count(&self) -> usize {
+        self.active_keys.len()
+    }
+
+    /// Share of all prefix and suffix combinations currently handed out
+    pub fn utilisation(&self) -> f32 {
+        utilisation(self.active_keys.len(), self.words().capacity())
+    }
+
+    pub fn stats(&self) -> KeyVaultStats {
+        KeyVaultStats {
+            active_keys: self.active_keys.len(),
+            capacity: self.words().capacity(),
+            utilisation_pct: self.utilisation() * 100.0,
+        }
+    }
+
+    fn random_idx(&self, words: &WordSets) -> Result<(usize, usize), KeyVaultError> {
+        if words.prefix.is_empty() || words.suffix.is_empty() {
+            return Err(KeyVaultError::FullCapasity);
+        }
 
-        Ok((prefix_idx, suffix_idx))
+        Ok(RNG.with_borrow_mut(|rng| {
+            (
+                rng.random_range(0..words.prefix.len()),
+                rng.random_range(0..words.suffix.len()),
+            )
+        }))
+    }
+
+    /// Marks the key as active unless another caller holds it, checking and
+    /// inserting under the same shard lock so two games never share a key
+    fn try_claim(&self, key: (String, String)) -> Result<Option<String>, KeyVaultError> {
+        match self.active_keys.entry(key) {
+            Entry::Occupied(_) => Ok(None),
+            Entry::Vacant(entry) => {
+                let created_at = SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs();
+                let key = entry.key();
+                let word = format!("{} {}", key.0, key.1);
+                entry.insert(created_at);
+                Ok(Some(word))
+            }
+        }
     }
 
     pub fn create_key(&self, pool: &Pool<Postgres>) -> Result<String, KeyVaultError> {
+        let words = self.words();
         for _ in 0..100 {
-            let Ok((idx1, idx2)) = self.random_idx() else {
+            let Ok((idx1, idx2)) = self.random_idx(&words) else {
                 break; // Log outside loop
             };
 
-            let key = (
-                self.prefix_words[idx1].clone(),
-                self.suffix_words[idx2].clone(),
-            );
-
-            if self.key_active(&key) {
-                continue;
+            let key = (words.prefix[idx1].clone(), words.suffix[idx2].clone());
+            if let Some(key) = self.try_claim(key)? {
+                return Ok(key);
             }
-
-            let created_at = SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs();
-            self.active_keys.insert(key.clone(), created_at);
-            return Ok(format!("{} {}", key.0, key.1));
         }
 
-        for i in 0..self.prefix_words.len() {
-            for j in 0..self.suffix_words.len() {
-                let key = (self.prefix_words[i].clone(), self.suffix_words[j].clone());
-
-                if self.key_active(&key) {
-                    continue;
+        for prefix in &words.prefix {
+            for suffix in &words.suffix {
+                if let Some(key) = self.try_claim((prefix.clone(), suffix.clone()))? {
+                    return Ok(key);
                 }
-
-                let created_at = SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs();
-                self.active_keys.insert(key.clone(), created_at);
-                return Ok(format!("{} {}", key.0, key.1));
             }
         }
 
-        SystemLogBuilder::new(pool)
+        SystemLogBuilder::new_with_function(pool, "create_key")
             .action(LogAction::Create)
             .ceverity(LogCeverity::Critical)
-            .function("create_key")
             .description("Library failed to create random id.")
             .log_async();
 
@@ -120,6 +250,7 @@ impl KeyVault {
     fn spawn_vault_cleanup(&self, pool: &Pool<Postgres>) {
         let mut interval = tokio::time::interval(Duration::from_secs(3600));
         let active_keys = self.active_keys.clone();
+        let words = self.words.clone();
         let pool = pool.clone();
 
         tokio::spawn(async move {
@@ -129,10 +260,9 @@ impl KeyVault {
 
                 let Ok(time) = SystemTime::now().duration_since(UNIX_EPOCH) else {
                     error!("Failed to obtain system time when cleaning up the vault");
-                    SystemLogBuilder::new(&pool)
+                    SystemLogBuilder::new_with_function(&pool, "spawn_vault_cleanup")
                         .action(LogAction::Other)
                         .ceverity(LogCeverity::Critical)
-                        .function("spawn_vault_cleanup")
                         .description("Failed to obtain system time")
                         .log_async();
 
@@ -140,22 +270,37 @@ impl KeyVault {
                 };
 
                 let keys_before = active_keys.len();
-                let timeout_threshold = time.as_secs() - 3600;
+                let timeout_threshold = time.as_secs() - KEY_TTL.as_secs();
                 active_keys.retain(|_, &mut created_at| created_at > timeout_threshold);
                 let keys_after = active_keys.len();
                 let removed_keys = keys_before - keys_after;
 
                 if removed_keys > 0 {
-                    SystemLogBuilder::new(&pool)
+                    SystemLogBuilder::new_with_function(&pool, "spawn_vault_cleanup")
                         .action(LogAction::Delete)
                         .ceverity(LogCeverity::Warning)
-                        .function("spawn_vault_cleanup")
                         .description(&format!("Cleaned up {} expired keys", removed_keys))
                         .metadata(json!({
                             "warning": "Indicates game crash or unexpected exit - keys should be freed on game start.",              
                         }))
                         .log_async();
                 }
+
+                let capacity = words.read().unwrap_or_else(|e| e.into_inner()).capacity();
+                let utilisation = utilisation(keys_after, capacity);
+                if utilisation > VAULT_UTILISATION_WARNING {
+                    warn!("KeyVault is {:.0}% utilised", utilisation * 100.0);
+                    SystemLogBuilder::new_with_function(&pool, "spawn_vault_cleanup")
+                        .action(LogAction::Other)
+                        .ceverity(LogCeverity::Warning)
+                        .description("KeyVault is running out of game keys")
+                        .metadata(json!({
+                            "active_keys": keys_after,
+                            "capacity": capacity,
+                            "utilisation_pct": utilisation * 100.0,
+                        }))
+                        .log_async();
+                }
             }
         });
     }
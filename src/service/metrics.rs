@@ -0,0 +1,171 @@
+use std::{
+    fmt::Write,
+    sync::{
+        Arc,
+        atomic::{AtomicI64, AtomicU64, Ordering},
+    },
+    time::Duration,
+};
+
+use dashmap::DashMap;
+
+/// Values that are sampled when the metrics are scraped
+pub struct MetricsSnapshot {
+    pub active_game_keys: usize,
+    pub page_cache_hits: u64,
+}
+
+/// Upper bounds in seconds of the latency histogram buckets
+pub static LATENCY_BUCKETS: [f64; 9] = [0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5];
+
+#[derive(Debug, Default)]
+struct Histogram {
+    buckets: [AtomicU64; LATENCY_BUCKETS.len()],
+    count: AtomicU64,
+    sum_micros: AtomicU64,
+}
+
+impl Histogram {
+    fn observe(&self, elapsed: Duration) {
+        let seconds = elapsed.as_secs_f64();
+        if let Some(idx) = LATENCY_BUCKETS.iter().position(|le| seconds <= *le) {
+            self.buckets[idx].fetch_add(1, Ordering::Relaxed);
+        }
+        self.count.fetch_add(1, Ordering::Relaxed);
+        self.sum_micros
+            .fetch_add(elapsed.as_micros() as u64, Ordering::Relaxed);
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct Metrics {
+    http_requests: Arc<DashMap<(String, String, u16), AtomicU64>>,
+    http_latency: Arc<DashMap<(String, u16), Histogram>>,
+    pseudo_user_count: Arc<AtomicI64>,
+}
+
+fn escape_label(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+impl Metrics {
+    pub fn record_request(&self, method: &str, route: &str, status: u16) {
+        self.http_requests
+            .entry((method.to_string(), route.to_string(), status))
+            .or_default()
+            .fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn observe_latency(&self, route: &str, status: u16, elapsed: Duration) {
+        self.http_latency
+            .entry((route.to_string(), status))
+            .or_default()
+            .observe(elapsed);
+    }
+
+    pub fn set_pseudo_user_count(&self, count: i64) {
+        self.pseudo_user_count.store(count, Ordering::Relaxed);
+    }
+
+    /// Renders the metrics in the Prometheus text exposition format
+    pub fn render(&self, snapshot: &MetricsSnapshot) -> String {
+        let mut out = String::new();
+
+        let _ = writeln!(
+            out,
+            "# HELP tero_active_game_keys Game keys currently handed out\n\
+             # TYPE tero_active_game_keys gauge\n\
+             tero_active_game_keys {}",
+            snapshot.active_game_keys
+        );
+        let _ = writeln!(
+            out,
+            "# HELP tero_game_page_cache_hits_total Game page requests served from the cache\n\
+             # TYPE tero_game_page_cache_hits_total counter\n\
+             tero_game_page_cache_hits_total {}",
+            snapshot.page_cache_hits
+        );
+        let _ = writeln!(
+            out,
+            "# HELP tero_pseudo_user_count Pseudo users in the database\n\
+             # TYPE tero_pseudo_user_count gauge\n\
+             tero_pseudo_user_count {}",
+            self.pseudo_user_count.load(Ordering::Relaxed)
+        );
+
+        let _ = writeln!(
+            out,
+            "# HELP tero_http_requests_total Handled HTTP requests\n\
+             # TYPE tero_http_requests_total counter"
+        );
+
+        let mut requests: Vec<(String, String, u16, u64)> = self
+            .http_requests
+            .iter()
+            .map(|entry| {
+                let (method, route, status) = entry.key();
+                let count = entry.value().load(Ordering::Relaxed);
+                (method.clone(), route.clone(), *status, count)
+            })
+            .collect();
+        requests.sort();
+
+        for (method, route, status, count) in requests {
+            let _ = writeln!(
+                out,
+                "tero_http_requests_total{{method=\"{}\",route=\"{}\",status=\"{}\"}} {}",
+                escape_label(&method),
+                escape_label(&route),
+                status,
+                count
+            );
+        }
+
+        let _ = writeln!(
+            out,
+            "# HELP tero_http_request_duration_seconds Time spent handling HTTP requests\n\
+             # TYPE tero_http_request_duration_seconds histogram"
+        );
+
+        let mut routes: Vec<(String, u16)> =
+            self.http_latency.iter().map(|e| e.key().clone()).collect();
+        routes.sort();
+
+        for key in routes {
+            let Some(histogram) = self.http_latency.get(&key) else {
+                continue;
+            };
+            let labels = format!(
+                "route=\"{}\",status_code=\"{}\"",
+                escape_label(&key.0),
+                key.1
+            );
+
+            // Prometheus buckets are cumulative
+            let mut cumulative = 0;
+            for (le, bucket) in LATENCY_BUCKETS.iter().zip(&histogram.buckets) {
+                cumulative += bucket.load(Ordering::Relaxed);
+                let _ = writeln!(
+                    out,
+                    "tero_http_request_duration_seconds_bucket{{{},le=\"{}\"}} {}",
+                    labels, le, cumulative
+                );
+            }
+
+            let count = histogram.count.load(Ordering::Relaxed);
+            let sum = histogram.sum_micros.load(Ordering::Relaxed) as f64 / 1_000_000.0;
+            let _ = writeln!(
+                out,
+                "tero_http_request_duration_seconds_bucket{{{},le=\"+Inf\"}} {}\n\
+                 tero_http_request_duration_seconds_sum{{{}}} {}\n\
+                 tero_http_request_duration_seconds_count{{{}}} {}",
+                labels, count, labels, sum, labels, count
+            );
+        }
+
+        out
+    }
+}
@@ -1,5 +1,14 @@
+pub mod audit;
 pub mod cache;
 pub mod db_query_builder;
+pub mod guest_token;
+pub mod idempotency_vault;
 pub mod key_vault;
+pub mod metrics;
+pub mod self_check;
 pub mod system_log_builder;
+pub mod telemetry;
+pub mod token_cache;
+pub mod trace_context;
+pub mod user_sync;
 pub mod util;
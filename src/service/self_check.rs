@@ -0,0 +1,100 @@
+use serde::Serialize;
+
+use crate::{
+    db::{
+        self,
+        health::{PendingMigration, pending_migrations},
+        key_vault::get_word_sets,
+    },
+    models::app_state::AppState,
+};
+
+/// Passed as the first argument to validate an environment without serving
+pub static CHECK_FLAG: &str = "--check";
+
+#[derive(Debug, Serialize)]
+pub struct CheckResult {
+    pub name: &'static str,
+    pub passed: bool,
+    pub detail: Option<String>,
+}
+
+impl CheckResult {
+    fn new(name: &'static str, result: Result<(), String>) -> Self {
+        Self {
+            name,
+            passed: result.is_ok(),
+            detail: result.err(),
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct SelfCheckReport {
+    pub passed: bool,
+    pub checks: Vec<CheckResult>,
+}
+
+impl SelfCheckReport {
+    pub fn from_checks(checks: Vec<CheckResult>) -> Self {
+        Self {
+            passed: checks.iter().all(|c| c.passed),
+            checks,
+        }
+    }
+
+    /// The state could not be built, so nothing else was checked
+    pub fn bootstrap_failed(reason: String) -> Self {
+        Self::from_checks(vec![CheckResult::new("bootstrap", Err(reason))])
+    }
+}
+
+/// Runs against an already built state, which means the config was valid, the
+/// database reachable and the JWKS fetched
+pub async fn run_self_check(state: &AppState) -> SelfCheckReport {
+    let database = db::health::health_check(state.get_pool())
+        .await
+        .map_err(|e| e.to_string());
+
+    let read_database = db::health::health_check(state.get_read_pool())
+        .await
+        .map_err(|e| e.to_string());
+
+    let migrations = match pending_migrations(state.get_pool()).await {
+        Ok(pending) if pending.is_empty() => Ok(()),
+        Ok(pending) => Err(format!("Pending migrations: {}", describe(&pending))),
+        Err(e) => Err(e.to_string()),
+    };
+
+    let jwks = match state.get_jwks().keys.is_empty() {
+        true => Err("Auth0 returned no keys".to_string()),
+        false => Ok(()),
+    };
+
+    // The key vault falls back to built-in words, so the tables are read directly
+    let word_tables = match get_word_sets(state.get_pool()).await {
+        Ok((prefix, suffix)) if prefix.is_empty() || suffix.is_empty() => Err(format!(
+            "{} prefix and {} suffix words",
+            prefix.len(),
+            suffix.len()
+        )),
+        Ok(_) => Ok(()),
+        Err(e) => Err(e.to_string()),
+    };
+
+    SelfCheckReport::from_checks(vec![
+        CheckResult::new("database", database),
+        CheckResult::new("read_database", read_database),
+        CheckResult::new("migrations", migrations),
+        CheckResult::new("jwks", jwks),
+        CheckResult::new("word_tables", word_tables),
+    ])
+}
+
+fn describe(pending: &[PendingMigration]) -> String {
+    pending
+        .iter()
+        .map(|m| format!("{} {}", m.version, m.description))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
@@ -1,4 +1,5 @@
 use sqlx::{Pool, Postgres};
+use uuid::Uuid;
 
 use tracing::error;
 
@@ -6,11 +7,15 @@ use crate::{
     db::system_log::create_system_log,
     models::{
         error::ServerError,
-        system_log::{LogAction, LogCeverity, SubjectType},
+        request_context::RequestContext,
+        system_log::{LogAction, LogCeverity, NewSystemLog, SubjectType},
         user::SubjectId,
     },
 };
 
+/// Function name stored for logs written by integrations that do not send one
+pub static UNSPECIFIED_FUNCTION: &str = "Not specified";
+
 pub struct SystemLogBuilder {
     pub pool: Pool<Postgres>,
     pub subject_id: Option<String>,
@@ -20,6 +25,7 @@ pub struct SystemLogBuilder {
     pub function: Option<String>,
     pub description: Option<String>,
     pub metadata: Option<serde_json::Value>,
+    pub correlation_id: Option<Uuid>,
 }
 
 impl SystemLogBuilder {
@@ -33,17 +39,17 @@ impl SystemLogBuilder {
             function: None,
             description: None,
             metadata: None,
+            correlation_id: None,
         }
     }
 
+    pub fn new_with_function(pool: &Pool<Postgres>, function: &str) -> Self {
+        Self::new(pool).function(function)
+    }
+
     pub fn subject(mut self, subject: SubjectId) -> Self {
-        let (id, _type) = match subject {
-            SubjectId::PseudoUser(id) => (id.to_string(), SubjectType::GuestUser),
-            SubjectId::BaseUser(id) => (id.to_string(), SubjectType::RegisteredUser),
-            SubjectId::Integration(int_name) => (int_name.to_string(), SubjectType::Integration),
-        };
-        self.subject_id = Some(id);
-        self.subject_type = Some(_type);
+        self.subject_id = Some(subject.id_string());
+        self.subject_type = Some(SubjectType::from(&subject));
         self
     }
 
@@ -72,7 +78,25 @@ impl SystemLogBuilder {
         self
     }
 
+    /// Links logs written while handling the same request
+    pub fn correlation_id(mut self, id: Uuid) -> Self {
+        self.correlation_id = Some(id);
+        self
+    }
+
+    /// Subject and correlation id of the request being handled
+    pub fn context(self, ctx: &RequestContext) -> Self {
+        self.subject(ctx.subject.clone())
+            .correlation_id(ctx.request_id)
+    }
+
     pub async fn log(self) -> Result<(), ServerError> {
+        let Some(function) = self.function else {
+            return Err(ServerError::Internal(
+                "System log is missing a function name".into(),
+            ));
+        };
+
         let (subject_id, subject_type) = match (self.subject_id, self.subject_type) {
             (Some(id), Some(_type)) => (id, _type),
             _ => ("[SYSTEM]".to_string(), SubjectType::System),
@@ -87,25 +111,29 @@ impl SystemLogBuilder {
             description = format!("{}...", &description[..509]);
         }
 
-        let action = self.action.unwrap_or_else(|| LogAction::Other);
-        let ceverity = self.ceverity.unwrap_or_else(|| LogCeverity::Info);
-        let function = self.function.unwrap_or_else(|| "Not specified".into());
-
-        create_system_log(
-            &self.pool,
-            &subject_id,
-            &subject_type,
-            &action,
-            &ceverity,
-            &function,
-            &description,
-            &self.metadata,
-        )
-        .await?;
-        Ok(())
+        let log = NewSystemLog {
+            subject_id,
+            subject_type,
+            action: self.action.unwrap_or(LogAction::Other),
+            ceverity: self.ceverity.unwrap_or(LogCeverity::Info),
+            function,
+            description,
+            metadata: self.metadata,
+            correlation_id: self.correlation_id,
+        };
+
+        create_system_log(&self.pool, &log).await
     }
 
     pub fn log_async(self) {
+        if self.function.is_none() {
+            error!(
+                "Dropped system log without function name: {:?}",
+                self.description
+            );
+            return;
+        }
+
         tokio::spawn(async move {
             self.log().await.map_err(|e| {
                 error!("Failed to system log async: {}", e);
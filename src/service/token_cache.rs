@@ -0,0 +1,103 @@
+use std::{
+    sync::Arc,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use dashmap::DashMap;
+use sha2::{Digest, Sha256};
+
+use crate::models::auth::Claims;
+
+// 5 minutes
+pub static TOKEN_CACHE_MAX_TTL_SECS: i64 = 300;
+pub static TOKEN_CACHE_MAX_ENTRIES: usize = 10_000;
+
+#[derive(Debug, Clone)]
+struct CachedClaims {
+    claims: Claims,
+    valid_until: i64,
+}
+
+/// Claims of already verified tokens, so repeated calls with the same token
+/// skip the RSA verification. Keyed by a hash of the whole token and never
+/// kept past the token's `exp`
+#[derive(Debug, Clone)]
+pub struct TokenCache {
+    entries: Arc<DashMap<[u8; 32], CachedClaims>>,
+    max_entries: usize,
+}
+
+impl TokenCache {
+    pub fn with_capacity(max_entries: usize) -> Self {
+        Self {
+            entries: Arc::new(DashMap::new()),
+            max_entries,
+        }
+    }
+
+    pub fn get(&self, token: &str) -> Option<Claims> {
+        self.get_at(token, unix_now())
+    }
+
+    pub fn insert(&self, token: &str, claims: &Claims) {
+        self.insert_at(token, claims, unix_now());
+    }
+
+    pub fn get_at(&self, token: &str, now: i64) -> Option<Claims> {
+        let key = token_key(token);
+        let cached = self.entries.get(&key)?.clone();
+
+        if now >= cached.valid_until {
+            self.entries.remove(&key);
+            return None;
+        }
+
+        Some(cached.claims)
+    }
+
+    pub fn insert_at(&self, token: &str, claims: &Claims, now: i64) {
+        let valid_until = claims.expires_at().min(now + TOKEN_CACHE_MAX_TTL_SECS);
+        if valid_until <= now {
+            return;
+        }
+
+        if self.entries.len() >= self.max_entries {
+            self.prune_at(now);
+        }
+
+        // Still full of live tokens, verify them the slow way until some expire
+        if self.entries.len() >= self.max_entries {
+            return;
+        }
+
+        self.entries.insert(
+            token_key(token),
+            CachedClaims {
+                claims: claims.clone(),
+                valid_until,
+            },
+        );
+    }
+
+    pub fn prune_at(&self, now: i64) -> usize {
+        let before = self.entries.len();
+        self.entries.retain(|_, cached| now < cached.valid_until);
+        before - self.entries.len()
+    }
+
+    #[allow(dead_code)]
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+}
+
+fn token_key(token: &str) -> [u8; 32] {
+    Sha256::digest(token.as_bytes()).into()
+}
+
+fn unix_now() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs() as i64)
+        .unwrap_or_default()
+}
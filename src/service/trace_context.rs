@@ -0,0 +1,157 @@
+use axum::http::{HeaderMap, HeaderValue};
+use opentelemetry::{
+    Context,
+    trace::{SpanContext, SpanId, TraceContextExt, TraceFlags, TraceId, TraceState},
+};
+use rand::Rng;
+use tracing::Span;
+use tracing_opentelemetry::OpenTelemetrySpanExt;
+use uuid::Uuid;
+
+pub static TRACEPARENT_HEADER: &str = "traceparent";
+pub static TRACESTATE_HEADER: &str = "tracestate";
+
+static TRACE_VERSION: &str = "00";
+
+tokio::task_local! {
+    static CURRENT_TRACE: TraceContext;
+}
+
+/// W3C trace context of the request currently being handled
+#[derive(Debug, Clone, PartialEq)]
+pub struct TraceContext {
+    pub trace_id: String,
+    pub span_id: String,
+    pub flags: String,
+    pub tracestate: Option<String>,
+}
+
+impl TraceContext {
+    pub fn new_root() -> Self {
+        Self {
+            trace_id: random_hex::<16>(),
+            span_id: random_hex::<8>(),
+            flags: "01".into(),
+            tracestate: None,
+        }
+    }
+
+    /// Parses the incoming `traceparent` header, an invalid or missing header
+    /// returns None so the caller can start a new trace
+    pub fn from_headers(headers: &HeaderMap) -> Option<Self> {
+        let traceparent = headers.get(TRACEPARENT_HEADER)?.to_str().ok()?;
+        let parts: Vec<&str> = traceparent.trim().split('-').collect();
+        let [version, trace_id, span_id, flags] = parts.as_slice() else {
+            return None;
+        };
+
+        let valid = *version == TRACE_VERSION
+            && is_hex_id(trace_id, 32)
+            && is_hex_id(span_id, 16)
+            && is_hex_id(flags, 2)
+            && trace_id.chars().any(|c| c != '0')
+            && span_id.chars().any(|c| c != '0');
+
+        if !valid {
+            return None;
+        }
+
+        let tracestate = headers
+            .get(TRACESTATE_HEADER)
+            .and_then(|value| value.to_str().ok())
+            .map(|value| value.to_string());
+
+        Some(Self {
+            trace_id: trace_id.to_string(),
+            span_id: span_id.to_string(),
+            flags: flags.to_string(),
+            tracestate,
+        })
+    }
+
+    /// The ids of `span` when it is exported, None when no OTLP endpoint is
+    /// set. Outgoing calls then name the exported span as their parent
+    pub fn from_span(span: &Span, tracestate: Option<String>) -> Option<Self> {
+        let cx = span.context();
+        let span_context = cx.span().span_context().clone();
+        if !span_context.is_valid() {
+            return None;
+        }
+
+        Some(Self {
+            trace_id: span_context.trace_id().to_string(),
+            span_id: span_context.span_id().to_string(),
+            flags: format!("{:02x}", span_context.trace_flags().to_u8()),
+            tracestate,
+        })
+    }
+
+    /// This context as the remote parent of an exported span
+    pub fn otel_context(&self) -> Context {
+        let span_context = SpanContext::new(
+            TraceId::from_hex(&self.trace_id).unwrap_or(TraceId::INVALID),
+            SpanId::from_hex(&self.span_id).unwrap_or(SpanId::INVALID),
+            TraceFlags::new(u8::from_str_radix(&self.flags, 16).unwrap_or_default()),
+            true,
+            TraceState::default(),
+        );
+
+        Context::new().with_remote_span_context(span_context)
+    }
+
+    /// Same trace with a new span id, used for work done by this service
+    pub fn child(&self) -> Self {
+        Self {
+            span_id: random_hex::<8>(),
+            ..self.clone()
+        }
+    }
+
+    /// The trace id as a uuid, stored on system logs to correlate them
+    pub fn correlation_id(&self) -> Uuid {
+        Uuid::try_parse(&self.trace_id).unwrap_or_default()
+    }
+
+    pub fn traceparent(&self) -> String {
+        format!(
+            "{}-{}-{}-{}",
+            TRACE_VERSION, self.trace_id, self.span_id, self.flags
+        )
+    }
+
+    pub fn inject(&self, headers: &mut HeaderMap) {
+        if let Ok(value) = HeaderValue::from_str(&self.traceparent()) {
+            headers.insert(TRACEPARENT_HEADER, value);
+        }
+
+        if let Some(value) = self
+            .tracestate
+            .as_ref()
+            .and_then(|state| HeaderValue::from_str(state).ok())
+        {
+            headers.insert(TRACESTATE_HEADER, value);
+        }
+    }
+
+    /// Runs `fut` with this context available through `TraceContext::current`
+    pub async fn scope<F: Future>(self, fut: F) -> F::Output {
+        CURRENT_TRACE.scope(self, fut).await
+    }
+
+    pub fn current() -> Option<Self> {
+        CURRENT_TRACE.try_with(|cx| cx.clone()).ok()
+    }
+}
+
+fn is_hex_id(value: &str, len: usize) -> bool {
+    value.len() == len
+        && value
+            .chars()
+            .all(|c| c.is_ascii_digit() || ('a'..='f').contains(&c))
+}
+
+fn random_hex<const N: usize>() -> String {
+    let mut bytes = [0u8; N];
+    rand::rng().fill(&mut bytes);
+    hex::encode(bytes)
+}
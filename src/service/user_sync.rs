@@ -0,0 +1,43 @@
+use std::sync::Arc;
+
+use dashmap::DashSet;
+use uuid::Uuid;
+
+/// `(base_id, pseudo_id)` pairs with a sync task running. A user's first
+/// requests tend to arrive together, only the first of them gets to sync
+#[derive(Debug, Clone, Default)]
+pub struct UserSyncs {
+    in_flight: Arc<DashSet<(Uuid, Uuid)>>,
+}
+
+impl UserSyncs {
+    /// None while another task holds the pair, the pair is freed again when
+    /// the returned guard is dropped
+    pub fn try_begin(&self, base_id: Uuid, pseudo_id: Uuid) -> Option<UserSyncGuard> {
+        let key = (base_id, pseudo_id);
+        if !self.in_flight.insert(key) {
+            return None;
+        }
+
+        Some(UserSyncGuard {
+            in_flight: self.in_flight.clone(),
+            key,
+        })
+    }
+
+    pub fn in_flight_count(&self) -> usize {
+        self.in_flight.len()
+    }
+}
+
+#[derive(Debug)]
+pub struct UserSyncGuard {
+    in_flight: Arc<DashSet<(Uuid, Uuid)>>,
+    key: (Uuid, Uuid),
+}
+
+impl Drop for UserSyncGuard {
+    fn drop(&mut self) {
+        self.in_flight.remove(&self.key);
+    }
+}
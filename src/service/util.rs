@@ -1,14 +1,18 @@
+use std::time::Duration;
+
 use axum::http::HeaderMap;
 use reqwest::StatusCode;
 use uuid::Uuid;
 
 use crate::models::error::ServerError;
 
+const MAX_LOCALE_LEN: usize = 35;
+
 pub fn to_uuid(value: &str) -> Result<Uuid, ServerError> {
     let Ok(guest_id) = value.parse() else {
         return Err(ServerError::Api(
-            StatusCode::UNAUTHORIZED,
-            "Guest id is invalid".into(),
+            StatusCode::BAD_REQUEST,
+            "Guest id must be a valid UUID".into(),
         ));
     };
     Ok(guest_id)
@@ -20,3 +24,56 @@ pub fn extract_header(key: &str, header_map: &HeaderMap) -> Option<String> {
         .and_then(|header| header.to_str().ok())
         .map(|s| s.to_owned())
 }
+
+/// Lowercase language tag as used for keys in localized content, e.g. `nb` or `en-gb`
+pub fn is_valid_locale(locale: &str) -> bool {
+    (1..=MAX_LOCALE_LEN).contains(&locale.len())
+        && locale
+            .chars()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-')
+}
+
+/// Language tags from an `Accept-Language` header, highest quality first.
+/// Wildcards and tags with a quality of zero are left out
+pub fn parse_accept_language(header: &str) -> Vec<String> {
+    let mut tags: Vec<(String, f32)> = header
+        .split(',')
+        .filter_map(|part| {
+            let mut params = part.split(';');
+            let tag = params.next()?.trim();
+            let quality = params
+                .find_map(|param| param.trim().strip_prefix("q="))
+                .map_or(Some(1.0), |q| q.trim().parse::<f32>().ok())?;
+
+            match tag.is_empty() || tag == "*" || quality <= 0.0 {
+                true => None,
+                false => Some((tag.to_string(), quality)),
+            }
+        })
+        .collect();
+
+    // Stable, so tags of equal quality keep the order the client sent
+    tags.sort_by(|a, b| b.1.total_cmp(&a.1));
+    tags.into_iter().map(|(tag, _)| tag).collect()
+}
+
+/// Runs `f` until it succeeds, at most `attempts` times with `delay` between
+/// tries, and returns the last error. For background tasks where a transient
+/// database error should not be the end of it
+pub async fn try_with_retry<F, Fut, T, E>(attempts: u8, delay: Duration, mut f: F) -> Result<T, E>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<T, E>>,
+{
+    let mut attempt = 1;
+    loop {
+        match f().await {
+            Ok(value) => return Ok(value),
+            Err(e) if attempt >= attempts => return Err(e),
+            Err(_) => {
+                attempt += 1;
+                tokio::time::sleep(delay).await;
+            }
+        }
+    }
+}
@@ -0,0 +1,78 @@
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use serde_json::json;
+    use sqlx::{Pool, Postgres};
+    use uuid::Uuid;
+
+    use crate::{
+        db::{game_base::delete_game, quiz_game::tx_persist_quiz_session},
+        models::{
+            game_base::{CreateGameRequest, GameType},
+            quiz_game::{QuizQuestion, QuizSession},
+            system_log::LogAction,
+            user::SubjectId,
+        },
+        service::audit::Audited,
+        tests::support::TestDb,
+    };
+
+    async fn audit_rows(pool: &Pool<Postgres>, game_id: Uuid) -> i64 {
+        sqlx::query_scalar(
+            r#"
+            SELECT COUNT(*) FROM "system_log"
+            WHERE file_name = 'delete_game' AND metadata->'details'->>'game_id' = $1
+            "#,
+        )
+        .bind(game_id.to_string())
+        .fetch_one(pool)
+        .await
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn game_delete_writes_one_audit_row() {
+        let db = TestDb::create().await;
+        let owner = Uuid::new_v4();
+
+        let request = CreateGameRequest {
+            name: "Vorspiel".into(),
+            description: None,
+            category: None,
+            visibility: None,
+        };
+        let mut session = QuizSession::from_create_request(request);
+        session
+            .questions
+            .push(QuizQuestion::from_text("Hvem ler mest?"));
+        let game_id = session.base_id;
+
+        let mut tx = db.pool.begin().await.unwrap();
+        tx_persist_quiz_session(&mut tx, &session, owner)
+            .await
+            .unwrap();
+        tx.commit().await.unwrap();
+
+        delete_game(&db.pool, &GameType::Quiz, game_id, Some(owner))
+            .await
+            .audited(
+                &db.pool,
+                &SubjectId::BaseUser(owner),
+                LogAction::Delete,
+                "delete_game",
+                json!({ "game_id": game_id }),
+            )
+            .unwrap();
+
+        // The audit write is fire-and-forget
+        for _ in 0..50 {
+            if audit_rows(&db.pool, game_id).await > 0 {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(20)).await;
+        }
+
+        assert_eq!(audit_rows(&db.pool, game_id).await, 1);
+    }
+}
@@ -0,0 +1,221 @@
+#[cfg(test)]
+mod tests {
+    use chrono::Utc;
+    use serde_json::json;
+
+    use crate::{
+        api::auth_mw::verify_jwt,
+        models::{
+            auth::{Claims, Jwks},
+            error::ServerError,
+            user::Permission,
+        },
+    };
+
+    fn jwks_with(count: usize) -> serde_json::Value {
+        let keys: Vec<_> = (0..count)
+            .map(|i| {
+                json!({
+                    "kid": format!("kid-{}", i),
+                    "n": "modulus",
+                    "e": "AQAB",
+                    "kty": "RSA",
+                    "alg": "RS256",
+                    "use": "sig",
+                })
+            })
+            .collect();
+        json!({ "keys": keys })
+    }
+
+    #[test]
+    fn machine_token_with_single_string_audience() {
+        let payload = json!({
+            "iss": "https://dev-tero.eu.auth0.com/",
+            "sub": "x7Qm2kLp9RtZ4vWbN8cYfH3jD6sAeUg1@clients",
+            "aud": "https://api.tero.com",
+            "iat": 1_760_000_000,
+            "exp": 1_760_086_400,
+            "scope": "write:game write:system_log",
+            "gty": "client-credentials",
+            "azp": "x7Qm2kLp9RtZ4vWbN8cYfH3jD6sAeUg1",
+            "permissions": ["write:game", "write:system_log"]
+        });
+
+        let claims: Claims = serde_json::from_value(payload).unwrap();
+        assert!(claims.is_machine());
+        assert!(
+            claims
+                .missing_permission([Permission::WriteGame, Permission::WriteSystemLog])
+                .is_none()
+        );
+    }
+
+    #[test]
+    fn user_token_with_audience_array_and_no_scope() {
+        let payload = json!({
+            "iss": "https://dev-tero.eu.auth0.com/",
+            "sub": "google-oauth2|104857392018475629384",
+            "aud": ["https://api.tero.com", "https://dev-tero.eu.auth0.com/userinfo"],
+            "iat": 1_760_000_000,
+            "exp": 1_760_086_400,
+            "azp": "Hc5TnV0bW2xLq8PzR1yK4dJ7mF9gA3sE",
+            "permissions": []
+        });
+
+        let claims: Claims = serde_json::from_value(payload).unwrap();
+        assert!(!claims.is_machine());
+        assert_eq!(claims.scope, "");
+        assert_eq!(claims.auth0_id(), "google-oauth2|104857392018475629384");
+    }
+
+    #[test]
+    fn audience_of_wrong_shape_is_rejected() {
+        let payload = json!({
+            "iss": "https://dev-tero.eu.auth0.com/",
+            "sub": "auth0|6512f0c2a1b3c4d5e6f70819",
+            "aud": 42,
+            "iat": 1_760_000_000,
+            "exp": 1_760_086_400,
+            "azp": "Hc5TnV0bW2xLq8PzR1yK4dJ7mF9gA3sE"
+        });
+
+        assert!(serde_json::from_value::<Claims>(payload).is_err());
+    }
+
+    fn claims_with(permissions: &[Permission]) -> Claims {
+        let mut claims = Claims::empty();
+        claims.permissions = Some(permissions.iter().cloned().collect());
+        claims
+    }
+
+    #[test]
+    fn permission_implication_matrix() {
+        use Permission::*;
+
+        let implied = |held: &Permission| -> Vec<Permission> {
+            match held {
+                SuperAdmin => Permission::ALL.to_vec(),
+                WriteAdmin => vec![WriteAdmin, ReadAdmin, ReadGame, WriteGame, WriteSystemLog],
+                WriteGame => vec![WriteGame, ReadGame],
+                other => vec![other.clone()],
+            }
+        };
+
+        for held in Permission::ALL {
+            let claims = claims_with(std::slice::from_ref(&held));
+            for required in Permission::ALL {
+                let expected = implied(&held).contains(&required);
+                assert_eq!(
+                    held.implies(&required),
+                    expected,
+                    "{:?} -> {:?}",
+                    held,
+                    required
+                );
+                assert_eq!(
+                    claims.missing_permission([required.clone()]).is_none(),
+                    expected,
+                    "{:?} -> {:?}",
+                    held,
+                    required
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn read_scopes_never_grant_write() {
+        let claims = claims_with(&[Permission::ReadAdmin]);
+        let missing = claims
+            .missing_permission([
+                Permission::ReadAdmin,
+                Permission::WriteAdmin,
+                Permission::WriteGame,
+            ])
+            .unwrap();
+
+        assert!(!missing.contains(&Permission::ReadAdmin));
+        assert!(missing.contains(&Permission::WriteAdmin));
+        assert!(missing.contains(&Permission::WriteGame));
+    }
+
+    #[test]
+    fn super_admin_scope_deserializes() {
+        let permission: Permission = serde_json::from_value(json!("super:admin")).unwrap();
+        assert_eq!(permission, Permission::SuperAdmin);
+    }
+
+    #[test]
+    fn scopes_round_trip() {
+        for permission in Permission::ALL {
+            let parsed: Permission = serde_json::from_value(json!(permission.scope())).unwrap();
+            assert_eq!(parsed, permission);
+        }
+    }
+
+    #[test]
+    fn expiry_is_checked_against_current_time() {
+        let claims_expiring_at = |exp: i64| -> Claims {
+            serde_json::from_value(json!({
+                "iss": "https://dev-tero.eu.auth0.com/",
+                "sub": "auth0|6512f0c2a1b3c4d5e6f70819",
+                "aud": "https://api.tero.com",
+                "iat": exp - 3600,
+                "exp": exp,
+                "azp": "Hc5TnV0bW2xLq8PzR1yK4dJ7mF9gA3sE"
+            }))
+            .unwrap()
+        };
+        let now = Utc::now().timestamp();
+
+        assert!(claims_expiring_at(now - 60).is_expired(0));
+        assert!(!claims_expiring_at(now + 60).is_expired(0));
+        assert!(!claims_expiring_at(now - 60).is_expired(120));
+        assert!(Claims::empty().is_expired(30));
+    }
+
+    #[test]
+    fn any_and_all_permission_checks() {
+        let claims = claims_with(&[Permission::ReadAdmin, Permission::WriteGame]);
+
+        assert!(claims.has_any_permission([Permission::WriteAdmin, Permission::ReadAdmin]));
+        assert!(!claims.has_any_permission([Permission::WriteAdmin, Permission::WriteBilling]));
+        assert!(claims.has_all_permissions([Permission::ReadAdmin, Permission::ReadGame]));
+        assert!(!claims.has_all_permissions([Permission::ReadAdmin, Permission::WriteAdmin]));
+    }
+
+    #[test]
+    fn empty_permission_checks() {
+        let without_claims = Claims::empty();
+        let with_none = claims_with(&[]);
+
+        for claims in [&without_claims, &with_none] {
+            assert!(!claims.has_any_permission([Permission::ReadAdmin]));
+            assert!(!claims.has_all_permissions([Permission::ReadAdmin]));
+            assert!(!claims.has_any_permission([]));
+            assert!(claims.has_all_permissions([]));
+            assert!(claims.missing_permission([]).is_none());
+        }
+    }
+
+    #[test]
+    fn jwks_deserializes_with_any_number_of_keys() {
+        for count in 1..=3 {
+            let jwks: Jwks = serde_json::from_value(jwks_with(count)).unwrap();
+            assert_eq!(jwks.keys.len(), count);
+        }
+    }
+
+    #[tokio::test]
+    async fn empty_jwks_rejects_tokens() {
+        let jwks: Jwks = serde_json::from_value(jwks_with(0)).unwrap();
+        // Never reaches the signature check, so the token does not need to be real
+        let token = "eyJhbGciOiJSUzI1NiIsImtpZCI6ImtpZC0wIn0.e30.c2ln";
+
+        match verify_jwt(token, &jwks).await {
+            Err(ServerError::JwtVerification(reason)) => assert_eq!(reason, "No JWKs are loaded"),
+            other => panic!("Expected a jwt verification error, got {:?}", other),
+        }
+    }
+}
@@ -0,0 +1,126 @@
+#[cfg(test)]
+mod tests {
+    use jsonwebtoken::{
+        Algorithm, DecodingKey, EncodingKey, Header, encode, get_current_timestamp,
+    };
+    use once_cell::sync::Lazy;
+    use rsa::{
+        RsaPrivateKey,
+        pkcs1::{EncodeRsaPrivateKey, EncodeRsaPublicKey, LineEnding},
+        rand_core::OsRng,
+    };
+    use serde_json::{Value, json};
+
+    use crate::{api::auth_mw::decode_claims, models::error::ServerError};
+
+    static AUDIENCE: &str = "https://api.tero.com";
+    static ISSUER: &str = "https://dev-tero.eu.auth0.com/";
+    static LEEWAY: u64 = 30;
+
+    // Generating a key is slow in debug builds, so the tests share one
+    static KEYS: Lazy<(EncodingKey, DecodingKey)> = Lazy::new(|| {
+        let private = RsaPrivateKey::new(&mut OsRng, 2048).unwrap();
+        let private_pem = private.to_pkcs1_pem(LineEnding::LF).unwrap();
+        let public_pem = private
+            .to_public_key()
+            .to_pkcs1_pem(LineEnding::LF)
+            .unwrap();
+
+        (
+            EncodingKey::from_rsa_pem(private_pem.as_bytes()).unwrap(),
+            DecodingKey::from_rsa_pem(public_pem.as_bytes()).unwrap(),
+        )
+    });
+
+    fn claims() -> Value {
+        let now = get_current_timestamp() as i64;
+        json!({
+            "gty": "client-credentials",
+            "aud": [AUDIENCE],
+            "azp": "session",
+            "exp": now + 600,
+            "iat": now,
+            "iss": ISSUER,
+            "scope": "",
+            "sub": "session@clients",
+            "permissions": ["write:game"],
+        })
+    }
+
+    fn sign(claims: &Value) -> String {
+        encode(&Header::new(Algorithm::RS256), claims, &KEYS.0).unwrap()
+    }
+
+    fn rejection(token: &str) -> String {
+        match decode_claims(token, &KEYS.1, AUDIENCE, ISSUER, LEEWAY) {
+            Err(ServerError::JwtVerification(reason)) => reason,
+            other => panic!("Expected a jwt verification error, got {:?}", other),
+        }
+    }
+
+    fn with(field: &str, value: Value) -> String {
+        let mut claims = claims();
+        claims[field] = value;
+        sign(&claims)
+    }
+
+    #[test]
+    fn valid_token_is_accepted() {
+        let claims = decode_claims(&sign(&claims()), &KEYS.1, AUDIENCE, ISSUER, LEEWAY).unwrap();
+        assert_eq!(claims.sub, "session@clients");
+    }
+
+    #[test]
+    fn each_failure_class_has_its_own_message() {
+        let now = get_current_timestamp() as i64;
+
+        assert_eq!(
+            rejection(&with("exp", json!(now - 60))),
+            "Token has expired"
+        );
+        assert_eq!(
+            rejection(&with("nbf", json!(now + 300))),
+            "Token is not valid yet"
+        );
+        assert_eq!(
+            rejection(&with("iat", json!(now + 300))),
+            "Token is issued in the future"
+        );
+        assert_eq!(
+            rejection(&with("aud", json!(["https://other.api"]))),
+            "Invalid token audience"
+        );
+        assert_eq!(
+            rejection(&with("iss", json!("https://evil.auth0.com/"))),
+            "Invalid token issuer"
+        );
+
+        let token = sign(&claims());
+        let (unsigned, signature) = token.rsplit_once('.').unwrap();
+        let tampered = match signature.starts_with('A') {
+            true => format!("{}.B{}", unsigned, &signature[1..]),
+            false => format!("{}.A{}", unsigned, &signature[1..]),
+        };
+        assert_eq!(rejection(&tampered), "Invalid token signature");
+        assert_eq!(rejection(&with("sub", json!(42))), "Invalid token claims");
+        assert_eq!(rejection("not.a.token"), "Malformed token");
+    }
+
+    #[test]
+    fn clock_skew_within_leeway_is_tolerated() {
+        let now = get_current_timestamp() as i64;
+        let skew = LEEWAY as i64 - 10;
+
+        for (field, value) in [
+            ("exp", now - skew),
+            ("nbf", now + skew),
+            ("iat", now + skew),
+        ] {
+            let token = with(field, json!(value));
+            assert!(
+                decode_claims(&token, &KEYS.1, AUDIENCE, ISSUER, LEEWAY).is_ok(),
+                "{field}"
+            );
+        }
+    }
+}
@@ -0,0 +1,60 @@
+#[cfg(test)]
+mod tests {
+    use crate::{
+        models::game_base::{GamePageQuery, GameType},
+        service::cache::GustCache,
+    };
+
+    #[tokio::test]
+    async fn get_or_counts_hits_and_misses() {
+        let cache: GustCache<u32> = GustCache::from_ttl(60);
+        let key = "page";
+
+        let value = cache.get_or(&key, async || Ok(7)).await.unwrap();
+        assert_eq!(value, 7);
+        let stats = cache.stats();
+        assert_eq!((stats.hits, stats.misses), (0, 1));
+
+        cache.get_or(&key, async || Ok(8)).await.unwrap();
+        let stats = cache.stats();
+        assert_eq!((stats.hits, stats.misses), (1, 1));
+
+        let value = cache.get_or(&key, async || Ok(9)).await.unwrap();
+        assert_eq!(value, 7);
+        let stats = cache.stats();
+        assert_eq!((stats.hits, stats.misses), (2, 1));
+        assert_eq!(stats.current_entries, 1);
+        assert!((stats.hit_rate - 2.0 / 3.0).abs() < f64::EPSILON);
+    }
+
+    #[tokio::test]
+    async fn clear_resets_counters() {
+        let cache: GustCache<u32> = GustCache::from_ttl(60);
+        cache.get_or(&1, async || Ok(1)).await.unwrap();
+        cache.get_or(&1, async || Ok(1)).await.unwrap();
+
+        cache.clear();
+        let stats = cache.stats();
+        assert_eq!((stats.hits, stats.misses, stats.current_entries), (0, 0, 0));
+        assert_eq!(stats.hit_rate, 0.0);
+    }
+
+    #[tokio::test]
+    async fn invalidate_reruns_loader_within_ttl() {
+        let cache: GustCache<u32> = GustCache::from_ttl(60);
+        let query = GamePageQuery {
+            page_num: 0,
+            game_type: GameType::Quiz,
+            category: None,
+            trending: false,
+        };
+
+        cache.get_or(&query, async || Ok(1)).await.unwrap();
+        assert!(cache.invalidate(&query));
+        assert!(!cache.invalidate(&query));
+
+        let value = cache.get_or(&query, async || Ok(2)).await.unwrap();
+        assert_eq!(value, 2);
+        assert_eq!(cache.stats().misses, 2);
+    }
+}
@@ -0,0 +1,64 @@
+#[cfg(test)]
+mod tests {
+    use axum::{
+        Router,
+        body::Body,
+        extract::Request,
+        http::{
+            HeaderMap, Method, StatusCode,
+            header::{
+                ACCESS_CONTROL_ALLOW_CREDENTIALS, ACCESS_CONTROL_ALLOW_HEADERS,
+                ACCESS_CONTROL_ALLOW_ORIGIN, ACCESS_CONTROL_REQUEST_HEADERS,
+                ACCESS_CONTROL_REQUEST_METHOD, ORIGIN,
+            },
+        },
+        routing::get,
+    };
+    use tower::ServiceExt;
+
+    use crate::{api::cors_mw::cors_layer, config::config::CorsConfig};
+
+    static ALLOWED: &str = "http://localhost:8081";
+
+    fn router() -> Router {
+        let config = CorsConfig {
+            allowed_origins: vec![ALLOWED.into()],
+            allow_credentials: true,
+        };
+
+        Router::new()
+            .route("/games", get(|| async { StatusCode::OK }))
+            .layer(cors_layer(&config))
+    }
+
+    async fn preflight(origin: &str) -> (StatusCode, HeaderMap) {
+        let request = Request::builder()
+            .method(Method::OPTIONS)
+            .uri("/games")
+            .header(ORIGIN, origin)
+            .header(ACCESS_CONTROL_REQUEST_METHOD, "GET")
+            .header(ACCESS_CONTROL_REQUEST_HEADERS, "x-guest-authentication")
+            .body(Body::empty())
+            .unwrap();
+
+        let response = router().oneshot(request).await.unwrap();
+        (response.status(), response.headers().clone())
+    }
+
+    #[tokio::test]
+    async fn preflight_from_allowed_origin_is_answered() {
+        let (status, headers) = preflight(ALLOWED).await;
+
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(headers[ACCESS_CONTROL_ALLOW_ORIGIN], ALLOWED);
+        assert_eq!(headers[ACCESS_CONTROL_ALLOW_CREDENTIALS], "true");
+        let allowed_headers = headers[ACCESS_CONTROL_ALLOW_HEADERS].to_str().unwrap();
+        assert!(allowed_headers.contains("x-guest-authentication"));
+    }
+
+    #[tokio::test]
+    async fn preflight_from_unknown_origin_gets_no_cors_headers() {
+        let (_, headers) = preflight("https://evil.example").await;
+        assert!(!headers.contains_key(ACCESS_CONTROL_ALLOW_ORIGIN));
+    }
+}
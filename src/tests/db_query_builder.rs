@@ -0,0 +1,127 @@
+#[cfg(test)]
+mod tests {
+    use axum::{http::StatusCode, response::IntoResponse};
+    use uuid::Uuid;
+
+    use crate::{models::system_log::LogCeverity, service::db_query_builder::DBQueryBuilder};
+
+    #[test]
+    fn search_and_filters_compose_with_and() {
+        let builder = DBQueryBuilder::select("id", &[])
+            .from("system_log")
+            .where_opt("ceverity", Some(LogCeverity::Critical))
+            .where_ilike_any_opt(&["description", "file_name"], Some("timeout"))
+            .build();
+
+        assert_eq!(
+            builder.sql(),
+            "SELECT id FROM system_log WHERE ceverity = $1 AND (description ILIKE $2 OR file_name ILIKE $3)"
+        );
+    }
+
+    #[test]
+    fn search_first_opens_where_clause() {
+        let builder = DBQueryBuilder::select("id", &[])
+            .from("system_log")
+            .where_ilike_any_opt(&["description"], Some("timeout"))
+            .where_gte_opt("created_at", Some(1))
+            .where_opt::<i32>("action", None)
+            .build();
+
+        assert_eq!(
+            builder.sql(),
+            "SELECT id FROM system_log WHERE (description ILIKE $1) AND created_at >= $2"
+        );
+    }
+
+    #[test]
+    fn blank_search_is_skipped() {
+        let builder = DBQueryBuilder::select("id", &[])
+            .from("system_log")
+            .where_ilike_any_opt(&["description"], Some("   "))
+            .build();
+
+        assert_eq!(builder.sql(), "SELECT id FROM system_log");
+    }
+
+    #[test]
+    fn order_pushes_allowlisted_column_as_identifier() {
+        let builder = DBQueryBuilder::select("id", &["created_at", "times_played"])
+            .from("system_log")
+            .where_opt("ceverity", Some(LogCeverity::Info))
+            .order_desc("created_at")
+            .limit(21)
+            .build();
+
+        assert_eq!(
+            builder.sql(),
+            "SELECT id FROM system_log WHERE ceverity = $1 ORDER BY created_at DESC LIMIT $2"
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "not in allowlist")]
+    fn order_rejects_column_outside_allowlist() {
+        DBQueryBuilder::select("id", &["created_at"])
+            .from("system_log")
+            .order_asc("created_at; DROP TABLE system_log");
+    }
+
+    #[test]
+    fn null_checks_respect_where_clause() {
+        let builder = DBQueryBuilder::select("id", &[])
+            .from("game_base")
+            .where_null("deleted_at")
+            .where_opt("category", Some(1))
+            .where_not_null("last_played")
+            .build();
+
+        assert_eq!(
+            builder.sql(),
+            "SELECT id FROM game_base WHERE deleted_at IS NULL AND category = $1 AND last_played IS NOT NULL"
+        );
+    }
+
+    #[test]
+    fn joins_come_before_where_clause() {
+        let builder = DBQueryBuilder::select("base.id", &[])
+            .from("game_base base")
+            .inner_join("saved_game saved", "base.id = saved.base_id")
+            .left_join("quiz_game quiz", "base.id = quiz.base_id")
+            .r#where("saved.user_id", 1)
+            .build();
+
+        assert_eq!(
+            builder.sql(),
+            "SELECT base.id FROM game_base base INNER JOIN saved_game saved ON base.id = saved.base_id LEFT JOIN quiz_game quiz ON base.id = quiz.base_id WHERE saved.user_id = $1"
+        );
+    }
+
+    #[test]
+    fn update_sets_only_present_fields() {
+        let builder = DBQueryBuilder::update("base_user")
+            .set_if_some("username", Some("ola"))
+            .set_if_some::<&str>("given_name", None)
+            .set_if_some("family_name", Some("Nordmann"))
+            .where_id(Uuid::nil())
+            .returning("id")
+            .build()
+            .unwrap();
+
+        assert_eq!(
+            builder.sql(),
+            "UPDATE base_user SET username = $1, family_name = $2 WHERE id = $3 RETURNING id"
+        );
+    }
+
+    #[test]
+    fn update_without_fields_is_rejected() {
+        let result = DBQueryBuilder::update("base_user")
+            .set_if_some::<&str>("username", None)
+            .where_id(Uuid::nil())
+            .build();
+
+        let status = result.err().unwrap().into_response().status();
+        assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+    }
+}
@@ -0,0 +1,77 @@
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use axum::{body::to_bytes, http::StatusCode, response::IntoResponse};
+    use serde_json::Value;
+
+    use crate::models::{
+        error::{FieldError, ServerError},
+        user::Permission,
+    };
+
+    async fn respond(error: ServerError) -> (StatusCode, Option<Value>) {
+        let response = error.into_response();
+        let status = response.status();
+        let bytes = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let body = match bytes.is_empty() {
+            true => None,
+            false => Some(serde_json::from_slice(&bytes).unwrap()),
+        };
+
+        (status, body)
+    }
+
+    #[tokio::test]
+    async fn client_errors_carry_code_and_message() {
+        let (status, body) = respond(ServerError::NotFound("Game does not exist".into())).await;
+        let body = body.unwrap();
+
+        assert_eq!(status, StatusCode::NOT_FOUND);
+        assert_eq!(body["code"], "not_found");
+        assert_eq!(body["message"], "Game does not exist");
+        assert!(body.get("details").is_none());
+
+        let (status, body) =
+            respond(ServerError::Api(StatusCode::BAD_REQUEST, "Bad key".into())).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(body.unwrap()["code"], "bad_request");
+    }
+
+    #[tokio::test]
+    async fn permission_error_lists_missing_scopes() {
+        let missing = HashSet::from([Permission::WriteGame, Permission::ReadAdmin]);
+        let (status, body) = respond(ServerError::Permission(missing)).await;
+        let body = body.unwrap();
+
+        assert_eq!(status, StatusCode::FORBIDDEN);
+        assert_eq!(body["code"], "missing_permission");
+        assert_eq!(
+            body["details"]["missing"],
+            serde_json::json!(["read:admin", "write:game"])
+        );
+    }
+
+    #[tokio::test]
+    async fn validation_error_has_entry_per_field() {
+        let errors = vec![
+            FieldError::new("name", "Name is required"),
+            FieldError::new("description", "Description is too long"),
+        ];
+        let (status, body) = respond(ServerError::Validation(errors)).await;
+        let body = body.unwrap();
+
+        assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+        assert_eq!(body["code"], "validation_failed");
+        assert_eq!(body["details"]["fields"][1]["field"], "description");
+        assert_eq!(body["details"]["fields"].as_array().unwrap().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn internal_errors_keep_empty_body() {
+        let (status, body) = respond(ServerError::Internal("db password leaked".into())).await;
+
+        assert_eq!(status, StatusCode::INTERNAL_SERVER_ERROR);
+        assert!(body.is_none());
+    }
+}
@@ -0,0 +1,17 @@
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use crate::config::config::FeatureFlags;
+
+    #[test]
+    fn only_configured_flags_are_enabled() {
+        let flags: FeatureFlags =
+            serde_json::from_value(json!({"cache_warmup": true, "beta": false})).unwrap();
+
+        assert!(flags.is_enabled("cache_warmup"));
+        assert!(!flags.is_enabled("beta"));
+        assert!(!flags.is_enabled("missing"));
+        assert!(!FeatureFlags::default().is_enabled("cache_warmup"));
+    }
+}
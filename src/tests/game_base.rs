@@ -0,0 +1,875 @@
+#[cfg(test)]
+mod tests {
+    use std::{collections::HashMap, time::Duration};
+
+    use axum::{http::StatusCode, response::IntoResponse};
+    use sqlx::{Pool, Postgres};
+    use uuid::Uuid;
+
+    use crate::{
+        api::game_base::{persist_session_envelope, standalone_session, start_with_game_key},
+        client::gs_client::GSClient,
+        db::{
+            game_base::{
+                bulk_delete_saved_games, bulk_save_games, delete_expired_game_plays, delete_game,
+                delete_non_active_games, delete_saved_game, game_base_exists, get_game_by_id,
+                get_game_page, get_saved_games_page, get_user_games_page, is_game_saved,
+                record_session_played, save_game, tx_reassign_game_owner,
+            },
+            quiz_game::{get_quiz_session_by_id, tx_persist_quiz_session},
+        },
+        models::{
+            error::{ErrorCode, ServerError},
+            game_base::{
+                CreateGameRequest, ENVELOPE_SCHEMA_VERSION, GameCategory, GameConverter,
+                GamePageItem, GamePageQuery, GameSession, GameType, GameVisibility,
+                InteractiveEnvelope, MAX_NAME_CHARS, SavedGameSort, SavedGamesPageQuery,
+                UserGamesPageQuery, Validate,
+            },
+            popup_manager::PagedResponse,
+            quiz_game::{QuizQuestion, QuizSession},
+            user::SubjectId,
+        },
+        service::{cache::GustCache, idempotency_vault::IdempotencyVault, key_vault::KeyVault},
+        tests::support::{TestApp, TestDb, seed_games, seed_users},
+    };
+
+    #[tokio::test]
+    async fn game_page_filters_and_orders_by_times_played() {
+        let db = TestDb::create().await;
+        let query = GamePageQuery {
+            page_num: 0,
+            game_type: GameType::Quiz,
+            category: Some(GameCategory::Default.into()),
+            trending: false,
+        };
+
+        let page = get_game_page(&db.pool, &query).await.unwrap();
+        let page = serde_json::to_value(page).unwrap();
+        let items = page["items"].as_array().unwrap();
+
+        assert!(items.iter().all(|g| g["game_type"] == "Quiz"));
+        assert!(items.iter().all(|g| g["category"] == "default"));
+        assert!(
+            items
+                .windows(2)
+                .all(|w| w[0]["times_played"].as_i64() >= w[1]["times_played"].as_i64())
+        );
+    }
+
+    #[test]
+    fn create_request_reports_each_invalid_field() {
+        let request = CreateGameRequest {
+            name: "  ".into(),
+            description: Some("x".repeat(501)),
+            category: None,
+            visibility: None,
+        };
+
+        let fields: Vec<String> = request
+            .field_errors()
+            .into_iter()
+            .map(|e| e.field)
+            .collect();
+        assert_eq!(fields, vec!["name", "description"]);
+
+        let response = request.validate().unwrap_err().into_response();
+        assert_eq!(response.status(), StatusCode::UNPROCESSABLE_ENTITY);
+    }
+
+    #[test]
+    fn create_request_name_is_bounded_after_trimming() {
+        let request = |name: String| CreateGameRequest {
+            name,
+            description: None,
+            category: None,
+            visibility: None,
+        };
+
+        assert!(request("".into()).validate().is_err());
+        assert!(request("x".repeat(MAX_NAME_CHARS + 1)).validate().is_err());
+        assert!(request("x".repeat(MAX_NAME_CHARS)).validate().is_ok());
+        assert!(
+            request(format!(" {} ", "x".repeat(MAX_NAME_CHARS)))
+                .validate()
+                .is_ok()
+        );
+    }
+
+    #[test]
+    fn quiz_session_requires_bounded_questions() {
+        let request = CreateGameRequest {
+            name: "Vorspiel".into(),
+            description: None,
+            category: None,
+            visibility: None,
+        };
+        let mut session = QuizSession::from_create_request(request);
+
+        let fields: Vec<String> = session
+            .field_errors()
+            .into_iter()
+            .map(|e| e.field)
+            .collect();
+        assert_eq!(fields, vec!["questions"]);
+
+        session.questions = vec![
+            QuizQuestion::from_text("Hvem ler mest?"),
+            QuizQuestion::from_text("x".repeat(301)),
+        ];
+        let fields: Vec<String> = session
+            .field_errors()
+            .into_iter()
+            .map(|e| e.field)
+            .collect();
+        assert_eq!(fields, vec!["questions[1]"]);
+
+        session.questions.pop();
+        assert!(session.validate().is_ok());
+    }
+
+    async fn user_game_ids(pool: &Pool<Postgres>, user_id: Uuid) -> Vec<Uuid> {
+        let query = UserGamesPageQuery {
+            page_num: 0,
+            game_type: Some(GameType::Quiz),
+        };
+        let page = get_user_games_page(pool, user_id, &query).await.unwrap();
+        let page = serde_json::to_value(page).unwrap();
+
+        page["items"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|g| serde_json::from_value(g["id"].clone()).unwrap())
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn ownership_follows_registration_and_guards_deletion() {
+        let db = TestDb::create().await;
+        let pseudo_id = Uuid::new_v4();
+        let base_id = Uuid::new_v4();
+
+        let request = CreateGameRequest {
+            name: "Vorspiel".into(),
+            description: None,
+            category: None,
+            visibility: None,
+        };
+        let mut session = QuizSession::from_create_request(request);
+        session
+            .questions
+            .push(QuizQuestion::from_text("Hvem ler mest?"));
+
+        let mut tx = db.pool.begin().await.unwrap();
+        tx_persist_quiz_session(&mut tx, &session, pseudo_id)
+            .await
+            .unwrap();
+        tx.commit().await.unwrap();
+        assert_eq!(
+            user_game_ids(&db.pool, pseudo_id).await,
+            vec![session.base_id]
+        );
+
+        let mut tx = db.pool.begin().await.unwrap();
+        let moved = tx_reassign_game_owner(&mut tx, pseudo_id, base_id)
+            .await
+            .unwrap();
+        tx.commit().await.unwrap();
+        assert_eq!(moved, 1);
+        assert!(user_game_ids(&db.pool, pseudo_id).await.is_empty());
+        assert_eq!(
+            user_game_ids(&db.pool, base_id).await,
+            vec![session.base_id]
+        );
+
+        let error = delete_game(&db.pool, &GameType::Quiz, session.base_id, Some(pseudo_id))
+            .await
+            .unwrap_err();
+        assert!(matches!(error, ServerError::NotFound(_)));
+
+        delete_game(&db.pool, &GameType::Quiz, session.base_id, Some(base_id))
+            .await
+            .unwrap();
+        assert!(user_game_ids(&db.pool, base_id).await.is_empty());
+    }
+
+    #[tokio::test]
+    async fn user_games_page_offset_does_not_overflow() {
+        let db = TestDb::create().await;
+        let query = UserGamesPageQuery {
+            page_num: u16::MAX,
+            game_type: None,
+        };
+
+        let page = get_user_games_page(&db.pool, Uuid::new_v4(), &query)
+            .await
+            .unwrap();
+        let page = serde_json::to_value(page).unwrap();
+        assert_eq!(page["items"], serde_json::json!([]));
+    }
+
+    async fn persist_quiz(pool: &Pool<Postgres>, owner: Uuid, visibility: GameVisibility) -> Uuid {
+        let request = CreateGameRequest {
+            name: "Vorspiel".into(),
+            description: None,
+            category: Some(GameCategory::Casual.into()),
+            visibility: Some(visibility),
+        };
+        let mut session = QuizSession::from_create_request(request);
+        session
+            .questions
+            .push(QuizQuestion::from_text("Hvem ler mest?"));
+
+        let mut tx = pool.begin().await.unwrap();
+        tx_persist_quiz_session(&mut tx, &session, owner)
+            .await
+            .unwrap();
+        tx.commit().await.unwrap();
+        session.base_id
+    }
+
+    #[tokio::test]
+    async fn cached_game_pages_only_hold_public_games() {
+        let db = TestDb::create().await;
+        let cache: GustCache<PagedResponse<GamePageItem>> = GustCache::from_ttl(60);
+        let owner = Uuid::new_v4();
+
+        let public_id = persist_quiz(&db.pool, owner, GameVisibility::Public).await;
+        let unlisted_id = persist_quiz(&db.pool, owner, GameVisibility::Unlisted).await;
+        let private_id = persist_quiz(&db.pool, owner, GameVisibility::Private).await;
+
+        let mut ids: Vec<Uuid> = Vec::new();
+        for page_num in 0.. {
+            let query = GamePageQuery {
+                page_num,
+                game_type: GameType::Quiz,
+                category: Some(GameCategory::Casual.into()),
+                trending: false,
+            };
+            let page = cache
+                .get_or(&query, || get_game_page(&db.pool, &query))
+                .await
+                .unwrap();
+            let page = serde_json::to_value(page).unwrap();
+
+            for game in page["items"].as_array().unwrap() {
+                assert_eq!(game["visibility"], "Public");
+                ids.push(serde_json::from_value(game["id"].clone()).unwrap());
+            }
+            if page["has_next"] == false {
+                break;
+            }
+        }
+
+        assert!(ids.contains(&public_id));
+        assert!(!ids.contains(&unlisted_id));
+        assert!(!ids.contains(&private_id));
+    }
+
+    #[tokio::test]
+    async fn game_pages_show_saves_and_creator_name() {
+        let db = TestDb::create().await;
+        let owner = insert_base_user(&db.pool).await;
+        let saved_id = persist_quiz(&db.pool, owner, GameVisibility::Public).await;
+        let guest_id = persist_quiz(&db.pool, Uuid::new_v4(), GameVisibility::Public).await;
+
+        for _ in 0..2 {
+            let user_id = insert_base_user(&db.pool).await;
+            save_game(&db.pool, user_id, saved_id).await.unwrap();
+        }
+
+        let mut items: Vec<GamePageItem> = Vec::new();
+        for page_num in 0.. {
+            let query = GamePageQuery {
+                page_num,
+                game_type: GameType::Quiz,
+                category: Some(GameCategory::Casual.into()),
+                trending: false,
+            };
+            let page = get_game_page(&db.pool, &query).await.unwrap();
+            let page = serde_json::to_value(page).unwrap();
+
+            items.extend(
+                serde_json::from_value::<Vec<GamePageItem>>(page["items"].clone()).unwrap(),
+            );
+            if page["has_next"] == false {
+                break;
+            }
+        }
+
+        let item = |id: Uuid| items.iter().find(|item| item.game.id == id).unwrap();
+        assert_eq!(item(saved_id).saves, 2);
+        assert_eq!(
+            item(saved_id).creator_name.as_deref(),
+            Some("kari.nordmann")
+        );
+        assert_eq!(item(guest_id).saves, 0);
+        assert_eq!(item(guest_id).creator_name, None);
+    }
+
+    #[test]
+    fn private_games_are_only_accessible_to_owner_and_admins() {
+        let owner = Uuid::new_v4();
+        let other = Uuid::new_v4();
+
+        assert!(GameVisibility::Unlisted.is_accessible(Some(owner), other, false));
+        assert!(GameVisibility::Private.is_accessible(Some(owner), owner, false));
+        assert!(GameVisibility::Private.is_accessible(Some(owner), other, true));
+        assert!(!GameVisibility::Private.is_accessible(Some(owner), other, false));
+        assert!(!GameVisibility::Private.is_accessible(None, other, false));
+    }
+
+    #[tokio::test]
+    async fn private_games_can_only_be_started_by_owner_and_admins() {
+        let app = TestApp::spawn().await;
+        let owner = Uuid::new_v4();
+        let other = Uuid::new_v4();
+        let private = persist_quiz(&app.db.pool, owner, GameVisibility::Private).await;
+
+        let pool = &app.db.pool;
+        let (owner, other) = (SubjectId::PseudoUser(owner), SubjectId::PseudoUser(other));
+        let result = standalone_session(pool, &other, GameType::Quiz, private, false).await;
+        assert!(matches!(result, Err(ServerError::NotFound(_))));
+        let result = standalone_session(pool, &owner, GameType::Quiz, private, false).await;
+        assert!(result.is_ok());
+        let result = standalone_session(pool, &other, GameType::Quiz, private, true).await;
+        assert!(result.is_ok());
+
+        let standalone = app
+            .client
+            .get(app.url(&format!("/games/static/Quiz/initiate/{}", private)))
+            .header("X-Guest-Authentication", other.id_string())
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(standalone.status(), StatusCode::NOT_FOUND);
+
+        let interactive = app
+            .client
+            .post(app.url(&format!("/games/session/Quiz/initiate/{}", private)))
+            .header("X-Guest-Authentication", other.id_string())
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(interactive.status(), StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn trending_page_orders_by_recent_plays() {
+        let db = TestDb::create().await;
+        let owner = Uuid::new_v4();
+        let quiet_id = persist_quiz(&db.pool, owner, GameVisibility::Public).await;
+        let busy_id = persist_quiz(&db.pool, owner, GameVisibility::Public).await;
+
+        for _ in 0..3 {
+            record_session_played(&db.pool, GameType::Quiz, busy_id, None, 0)
+                .await
+                .unwrap();
+        }
+
+        let mut ids: Vec<Uuid> = Vec::new();
+        for page_num in 0.. {
+            let query = GamePageQuery {
+                page_num,
+                game_type: GameType::Quiz,
+                category: Some(GameCategory::Casual.into()),
+                trending: true,
+            };
+            let page =
+                serde_json::to_value(get_game_page(&db.pool, &query).await.unwrap()).unwrap();
+
+            for game in page["items"].as_array().unwrap() {
+                ids.push(serde_json::from_value(game["id"].clone()).unwrap());
+            }
+            if page["has_next"] == false {
+                break;
+            }
+        }
+
+        let position = |id: Uuid| ids.iter().position(|i| *i == id).unwrap();
+        assert!(position(busy_id) < position(quiet_id));
+        delete_expired_game_plays(&db.pool).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn replayed_persist_stores_a_single_game() {
+        let db = TestDb::create().await;
+        let request = CreateGameRequest {
+            name: "Vorspiel".into(),
+            description: None,
+            category: None,
+            visibility: None,
+        };
+        let mut session = QuizSession::from_create_request(request);
+        session
+            .questions
+            .push(QuizQuestion::from_text("Hvem ler mest?"));
+        let base_id = session.base_id;
+
+        let envelope = || InteractiveEnvelope {
+            game_key: "fest lyd".into(),
+            host_id: Uuid::new_v4(),
+            game_type: GameType::Quiz,
+            schema_version: ENVELOPE_SCHEMA_VERSION,
+            payload: session.to_json_value().unwrap(),
+        };
+
+        let vault = IdempotencyVault::from_ttl(Duration::from_secs(300));
+        let first = persist_session_envelope(&db.pool, &vault, envelope()).await;
+        let second = persist_session_envelope(&db.pool, &vault, envelope()).await;
+        assert_eq!(first.unwrap(), Some(GameType::Quiz));
+        assert_eq!(second.unwrap(), None);
+
+        // A restarted server has an empty vault and relies on the upsert
+        let restarted = IdempotencyVault::from_ttl(Duration::from_secs(300));
+        let third = persist_session_envelope(&db.pool, &restarted, envelope()).await;
+        assert_eq!(third.unwrap(), None);
+
+        for sql in [
+            r#"SELECT COUNT(*) FROM "game_base" WHERE id = $1"#,
+            r#"SELECT COUNT(*) FROM "quiz_game" WHERE base_id = $1"#,
+            r#"SELECT COUNT(*) FROM "game_play" WHERE base_id = $1"#,
+        ] {
+            let count = sqlx::query_scalar::<_, i64>(sql)
+                .bind(base_id)
+                .fetch_one(&db.pool)
+                .await
+                .unwrap();
+            assert_eq!(count, 1, "{}", sql);
+        }
+    }
+
+    #[tokio::test]
+    async fn played_quiz_session_is_stored_when_missing() {
+        let db = TestDb::create().await;
+        let request = CreateGameRequest {
+            name: "Etterspill".into(),
+            description: None,
+            category: None,
+            visibility: None,
+        };
+        let mut session = QuizSession::from_create_request(request);
+        session
+            .questions
+            .push(QuizQuestion::from_text("Hvem danser best?"));
+        session.times_played = 3;
+
+        let envelope = InteractiveEnvelope {
+            game_key: "sen kveld".into(),
+            host_id: Uuid::new_v4(),
+            game_type: GameType::Quiz,
+            schema_version: ENVELOPE_SCHEMA_VERSION,
+            payload: session.to_json_value().unwrap(),
+        };
+
+        let vault = IdempotencyVault::from_ttl(Duration::from_secs(300));
+        let persisted = persist_session_envelope(&db.pool, &vault, envelope).await;
+        assert_eq!(persisted.unwrap(), Some(GameType::Quiz));
+
+        let count =
+            sqlx::query_scalar::<_, i64>(r#"SELECT COUNT(*) FROM "quiz_game" WHERE base_id = $1"#)
+                .bind(session.base_id)
+                .fetch_one(&db.pool)
+                .await
+                .unwrap();
+        assert_eq!(count, 1);
+    }
+
+    #[tokio::test]
+    async fn replayed_session_of_stored_game_increments_times_played() {
+        let db = TestDb::create().await;
+        let base_id = persist_quiz(&db.pool, Uuid::new_v4(), GameVisibility::Public).await;
+        assert!(game_base_exists(&db.pool, base_id).await.unwrap());
+        assert!(!game_base_exists(&db.pool, Uuid::new_v4()).await.unwrap());
+
+        let session = get_quiz_session_by_id(&db.pool, &base_id).await.unwrap();
+        let played_before = session.times_played;
+        let envelope = InteractiveEnvelope {
+            game_key: "sein kveld".into(),
+            host_id: Uuid::new_v4(),
+            game_type: GameType::Quiz,
+            schema_version: ENVELOPE_SCHEMA_VERSION,
+            payload: session.to_json_value().unwrap(),
+        };
+
+        let vault = IdempotencyVault::from_ttl(Duration::from_secs(300));
+        let persisted = persist_session_envelope(&db.pool, &vault, envelope).await;
+        assert_eq!(persisted.unwrap(), None);
+
+        let (game, _) = get_game_by_id(&db.pool, base_id).await.unwrap();
+        assert_eq!(game.times_played, played_before + 1);
+    }
+
+    #[tokio::test]
+    async fn stored_sessions_accumulate_rounds_played() {
+        let db = TestDb::create().await;
+        let base_id = persist_quiz(&db.pool, Uuid::new_v4(), GameVisibility::Public).await;
+        let (before, _) = get_game_by_id(&db.pool, base_id).await.unwrap();
+
+        let vault = IdempotencyVault::from_ttl(Duration::from_secs(300));
+        for (game_key, rounds) in [("fest lyd", 3), ("sen kveld", 5)] {
+            let mut session = get_quiz_session_by_id(&db.pool, &base_id).await.unwrap();
+            session.iterations = rounds;
+            let envelope = InteractiveEnvelope {
+                game_key: game_key.into(),
+                host_id: Uuid::new_v4(),
+                game_type: GameType::Quiz,
+                schema_version: ENVELOPE_SCHEMA_VERSION,
+                payload: session.to_json_value().unwrap(),
+            };
+            persist_session_envelope(&db.pool, &vault, envelope)
+                .await
+                .unwrap();
+        }
+
+        let (game, _) = get_game_by_id(&db.pool, base_id).await.unwrap();
+        assert_eq!(game.iterations, 5);
+        assert_eq!(game.total_rounds_played, before.total_rounds_played + 8);
+        assert_eq!(game.times_played, before.times_played + 2);
+    }
+
+    fn quiz_envelope(game_type: GameType, schema_version: u8) -> InteractiveEnvelope {
+        let request = CreateGameRequest {
+            name: "Vorspiel".into(),
+            description: None,
+            category: None,
+            visibility: None,
+        };
+        let mut session = QuizSession::from_create_request(request);
+        session
+            .questions
+            .push(QuizQuestion::from_text("Hvem ler mest?"));
+
+        InteractiveEnvelope {
+            game_key: "fest lyd".into(),
+            host_id: Uuid::new_v4(),
+            game_type,
+            schema_version,
+            payload: session.to_json_value().unwrap(),
+        }
+    }
+
+    fn rejected_field(envelope: InteractiveEnvelope) -> String {
+        match envelope.try_into_session() {
+            Err(ServerError::Validation(errors)) => errors[0].field.clone(),
+            other => panic!("Expected validation error, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn envelope_payload_must_match_declared_game_type() {
+        let envelope = quiz_envelope(GameType::Quiz, ENVELOPE_SCHEMA_VERSION);
+        assert!(matches!(
+            envelope.try_into_session(),
+            Ok(GameSession::Quiz(_))
+        ));
+
+        let envelope = quiz_envelope(GameType::Spin, ENVELOPE_SCHEMA_VERSION);
+        assert_eq!(rejected_field(envelope), "payload");
+    }
+
+    #[test]
+    fn envelope_from_future_schema_version_is_rejected() {
+        let envelope = quiz_envelope(GameType::Quiz, ENVELOPE_SCHEMA_VERSION + 1);
+        assert_eq!(rejected_field(envelope), "schema_version");
+
+        let response = quiz_envelope(GameType::Quiz, ENVELOPE_SCHEMA_VERSION + 1)
+            .try_into_session()
+            .unwrap_err()
+            .into_response();
+        assert_eq!(response.status(), StatusCode::UNPROCESSABLE_ENTITY);
+    }
+
+    #[tokio::test]
+    async fn game_key_is_released_when_session_service_fails() {
+        let db = TestDb::create().await;
+        let vault = KeyVault::load_words(&db.pool).await.unwrap();
+        let client = reqwest::Client::new();
+        let gs_client = GSClient::new("http://127.0.0.1:9/");
+
+        let result = start_with_game_key(&vault, &db.pool, |game_key| async {
+            let envelope = quiz_envelope(GameType::Quiz, ENVELOPE_SCHEMA_VERSION);
+            let envelope = InteractiveEnvelope {
+                game_key,
+                ..envelope
+            };
+            gs_client
+                .create_interactive_game(&client, &envelope)
+                .await?;
+            Ok(())
+        })
+        .await;
+
+        assert!(result.is_err());
+        assert_eq!(vault.active_key_count(), 0);
+
+        let key_word = start_with_game_key(&vault, &db.pool, |_| async { Ok(()) })
+            .await
+            .unwrap();
+        assert_eq!(vault.active_key_count(), 1);
+        vault.release_key(&key_word);
+        assert_eq!(vault.active_key_count(), 0);
+    }
+
+    async fn insert_base_user(pool: &Pool<Postgres>) -> Uuid {
+        sqlx::query_scalar(
+            r#"INSERT INTO "base_user" (username) VALUES ('kari.nordmann') RETURNING id"#,
+        )
+        .fetch_one(pool)
+        .await
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn saved_games_are_filtered_and_ordered_by_saved_at() {
+        let db = TestDb::create().await;
+        let user_id = insert_base_user(&db.pool).await;
+
+        let first = persist_quiz(&db.pool, user_id, GameVisibility::Public).await;
+        let second = persist_quiz(&db.pool, user_id, GameVisibility::Public).await;
+        save_game(&db.pool, user_id, first).await.unwrap();
+        save_game(&db.pool, user_id, second).await.unwrap();
+        sqlx::query(
+            r#"UPDATE "saved_game" SET saved_at = NOW() - INTERVAL '1 day' WHERE base_id = $1"#,
+        )
+        .bind(first)
+        .execute(&db.pool)
+        .await
+        .unwrap();
+
+        let query = |game_type| SavedGamesPageQuery {
+            page_num: 0,
+            game_type,
+            category: Some(GameCategory::Casual.into()),
+            sort: None,
+        };
+
+        let page = get_saved_games_page(&db.pool, user_id, query(Some(GameType::Quiz)))
+            .await
+            .unwrap();
+        let page = serde_json::to_value(page).unwrap();
+        let items = page["items"].as_array().unwrap();
+        let ids: Vec<Uuid> = items
+            .iter()
+            .map(|g| serde_json::from_value(g["id"].clone()).unwrap())
+            .collect();
+        assert_eq!(ids, vec![second, first]);
+        assert!(items[0]["saved_at"].is_string());
+
+        let page = get_saved_games_page(&db.pool, user_id, query(Some(GameType::Spin)))
+            .await
+            .unwrap();
+        let page = serde_json::to_value(page).unwrap();
+        assert!(page["items"].as_array().unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn save_game_rejects_duplicates_and_unknown_games() {
+        let db = TestDb::create().await;
+        let user_id = insert_base_user(&db.pool).await;
+        let game_id = persist_quiz(&db.pool, user_id, GameVisibility::Public).await;
+
+        assert!(!is_game_saved(&db.pool, user_id, game_id).await.unwrap());
+        save_game(&db.pool, user_id, game_id).await.unwrap();
+        assert!(is_game_saved(&db.pool, user_id, game_id).await.unwrap());
+
+        let duplicate = save_game(&db.pool, user_id, game_id).await.unwrap_err();
+        assert_eq!(duplicate.code(), ErrorCode::Conflict);
+
+        let unknown = save_game(&db.pool, user_id, Uuid::new_v4())
+            .await
+            .unwrap_err();
+        assert!(matches!(unknown, ServerError::NotFound(_)));
+    }
+
+    #[tokio::test]
+    async fn delete_saved_game_is_not_found_when_not_saved() {
+        let db = TestDb::create().await;
+        let user_id = insert_base_user(&db.pool).await;
+        let game_id = persist_quiz(&db.pool, user_id, GameVisibility::Public).await;
+
+        let unsaved = delete_saved_game(&db.pool, user_id, game_id)
+            .await
+            .unwrap_err();
+        assert!(matches!(unsaved, ServerError::NotFound(_)));
+
+        save_game(&db.pool, user_id, game_id).await.unwrap();
+        delete_saved_game(&db.pool, user_id, game_id).await.unwrap();
+        assert!(!is_game_saved(&db.pool, user_id, game_id).await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn bulk_save_skips_saved_private_and_unknown_games() {
+        let db = TestDb::create().await;
+        let user_id = insert_base_user(&db.pool).await;
+        let other = Uuid::new_v4();
+
+        let saved = persist_quiz(&db.pool, other, GameVisibility::Public).await;
+        let fresh = persist_quiz(&db.pool, other, GameVisibility::Unlisted).await;
+        let private = persist_quiz(&db.pool, other, GameVisibility::Private).await;
+        save_game(&db.pool, user_id, saved).await.unwrap();
+
+        let ids = [saved, fresh, private, Uuid::new_v4()];
+        let inserted = bulk_save_games(&db.pool, user_id, &ids).await.unwrap();
+        assert_eq!(inserted, 1);
+        assert!(is_game_saved(&db.pool, user_id, fresh).await.unwrap());
+        assert!(!is_game_saved(&db.pool, user_id, private).await.unwrap());
+
+        let removed = bulk_delete_saved_games(&db.pool, user_id, &ids)
+            .await
+            .unwrap();
+        assert_eq!(removed, 2);
+        assert!(!is_game_saved(&db.pool, user_id, saved).await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn saved_games_can_be_sorted_by_times_played() {
+        let db = TestDb::create().await;
+        let user_id = insert_base_user(&db.pool).await;
+
+        let popular = persist_quiz(&db.pool, user_id, GameVisibility::Public).await;
+        let recent = persist_quiz(&db.pool, user_id, GameVisibility::Public).await;
+        save_game(&db.pool, user_id, popular).await.unwrap();
+        save_game(&db.pool, user_id, recent).await.unwrap();
+        sqlx::query(r#"UPDATE "game_base" SET times_played = 10 WHERE id = $1"#)
+            .bind(popular)
+            .execute(&db.pool)
+            .await
+            .unwrap();
+        sqlx::query(
+            r#"UPDATE "saved_game" SET saved_at = NOW() - INTERVAL '1 day' WHERE base_id = $1"#,
+        )
+        .bind(popular)
+        .execute(&db.pool)
+        .await
+        .unwrap();
+
+        let saved_ids = |sort| {
+            let pool = db.pool.clone();
+            async move {
+                let query = SavedGamesPageQuery {
+                    page_num: 0,
+                    game_type: None,
+                    category: None,
+                    sort,
+                };
+                let page = get_saved_games_page(&pool, user_id, query).await.unwrap();
+                let page = serde_json::to_value(page).unwrap();
+                page["items"]
+                    .as_array()
+                    .unwrap()
+                    .iter()
+                    .map(|g| serde_json::from_value(g["id"].clone()).unwrap())
+                    .collect::<Vec<Uuid>>()
+            }
+        };
+
+        assert_eq!(saved_ids(None).await, vec![recent, popular]);
+        assert_eq!(
+            saved_ids(Some(SavedGameSort::TimesPlayed)).await,
+            vec![popular, recent]
+        );
+    }
+
+    #[tokio::test]
+    async fn inactive_games_are_purged_per_game_type_retention() {
+        let db = TestDb::create().await;
+        let game_id = persist_quiz(&db.pool, Uuid::new_v4(), GameVisibility::Public).await;
+        sqlx::query(
+            r#"UPDATE "game_base" SET last_played = NOW() - INTERVAL '30 years' WHERE id = $1"#,
+        )
+        .bind(game_id)
+        .execute(&db.pool)
+        .await
+        .unwrap();
+
+        // Only quizzes fall inside a window short enough to reach the old game
+        let default_days = 365 * 100;
+        let retention = HashMap::from([("quiz".to_string(), 365 * 20)]);
+
+        let counted = delete_non_active_games(&db.pool, &retention, default_days, true)
+            .await
+            .unwrap();
+        assert!(counted[&GameType::Quiz] >= 1);
+        assert_eq!(counted[&GameType::Spin], 0);
+        assert!(get_game_by_id(&db.pool, game_id).await.is_ok());
+
+        let spared = delete_non_active_games(&db.pool, &HashMap::new(), default_days, false)
+            .await
+            .unwrap();
+        assert_eq!(spared[&GameType::Quiz], 0);
+        assert!(get_game_by_id(&db.pool, game_id).await.is_ok());
+
+        let deleted = delete_non_active_games(&db.pool, &retention, default_days, false)
+            .await
+            .unwrap();
+        assert!(deleted[&GameType::Quiz] >= 1);
+        assert!(get_game_by_id(&db.pool, game_id).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn game_page_endpoint_serves_seeded_games() {
+        let app = TestApp::spawn().await;
+        let owner = seed_users(&app.db.pool, 1).await[0];
+        let seeded = seed_games(&app.db.pool, owner, 3).await;
+
+        // The mock data migration also adds games, so put ours on top
+        sqlx::query(r#"UPDATE "game_base" SET times_played = 1000000 WHERE id = ANY($1)"#)
+            .bind(&seeded)
+            .execute(&app.db.pool)
+            .await
+            .unwrap();
+
+        let query = GamePageQuery {
+            page_num: 0,
+            game_type: GameType::Quiz,
+            category: None,
+            trending: false,
+        };
+        let response = app
+            .client
+            .post(app.url("/games/general/page"))
+            .header("X-Guest-Authentication", Uuid::new_v4().to_string())
+            .json(&query)
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let page: serde_json::Value = response.json().await.unwrap();
+        let ids: Vec<Uuid> = page["items"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|game| serde_json::from_value(game["id"].clone()).unwrap())
+            .collect();
+
+        let top = &ids[..seeded.len()];
+        assert!(seeded.iter().all(|id| top.contains(id)));
+    }
+
+    #[tokio::test]
+    async fn game_type_column_values_and_table_names_match_schema() {
+        let db = TestDb::create().await;
+        let owner = seed_users(&db.pool, 1).await[0];
+        let game_id = seed_games(&db.pool, owner, 1).await[0];
+
+        let stored: String =
+            sqlx::query_scalar(r#"SELECT game_type::TEXT FROM "game_base" WHERE id = $1"#)
+                .bind(game_id)
+                .fetch_one(&db.pool)
+                .await
+                .unwrap();
+        assert_eq!(stored, GameType::Quiz.column_value());
+
+        for game_type in GameType::ALL {
+            let exists: bool = sqlx::query_scalar("SELECT to_regclass($1) IS NOT NULL")
+                .bind(game_type.table_name())
+                .fetch_one(&db.pool)
+                .await
+                .unwrap();
+            assert!(exists, "No table {}", game_type.table_name());
+        }
+    }
+}
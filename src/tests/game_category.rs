@@ -0,0 +1,124 @@
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use axum::http::StatusCode;
+    use sqlx::types::Json;
+    use uuid::Uuid;
+
+    use crate::{
+        models::{
+            error::ErrorCode,
+            game_base::{GameCategory, GamePageQuery, GameType, Validate},
+            game_category::{Category, CategoryManager, CategorySlug},
+        },
+        tests::support::{TestApp, TestDb, seed_games, seed_users},
+    };
+
+    fn seasonal(slug: &str) -> Category {
+        Category {
+            slug: CategorySlug::new(slug),
+            display_name: Json(HashMap::from([
+                ("nb".to_string(), "Julebord".to_string()),
+                ("en".to_string(), "Christmas party".to_string()),
+            ])),
+            sort_order: 10,
+            active: true,
+        }
+    }
+
+    #[test]
+    fn old_enum_names_map_to_slugs() {
+        let slug: CategorySlug = serde_json::from_str(r#""Casual""#).unwrap();
+        assert_eq!(slug, GameCategory::Casual.into());
+        assert_eq!(serde_json::to_string(&slug).unwrap(), r#""casual""#);
+    }
+
+    #[test]
+    fn category_needs_valid_slug_and_display_name() {
+        let mut category = seasonal("jul 2026");
+        category.display_name.insert("EN".into(), " ".into());
+
+        let fields: Vec<String> = category
+            .field_errors()
+            .into_iter()
+            .map(|e| e.field)
+            .collect();
+        assert_eq!(fields, vec!["slug", "display_name"]);
+    }
+
+    #[tokio::test]
+    async fn seasonal_category_lifecycle() {
+        let db = TestDb::create().await;
+        let manager = CategoryManager::load(&db.pool).await.unwrap();
+
+        let seeded = manager.slugs().await;
+        assert_eq!(seeded.len(), 5);
+        assert_eq!(seeded[0], GameCategory::Casual.into());
+
+        let slug = manager.update(seasonal("jul")).await.unwrap().slug;
+        assert!(manager.require_active(&slug).await.is_ok());
+
+        let owner = seed_users(&db.pool, 1).await[0];
+        let game_id = seed_games(&db.pool, owner, 1).await[0];
+        sqlx::query(r#"UPDATE "game_base" SET category = $1 WHERE id = $2"#)
+            .bind(&slug)
+            .bind(game_id)
+            .execute(&db.pool)
+            .await
+            .unwrap();
+
+        let error = manager.remove(&slug).await.unwrap_err();
+        assert_eq!(error.code(), ErrorCode::Conflict);
+
+        let mut inactive = seasonal("jul");
+        inactive.active = false;
+        manager.update(inactive).await.unwrap();
+        assert!(!manager.list(false).await.iter().any(|c| c.slug == slug));
+        assert!(manager.list(true).await.iter().any(|c| c.slug == slug));
+        assert!(manager.require_known(&slug).await.is_ok());
+        assert!(manager.require_active(&slug).await.is_err());
+
+        let unused = manager.update(seasonal("paske")).await.unwrap().slug;
+        manager.remove(&unused).await.unwrap();
+        let restarted = CategoryManager::load(&db.pool).await.unwrap();
+        assert!(restarted.require_known(&unused).await.is_err());
+        assert!(restarted.require_known(&slug).await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn game_page_rejects_unknown_category() {
+        let app = TestApp::spawn().await;
+        let page = |category: &str| {
+            let query = GamePageQuery {
+                page_num: 0,
+                game_type: GameType::Quiz,
+                category: Some(CategorySlug::new(category)),
+                trending: false,
+            };
+            app.client
+                .post(app.url("/games/general/page"))
+                .header("X-Guest-Authentication", Uuid::new_v4().to_string())
+                .json(&query)
+                .send()
+        };
+
+        assert_eq!(page("casual").await.unwrap().status(), StatusCode::OK);
+        assert_eq!(
+            page("halloween").await.unwrap().status(),
+            StatusCode::UNPROCESSABLE_ENTITY
+        );
+
+        let categories: Vec<Category> = app
+            .client
+            .get(app.url("/games/general/categories"))
+            .header("X-Guest-Authentication", Uuid::new_v4().to_string())
+            .send()
+            .await
+            .unwrap()
+            .json()
+            .await
+            .unwrap();
+        assert_eq!(categories.len(), 5);
+    }
+}
@@ -0,0 +1,100 @@
+#[cfg(test)]
+mod tests {
+    use sqlx::{Pool, Postgres};
+    use uuid::Uuid;
+
+    use crate::{
+        config::config::CONFIG,
+        db::{
+            game_report::{create_game_report, moderate_game},
+            quiz_game::tx_persist_quiz_session,
+        },
+        models::{
+            error::ServerError,
+            game_base::{CreateGameRequest, GameType},
+            game_report::{CreateReportRequest, ModerateAction, ReportOutcome, ReportReason},
+            quiz_game::{QuizQuestion, QuizSession},
+        },
+        tests::support::TestDb,
+    };
+
+    async fn persisted_quiz(pool: &Pool<Postgres>) -> Uuid {
+        let request = CreateGameRequest {
+            name: "Vorspiel".into(),
+            description: None,
+            category: None,
+            visibility: None,
+        };
+        let mut session = QuizSession::from_create_request(request);
+        session
+            .questions
+            .push(QuizQuestion::from_text("Hvem ler mest?"));
+
+        let mut tx = pool.begin().await.unwrap();
+        tx_persist_quiz_session(&mut tx, &session, Uuid::new_v4())
+            .await
+            .unwrap();
+        tx.commit().await.unwrap();
+        session.base_id
+    }
+
+    async fn is_hidden(pool: &Pool<Postgres>, base_id: Uuid) -> bool {
+        sqlx::query_scalar::<_, bool>(r#"SELECT hidden FROM "game_base" WHERE id = $1"#)
+            .bind(base_id)
+            .fetch_one(pool)
+            .await
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn reports_are_deduplicated_and_hide_at_threshold() {
+        let db = TestDb::create().await;
+        let base_id = persisted_quiz(&db.pool).await;
+        let request = CreateReportRequest {
+            reason: ReportReason::Spam,
+            description: None,
+        };
+
+        let reporter = Uuid::new_v4();
+        let outcome = create_game_report(&db.pool, base_id, reporter, &request)
+            .await
+            .unwrap();
+        assert_eq!(outcome, ReportOutcome::Created);
+        let outcome = create_game_report(&db.pool, base_id, reporter, &request)
+            .await
+            .unwrap();
+        assert_eq!(outcome, ReportOutcome::Duplicate);
+
+        let threshold = CONFIG.moderation.report_threshold;
+        let mut last = ReportOutcome::Created;
+        for _ in 1..threshold {
+            assert!(!is_hidden(&db.pool, base_id).await);
+            last = create_game_report(&db.pool, base_id, Uuid::new_v4(), &request)
+                .await
+                .unwrap();
+        }
+        assert_eq!(last, ReportOutcome::Hidden(GameType::Quiz));
+        assert!(is_hidden(&db.pool, base_id).await);
+
+        let (game_type, resolved) = moderate_game(&db.pool, base_id, ModerateAction::Clear)
+            .await
+            .unwrap();
+        assert_eq!(game_type, GameType::Quiz);
+        assert_eq!(resolved, threshold as u64);
+        assert!(!is_hidden(&db.pool, base_id).await);
+    }
+
+    #[tokio::test]
+    async fn reporting_missing_game_is_not_found() {
+        let db = TestDb::create().await;
+        let request = CreateReportRequest {
+            reason: ReportReason::Other,
+            description: Some("Finnes ikke".into()),
+        };
+
+        let error = create_game_report(&db.pool, Uuid::new_v4(), Uuid::new_v4(), &request)
+            .await
+            .unwrap_err();
+        assert!(matches!(error, ServerError::NotFound(_)));
+    }
+}
@@ -0,0 +1,78 @@
+#[cfg(test)]
+mod tests {
+    use axum::http::StatusCode;
+    use uuid::Uuid;
+
+    use crate::{
+        config::config::GuestConfig,
+        models::error::ServerError,
+        service::guest_token::{
+            GUEST_TOKEN_HEADER, guest_token_headers, sign_guest_id, verify_guest_token,
+        },
+    };
+
+    static KEY: &str = "guest-test-key";
+
+    fn config(enforce_signature: bool) -> GuestConfig {
+        GuestConfig {
+            signing_key: Some(KEY.into()),
+            enforce_signature,
+        }
+    }
+
+    fn rejected_status(result: Result<Uuid, ServerError>) -> StatusCode {
+        match result {
+            Err(ServerError::Api(status, _)) => status,
+            other => panic!("Expected api error, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn malformed_guest_id_is_bad_request() {
+        for header in ["", "not-a-uuid", "1234.abcd"] {
+            let status = rejected_status(verify_guest_token(header, &config(false)));
+            assert_eq!(status, StatusCode::BAD_REQUEST, "{}", header);
+        }
+    }
+
+    #[test]
+    fn unsigned_guest_id_is_accepted_until_enforced() {
+        let pseudo_id = Uuid::new_v4();
+        let header = pseudo_id.to_string();
+
+        let accepted = verify_guest_token(&header, &config(false)).unwrap();
+        assert_eq!(accepted, pseudo_id);
+
+        let status = rejected_status(verify_guest_token(&header, &config(true)));
+        assert_eq!(status, StatusCode::UNAUTHORIZED);
+    }
+
+    #[test]
+    fn signed_guest_token_is_verified() {
+        let pseudo_id = Uuid::new_v4();
+        let token = sign_guest_id(pseudo_id, KEY).unwrap();
+
+        let accepted = verify_guest_token(&token, &config(true)).unwrap();
+        assert_eq!(accepted, pseudo_id);
+
+        let other_key = sign_guest_id(pseudo_id, "other-key").unwrap();
+        let other_id = format!("{}.{}", Uuid::new_v4(), token.split_once('.').unwrap().1);
+        let not_hex = format!("{}.zz", pseudo_id);
+        for header in [other_key, other_id, not_hex] {
+            let status = rejected_status(verify_guest_token(&header, &config(false)));
+            assert_eq!(status, StatusCode::UNAUTHORIZED, "{}", header);
+        }
+    }
+
+    #[test]
+    fn token_header_is_only_sent_with_signing_key() {
+        let pseudo_id = Uuid::new_v4();
+
+        let headers = guest_token_headers(pseudo_id, &config(false)).unwrap();
+        let token = headers.get(GUEST_TOKEN_HEADER).unwrap().to_str().unwrap();
+        assert_eq!(verify_guest_token(token, &config(true)).unwrap(), pseudo_id);
+
+        let headers = guest_token_headers(pseudo_id, &GuestConfig::default()).unwrap();
+        assert!(headers.is_empty());
+    }
+}
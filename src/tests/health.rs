@@ -0,0 +1,47 @@
+#[cfg(test)]
+mod tests {
+    use axum::http::StatusCode;
+    use serde_json::Value;
+
+    use crate::{
+        db::health::pending_migrations,
+        tests::support::{TestApp, TestDb},
+    };
+
+    #[tokio::test]
+    async fn migrated_database_has_nothing_pending() {
+        let db = TestDb::create().await;
+        assert!(pending_migrations(&db.pool).await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn unapplied_migration_fails_the_readiness_probe() {
+        let app = TestApp::spawn().await;
+
+        // Makes the latest migration look like it was never run
+        let version: i64 = sqlx::query_scalar(
+            r#"DELETE FROM "_sqlx_migrations"
+            WHERE version = (SELECT MAX(version) FROM "_sqlx_migrations")
+            RETURNING version"#,
+        )
+        .fetch_one(&app.db.pool)
+        .await
+        .unwrap();
+
+        let pending = pending_migrations(&app.db.pool).await.unwrap();
+        assert_eq!(pending.len(), 1);
+        assert_eq!(pending[0].version, version);
+
+        let response = app
+            .client
+            .get(app.url("/health/migrations"))
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::SERVICE_UNAVAILABLE);
+
+        let body: Value = response.json().await.unwrap();
+        assert_eq!(body["migrations_pending"], true);
+        assert_eq!(body["pending_migrations"][0]["version"], version);
+    }
+}
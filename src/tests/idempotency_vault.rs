@@ -0,0 +1,27 @@
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use crate::service::idempotency_vault::IdempotencyVault;
+
+    #[test]
+    fn repeated_key_is_detected_until_removed() {
+        let vault = IdempotencyVault::from_ttl(Duration::from_secs(300));
+
+        assert!(!vault.check_and_insert("auth0:req-1"));
+        assert!(vault.check_and_insert("auth0:req-1"));
+        assert!(!vault.check_and_insert("auth0:req-2"));
+
+        vault.remove("auth0:req-1");
+        assert!(!vault.check_and_insert("auth0:req-1"));
+    }
+
+    #[test]
+    fn expired_keys_are_pruned_and_accepted_again() {
+        let vault = IdempotencyVault::from_ttl(Duration::ZERO);
+
+        assert!(!vault.check_and_insert("auth0:req-1"));
+        assert!(!vault.check_and_insert("auth0:req-1"));
+        assert_eq!(vault.prune(), 1);
+    }
+}
@@ -0,0 +1,106 @@
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use sqlx::Row;
+    use uuid::Uuid;
+
+    use crate::{
+        db::integration::{create_integration, delete_integration},
+        models::{
+            game_base::Validate,
+            integration::{
+                CreateIntegrationRequest, IntegrationName, IntegrationRegistry, swap_integrations,
+            },
+        },
+        tests::support::TestDb,
+    };
+
+    #[tokio::test]
+    async fn new_integration_subject_is_recognized_after_reload() {
+        let db = TestDb::create().await;
+        let registry = IntegrationRegistry::load(&db.pool).await.unwrap();
+        let subject = format!("{}@clients", &Uuid::new_v4().simple().to_string()[..20]);
+        assert_eq!(registry.name_for_subject(&subject), None);
+
+        let integration = create_integration(
+            &db.pool,
+            &subject,
+            &IntegrationName::from(IntegrationName::STRIPE),
+            &None,
+        )
+        .await
+        .unwrap();
+        assert_eq!(registry.name_for_subject(&subject), None);
+
+        registry.reload(&db.pool).await.unwrap();
+        assert_eq!(
+            registry.name_for_subject(&subject),
+            Some(IntegrationName::from(IntegrationName::STRIPE))
+        );
+
+        delete_integration(&db.pool, integration.id).await.unwrap();
+        registry.reload(&db.pool).await.unwrap();
+        assert_eq!(registry.name_for_subject(&subject), None);
+    }
+
+    #[tokio::test]
+    async fn known_and_custom_integration_names_round_trip() {
+        let db = TestDb::create().await;
+        let names = [
+            IntegrationName::AUTH0,
+            IntegrationName::SESSION,
+            IntegrationName::STRIPE,
+            "partner_api",
+        ];
+
+        for name in names {
+            let subject = format!("{}@clients", &Uuid::new_v4().simple().to_string()[..20]);
+            let created =
+                create_integration(&db.pool, &subject, &IntegrationName::from(name), &None)
+                    .await
+                    .unwrap();
+            assert_eq!(created.name, name);
+
+            let stored: String = sqlx::query(r#"SELECT name FROM "integration" WHERE id = $1"#)
+                .bind(created.id)
+                .fetch_one(&db.pool)
+                .await
+                .unwrap()
+                .get("name");
+            assert_eq!(stored, name);
+
+            delete_integration(&db.pool, created.id).await.unwrap();
+        }
+    }
+
+    #[tokio::test]
+    async fn malformed_integration_names_are_rejected() {
+        let db = TestDb::create().await;
+        let request = CreateIntegrationRequest {
+            subject: "malformed@clients".into(),
+            name: IntegrationName::from("Not Valid"),
+            url: None,
+        };
+        assert!(request.validate().is_err());
+
+        let result = create_integration(&db.pool, &request.subject, &request.name, &None).await;
+        assert!(result.is_err());
+    }
+
+    #[tokio::test]
+    #[allow(deprecated)]
+    async fn swapped_integration_maps_stay_consistent() {
+        use crate::models::integration::{INTEGRATION_IDS, INTEGRATION_NAMES};
+
+        let name = IntegrationName::new("swap_test");
+        let names = HashMap::from([("swap@clients".to_string(), name.clone())]);
+        let ids = HashMap::from([(name, Uuid::new_v4())]);
+        swap_integrations(names, ids).await;
+
+        // Other tests reload the maps too, so only check that they agree
+        let names = INTEGRATION_NAMES.read().await;
+        let ids = INTEGRATION_IDS.read().await;
+        assert!(names.values().all(|name| ids.contains_key(name)));
+    }
+}
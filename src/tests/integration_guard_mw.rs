@@ -0,0 +1,98 @@
+#[cfg(test)]
+mod tests {
+    use axum::{
+        Router,
+        body::Body,
+        extract::Request,
+        http::StatusCode,
+        middleware::{Next, from_fn, from_fn_with_state},
+        response::Response,
+        routing::post,
+    };
+    use sqlx::{Pool, Postgres};
+    use tower::ServiceExt;
+    use uuid::Uuid;
+
+    use crate::{
+        api::integration_guard_mw::{IntegrationGuard, integration_guard_mw},
+        models::{integration::IntegrationName, user::SubjectId},
+        tests::support::TestDb,
+    };
+
+    static SUBJECT_HEADER: &str = "x-test-subject";
+
+    /// Stands in for auth_mw/webhook_mw, which resolve the subject from a token
+    async fn fake_subject_mw(mut req: Request<Body>, next: Next) -> Response {
+        let subject = match req.headers().get(SUBJECT_HEADER) {
+            Some(name) => SubjectId::Integration(IntegrationName::from(name.to_str().unwrap())),
+            None => SubjectId::BaseUser(Uuid::new_v4()),
+        };
+        req.extensions_mut().insert(subject);
+        next.run(req).await
+    }
+
+    fn guarded_group(pool: &Pool<Postgres>, allowed: &'static [&'static str]) -> Router {
+        Router::new()
+            .route("/", post(|| async { StatusCode::OK }))
+            .layer(from_fn_with_state(
+                IntegrationGuard::new(pool, allowed),
+                integration_guard_mw,
+            ))
+            .layer(from_fn(fake_subject_mw))
+    }
+
+    async fn status(router: &Router, subject: Option<&str>) -> StatusCode {
+        let mut req = Request::post("/");
+        if let Some(subject) = subject {
+            req = req.header(SUBJECT_HEADER, subject);
+        }
+
+        router
+            .clone()
+            .oneshot(req.body(Body::empty()).unwrap())
+            .await
+            .unwrap()
+            .status()
+    }
+
+    #[tokio::test]
+    async fn integrations_only_reach_their_own_route_groups() {
+        let db = TestDb::create().await;
+        let groups = [
+            (
+                IntegrationName::AUTH0,
+                guarded_group(&db.pool, &[IntegrationName::AUTH0]),
+            ),
+            (
+                IntegrationName::SESSION,
+                guarded_group(&db.pool, &[IntegrationName::SESSION]),
+            ),
+            (
+                IntegrationName::STRIPE,
+                guarded_group(&db.pool, &[IntegrationName::STRIPE]),
+            ),
+        ];
+        let callers = [
+            IntegrationName::AUTH0,
+            IntegrationName::SESSION,
+            IntegrationName::STRIPE,
+            "partner_api",
+        ];
+
+        for (owner, router) in &groups {
+            for caller in callers {
+                let expected = match caller == *owner {
+                    true => StatusCode::OK,
+                    false => StatusCode::FORBIDDEN,
+                };
+                assert_eq!(
+                    status(router, Some(caller)).await,
+                    expected,
+                    "{caller} -> {owner}"
+                );
+            }
+
+            assert_eq!(status(router, None).await, StatusCode::FORBIDDEN);
+        }
+    }
+}
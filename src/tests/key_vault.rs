@@ -1,111 +1,141 @@
 #[cfg(test)]
 mod tests {
-    use std::{env, sync::Arc};
+    use std::{collections::HashSet, sync::Arc};
 
-    use dotenv::dotenv;
     use tracing::level_filters::LevelFilter;
 
-    use crate::{models::app_state::AppState, service::key_vault::KeyVaultError};
+    use crate::{
+        service::key_vault::{KeyVault, KeyVaultError, parse_game_key},
+        tests::support::{TestDb, seed_words},
+    };
 
     fn setup_logging() {
-        tracing_subscriber::FmtSubscriber::builder()
+        let _ = tracing_subscriber::FmtSubscriber::builder()
             .with_max_level(LevelFilter::DEBUG)
             .with_test_writer()
-            .try_init()
-            .unwrap();
-    }
-
-    async fn setup_app_state() -> Arc<AppState> {
-        dotenv().ok();
-        let connection_string =
-            env::var("TERO__DATABASE_URL").expect("Failed to obtain connection string");
-        let state = AppState::from_connection_string(&connection_string)
-            .await
-            .unwrap();
-        state
+            .try_init();
     }
 
     #[tokio::test]
     async fn max_limit_keys() {
         setup_logging();
-        let state = setup_app_state().await;
-        let vault = state.get_vault();
+        let db = TestDb::create().await;
+        seed_words(&db.pool, &["blå", "rask", "glad"], &["ulv", "elg"]).await;
+        let vault = KeyVault::load_words(&db.pool).await.unwrap();
 
-        for num in 0..10_000 {
-            let word = vault.create_key(state.get_pool()).unwrap();
+        for num in 0..6 {
+            let word = vault.create_key(&db.pool).unwrap();
             println!("{} - {}", num + 1, word)
         }
 
-        let result = vault.create_key(state.get_pool());
-        assert!(result.is_err());
-
-        let error = result.err().unwrap();
-        match error {
-            KeyVaultError::FullCapasity => assert!(true),
-            _ => assert!(false, "Failed with: {}", error.to_string()),
-        }
+        let result = vault.create_key(&db.pool);
+        assert!(matches!(result, Err(KeyVaultError::FullCapasity)));
     }
 
     #[tokio::test]
     async fn test_concurrent_key_creation() {
         setup_logging();
-        let state = setup_app_state().await;
+        let db = TestDb::create().await;
+        let prefixes = ["a", "b", "c", "d", "e", "f", "g", "h", "i", "j"];
+        let suffixes = ["k", "l", "m", "n", "o", "p", "q", "r", "s", "t"];
+        seed_words(&db.pool, &prefixes, &suffixes).await;
+        let vault = Arc::new(KeyVault::load_words(&db.pool).await.unwrap());
 
         let mut handles = Vec::new();
-
-        for i in 0..10001 {
-            let state_clone = Arc::clone(&state);
-
-            let handle = tokio::spawn(async move {
-                let vault = state_clone.get_vault();
-                match vault.create_key(state_clone.get_pool()) {
-                    Ok(key) => {
-                        println!("Task {} opprettet nøkkel: {}", i, key);
-                        Ok(key)
-                    }
-                    Err(e) => {
-                        println!("Task {} feilet: {:?}", i, e);
-                        Err(e)
-                    }
-                }
-            });
-
-            handles.push(handle);
+        for _ in 0..101 {
+            let vault = Arc::clone(&vault);
+            let pool = db.pool.clone();
+            handles.push(tokio::spawn(async move { vault.create_key(&pool) }));
         }
 
-        // Await alle tasks samtidig
         let results = futures::future::join_all(handles).await;
 
-        // Analyser resultater
         let mut successful_keys: Vec<String> = Vec::new();
         let mut failed_count = 0;
-
         for result in results {
             match result.unwrap() {
-                // unwrap the JoinResult
                 Ok(key) => successful_keys.push(key),
                 Err(_) => failed_count += 1,
             }
         }
 
-        println!("Vellykkede nøkler: {}", successful_keys.len());
-        println!("Feilede forsøk: {}", failed_count);
-
-        // Sjekk at alle nøkler er unike
-        let mut unique_keys = std::collections::HashSet::new();
-        for key in &successful_keys {
-            assert!(
-                unique_keys.insert(key.clone()),
-                "Duplikat nøkkel funnet: {}",
-                key
-            );
-        }
+        let unique_keys: HashSet<&String> = successful_keys.iter().collect();
+        assert_eq!(successful_keys.len(), 100);
+        assert_eq!(unique_keys.len(), 100, "Duplicate keys handed out");
+        assert_eq!(failed_count, 1);
+    }
+
+    #[tokio::test]
+    async fn utilisation_tracks_handed_out_keys() {
+        let db = TestDb::create().await;
+        let vault = KeyVault::load_words(&db.pool).await.unwrap();
+
+        let before = vault.stats();
+        assert_eq!(before.active_keys, 0);
+        assert!(before.capacity > 0);
+
+        let key = vault.create_key(&db.pool).unwrap();
+        let during = vault.stats();
+        assert_eq!(during.active_keys, 1);
+        assert_eq!(during.capacity, before.capacity);
+        assert!((vault.utilisation() - 1.0 / during.capacity as f32).abs() < f32::EPSILON);
+        assert!((during.utilisation_pct - 100.0 / during.capacity as f32).abs() < 1e-4);
+
+        vault.release_key(&key);
+        assert_eq!(vault.stats().active_keys, 0);
+    }
 
-        assert!(successful_keys.len() > 0, "Ingen nøkler ble opprettet");
+    #[test]
+    fn from_words_rejects_empty_lists() {
+        let words = || vec!["glad".to_string()];
+
+        assert!(matches!(
+            KeyVault::from_words(Vec::new(), words()),
+            Err(KeyVaultError::EmptyWords)
+        ));
+        assert!(matches!(
+            KeyVault::from_words(words(), Vec::new()),
+            Err(KeyVaultError::EmptyWords)
+        ));
+    }
+
+    #[tokio::test]
+    async fn from_words_hands_out_every_combination() {
+        let db = TestDb::create().await;
+        let vault =
+            KeyVault::from_words(vec!["glad".into(), "rask".into()], vec!["ulv".into()]).unwrap();
+
+        let mut keys = vec![
+            vault.create_key(&db.pool).unwrap(),
+            vault.create_key(&db.pool).unwrap(),
+        ];
+        keys.sort();
+
+        assert_eq!(keys, vec!["glad ulv", "rask ulv"]);
+        assert!(matches!(
+            vault.create_key(&db.pool),
+            Err(KeyVaultError::FullCapasity)
+        ));
+    }
+
+    #[tokio::test]
+    async fn empty_word_tables_fall_back_to_built_in_words() {
+        let db = TestDb::create().await;
+        seed_words(&db.pool, &[], &[]).await;
+
+        let vault = KeyVault::load_words(&db.pool).await.unwrap();
+        assert_eq!(vault.stats().capacity, 100);
+    }
+
+    #[test]
+    fn game_key_needs_two_non_empty_words() {
         assert_eq!(
-            successful_keys.len(),
-            unique_keys.len(),
-            "Duplikate nøkler oppdaget"
+            parse_game_key("fest lyd").unwrap(),
+            ("fest".to_string(), "lyd".to_string())
         );
+
+        for key_word in ["", "fest", "fest ", " lyd", "fest  lyd", "fest lyd kveld"] {
+            assert!(parse_game_key(key_word).is_err(), "{:?}", key_word);
+        }
     }
 }
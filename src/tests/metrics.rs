@@ -0,0 +1,70 @@
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use crate::service::metrics::{Metrics, MetricsSnapshot};
+
+    #[test]
+    fn render_outputs_prometheus_text_format() {
+        let metrics = Metrics::default();
+        metrics.record_request("GET", "/games/general/page", 200);
+        metrics.record_request("GET", "/games/general/page", 200);
+        metrics.record_request("DELETE", "/games/general/{game_type}/{game_id}", 404);
+        metrics.set_pseudo_user_count(42);
+
+        let snapshot = MetricsSnapshot {
+            active_game_keys: 3,
+            page_cache_hits: 7,
+        };
+        let output = metrics.render(&snapshot);
+        let lines: Vec<&str> = output.lines().collect();
+
+        assert!(lines.contains(&"# TYPE tero_active_game_keys gauge"));
+        assert!(lines.contains(&"tero_active_game_keys 3"));
+        assert!(lines.contains(&"tero_game_page_cache_hits_total 7"));
+        assert!(lines.contains(&"tero_pseudo_user_count 42"));
+        assert!(lines.contains(
+            &r#"tero_http_requests_total{method="GET",route="/games/general/page",status="200"} 2"#
+        ));
+        assert!(lines.contains(
+            &r#"tero_http_requests_total{method="DELETE",route="/games/general/{game_type}/{game_id}",status="404"} 1"#
+        ));
+    }
+
+    #[test]
+    fn latency_histogram_buckets_are_cumulative() {
+        let metrics = Metrics::default();
+        metrics.observe_latency("/games/general/page", 200, Duration::from_millis(3));
+        metrics.observe_latency("/games/general/page", 200, Duration::from_millis(40));
+        metrics.observe_latency("/games/general/page", 200, Duration::from_secs(5));
+        metrics.observe_latency("unknown", 404, Duration::from_millis(1));
+
+        let snapshot = MetricsSnapshot {
+            active_game_keys: 0,
+            page_cache_hits: 0,
+        };
+        let output = metrics.render(&snapshot);
+        let lines: Vec<&str> = output.lines().collect();
+        let labels = r#"route="/games/general/page",status_code="200""#;
+
+        for (le, count) in [
+            ("0.005", 1),
+            ("0.025", 1),
+            ("0.05", 2),
+            ("2.5", 2),
+            ("+Inf", 3),
+        ] {
+            let line = format!(
+                "tero_http_request_duration_seconds_bucket{{{},le=\"{}\"}} {}",
+                labels, le, count
+            );
+            assert!(lines.contains(&line.as_str()), "missing {}", line);
+        }
+        assert!(lines.contains(
+            &format!("tero_http_request_duration_seconds_count{{{}}} 3", labels).as_str()
+        ));
+        assert!(lines.contains(
+            &r#"tero_http_request_duration_seconds_count{route="unknown",status_code="404"} 1"#
+        ));
+    }
+}
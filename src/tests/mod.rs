@@ -1 +1,37 @@
+pub mod audit;
+pub mod auth;
+pub mod auth_mw;
+pub mod cache;
+pub mod config;
+pub mod cors_mw;
+pub mod db_query_builder;
+pub mod error;
+pub mod feature_flags;
+pub mod game_base;
+pub mod game_category;
+pub mod game_report;
+pub mod guest_token;
+pub mod health;
+pub mod idempotency_vault;
+pub mod integration;
+pub mod integration_guard_mw;
 pub mod key_vault;
+pub mod metrics;
+pub mod openapi;
+pub mod payload_mw;
+pub mod pool;
+pub mod popup_manager;
+pub mod quiz_game;
+pub mod request_context;
+pub mod self_check;
+pub mod spin_game;
+#[cfg(test)]
+pub mod support;
+pub mod system_log;
+pub mod token_cache;
+pub mod trace_context;
+pub mod user;
+pub mod user_sync;
+pub mod util;
+pub mod webhook_mw;
+pub mod word_pool;
@@ -0,0 +1,226 @@
+#[cfg(test)]
+mod tests {
+    use std::{
+        collections::{BTreeSet, HashMap},
+        fs,
+        path::Path,
+    };
+
+    use serde_json::Value;
+    use utoipa::OpenApi;
+
+    use crate::api::openapi::ApiDoc;
+
+    static METHODS: [&str; 5] = ["get", "post", "put", "patch", "delete"];
+
+    /// Serves the document itself, not part of the api it describes
+    static DOC_SOURCE: &str = "openapi.rs";
+
+    fn spec() -> Value {
+        serde_json::to_value(ApiDoc::openapi()).unwrap()
+    }
+
+    /// Name of the router a call at `pos` is chained on, the `let` binding
+    /// it is assigned to or else the function returning it
+    fn router_name(source: &str, pos: usize) -> String {
+        let chain = source[..pos].rfind("Router::new()").unwrap();
+        let before = source[..chain].trim_end();
+        if let Some(binding) = before.strip_suffix('=') {
+            let binding = binding.trim_end();
+            if let Some(let_pos) = binding.rfind("let ") {
+                return binding[let_pos + 4..].trim().to_string();
+            }
+        }
+
+        let fn_pos = before.rfind("fn ").unwrap();
+        before[fn_pos + 3..].split('(').next().unwrap().to_string()
+    }
+
+    /// Text from `start` up to the paren closing the call opened there
+    fn call_at(source: &str, start: usize) -> &str {
+        let mut depth = 0;
+        let end = source[start..]
+            .char_indices()
+            .find_map(|(idx, c)| {
+                match c {
+                    '(' => depth += 1,
+                    ')' if depth == 1 => return Some(start + idx),
+                    ')' => depth -= 1,
+                    _ => {}
+                }
+                None
+            })
+            .unwrap();
+        &source[start..end]
+    }
+
+    /// The path of a route call and the methods it registers
+    fn parse_route(call: &str) -> (String, Vec<&'static str>) {
+        let path = call.split('"').nth(1).unwrap().to_string();
+        let methods = METHODS
+            .into_iter()
+            .filter(|method| {
+                call.match_indices(&format!("{method}(")).any(|(idx, _)| {
+                    call[..idx]
+                        .chars()
+                        .last()
+                        .is_none_or(|c| !c.is_alphanumeric() && c != '_')
+                })
+            })
+            .collect();
+
+        (path, methods)
+    }
+
+    /// Every method and full path registered with `.route(..)`, resolved
+    /// through the `.nest(..)` and `.merge(..)` calls mounting its router
+    fn registered_routes() -> BTreeSet<(String, String)> {
+        let root = Path::new(env!("CARGO_MANIFEST_DIR")).join("src");
+        let mut sources = vec![root.join("main.rs")];
+        for entry in fs::read_dir(root.join("api")).unwrap() {
+            let path = entry.unwrap().path();
+            if path.extension().is_some_and(|ext| ext == "rs") && !path.ends_with(DOC_SOURCE) {
+                sources.push(path);
+            }
+        }
+
+        let mut routes = Vec::new();
+        let mut mounts = HashMap::new();
+        for path in sources {
+            let source = fs::read_to_string(&path).unwrap();
+            for (start, _) in source.match_indices(".route(") {
+                routes.push((
+                    router_name(&source, start),
+                    call_at(&source, start).to_string(),
+                ));
+            }
+
+            for (start, _) in source.match_indices(".nest(") {
+                let call = call_at(&source, start);
+                let prefix = call.split('"').nth(1).unwrap().to_string();
+                let mounted = call.split(", ").nth(1).unwrap().split('(').next().unwrap();
+                mounts.insert(mounted.to_string(), (prefix, router_name(&source, start)));
+            }
+
+            for (start, _) in source.match_indices(".merge(") {
+                let call = call_at(&source, start);
+                let mounted = call[".merge(".len()..].split('(').next().unwrap();
+                mounts.insert(
+                    mounted.to_string(),
+                    (String::new(), router_name(&source, start)),
+                );
+            }
+        }
+
+        fn full_prefix(name: &str, mounts: &HashMap<String, (String, String)>) -> String {
+            match mounts.get(name) {
+                Some((prefix, parent)) => format!("{}{}", full_prefix(parent, mounts), prefix),
+                None => String::new(),
+            }
+        }
+
+        let mut registered = BTreeSet::new();
+        for (name, call) in routes {
+            let prefix = full_prefix(&name, &mounts);
+            let (route, methods) = parse_route(&call);
+            assert!(!methods.is_empty(), "No method found in {}", call);
+
+            let path = match route.as_str() {
+                "/" if !prefix.is_empty() => prefix,
+                _ => format!("{}{}", prefix, route),
+            };
+            for method in methods {
+                registered.insert((method.to_string(), path.clone()));
+            }
+        }
+
+        registered
+    }
+
+    fn documented_routes() -> BTreeSet<(String, String)> {
+        let spec = spec();
+        let mut documented = BTreeSet::new();
+        for (path, item) in spec["paths"].as_object().unwrap() {
+            for method in item.as_object().unwrap().keys() {
+                documented.insert((method.clone(), path.clone()));
+            }
+        }
+
+        documented
+    }
+
+    #[test]
+    fn route_calls_are_parsed() {
+        let (path, methods) =
+            parse_route(".route(\n    \"/{id}\",\n    get(get_game).delete(delete_game)");
+        assert_eq!(path, "/{id}");
+        assert_eq!(methods, vec!["get", "delete"]);
+
+        let (_, methods) = parse_route(".route(\"/page\", post(get_games)");
+        assert_eq!(methods, vec!["post"]);
+    }
+
+    #[test]
+    fn routes_are_resolved_through_nested_routers() {
+        let registered = registered_routes();
+        assert!(
+            registered.len() > 40,
+            "Found only {} routes",
+            registered.len()
+        );
+
+        for route in [
+            ("get", "/health"),
+            ("post", "/games/general/page"),
+            ("post", "/games/session/{game_type}/join/{game_id}"),
+            ("post", "/webhooks/auth0/{pseudo_id}"),
+            ("get", "/metrics"),
+        ] {
+            let route = (route.0.to_string(), route.1.to_string());
+            assert!(registered.contains(&route), "{:?} was not resolved", route);
+        }
+    }
+
+    /// Fails when a handler is added without an annotation, or an annotation
+    /// is left behind after its route is removed or moved
+    #[test]
+    fn spec_documents_exactly_the_registered_routes() {
+        let registered = registered_routes();
+        let documented = documented_routes();
+
+        let undocumented: Vec<_> = registered.difference(&documented).collect();
+        assert!(
+            undocumented.is_empty(),
+            "Not in the spec: {:?}",
+            undocumented
+        );
+
+        let unrouted: Vec<_> = documented.difference(&registered).collect();
+        assert!(unrouted.is_empty(), "Not on the router: {:?}", unrouted);
+    }
+
+    #[test]
+    fn spec_describes_the_shared_models() {
+        let spec = spec();
+        let schemas = spec["components"]["schemas"].as_object().unwrap();
+        for schema in [
+            "GamePageQuery",
+            "InteractiveEnvelope",
+            "GameStartResponse",
+            "GameSessionDescriptor",
+            "BaseUser",
+            "PagedResponse_GamePageItem",
+            "ErrorBody",
+            "ErrorCode",
+        ] {
+            assert!(schemas.contains_key(schema), "{} is missing", schema);
+        }
+
+        let page = &spec["paths"]["/games/general/page"]["post"]["responses"];
+        assert!(page["200"].is_object());
+        assert_eq!(
+            page["default"]["content"]["application/json"]["schema"]["$ref"],
+            "#/components/schemas/ErrorBody"
+        );
+    }
+}
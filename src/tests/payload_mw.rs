@@ -0,0 +1,147 @@
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use axum::{
+        Json, Router,
+        body::Body,
+        extract::{DefaultBodyLimit, Request},
+        http::{StatusCode, header::CONTENT_TYPE},
+        middleware::{Next, from_fn, from_fn_with_state},
+        response::Response,
+        routing::post,
+    };
+    use serde_json::{Value, json};
+    use sqlx::{Pool, Postgres};
+    use tower::ServiceExt;
+    use uuid::Uuid;
+
+    use crate::{
+        api::payload_mw::payload_mw,
+        models::{
+            auth::Claims, integration::IntegrationName, request_context::RequestContext,
+            user::SubjectId,
+        },
+        tests::support::TestDb,
+    };
+
+    static REQUEST_ID_HEADER: &str = "x-test-request-id";
+    static INTEGRATION_HEADER: &str = "x-test-integration";
+
+    /// Stands in for auth_mw/webhook_mw, which attach the request context
+    async fn fake_context_mw(mut req: Request<Body>, next: Next) -> Response {
+        let headers = req.headers();
+        let request_id = headers[REQUEST_ID_HEADER]
+            .to_str()
+            .unwrap()
+            .parse()
+            .unwrap();
+        let subject = match headers.get(INTEGRATION_HEADER) {
+            Some(name) => SubjectId::Integration(IntegrationName::from(name.to_str().unwrap())),
+            None => SubjectId::BaseUser(Uuid::new_v4()),
+        };
+
+        req.extensions_mut()
+            .insert(RequestContext::new(subject, Claims::empty(), request_id));
+        next.run(req).await
+    }
+
+    fn router(pool: &Pool<Postgres>) -> Router {
+        Router::new()
+            .route("/", post(|Json(_): Json<Value>| async { StatusCode::OK }))
+            .layer(from_fn_with_state(pool.clone(), payload_mw))
+            .layer(from_fn(fake_context_mw))
+            .layer(DefaultBodyLimit::max(64))
+    }
+
+    async fn send(
+        pool: &Pool<Postgres>,
+        integration: Option<&str>,
+        content_type: &str,
+        body: String,
+    ) -> (StatusCode, Uuid) {
+        let request_id = Uuid::new_v4();
+        let mut req = Request::post("/")
+            .header(CONTENT_TYPE, content_type)
+            .header(REQUEST_ID_HEADER, request_id.to_string());
+        if let Some(integration) = integration {
+            req = req.header(INTEGRATION_HEADER, integration);
+        }
+
+        let response = router(pool)
+            .oneshot(req.body(Body::from(body)).unwrap())
+            .await
+            .unwrap();
+        (response.status(), request_id)
+    }
+
+    /// The rejection is logged in the background
+    async fn logged(pool: &Pool<Postgres>, request_id: Uuid) -> bool {
+        for _ in 0..20 {
+            let count = sqlx::query_scalar::<_, i64>(
+                r#"SELECT COUNT(*) FROM "system_log" WHERE correlation_id = $1"#,
+            )
+            .bind(request_id)
+            .fetch_one(pool)
+            .await
+            .unwrap();
+
+            if count > 0 {
+                return true;
+            }
+            tokio::time::sleep(Duration::from_millis(50)).await;
+        }
+
+        false
+    }
+
+    fn oversized() -> String {
+        json!({ "payload": "x".repeat(256) }).to_string()
+    }
+
+    #[tokio::test]
+    async fn small_json_body_passes() {
+        let db = TestDb::create().await;
+        let (status, _) = send(&db.pool, None, "application/json", "{}".into()).await;
+        assert_eq!(status, StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn oversized_integration_body_is_rejected_and_logged() {
+        let db = TestDb::create().await;
+        let (status, request_id) = send(
+            &db.pool,
+            Some(IntegrationName::SESSION),
+            "application/json",
+            oversized(),
+        )
+        .await;
+
+        assert_eq!(status, StatusCode::PAYLOAD_TOO_LARGE);
+        assert!(logged(&db.pool, request_id).await);
+    }
+
+    #[tokio::test]
+    async fn wrong_content_type_is_rejected_and_logged() {
+        let db = TestDb::create().await;
+        let (status, request_id) = send(
+            &db.pool,
+            Some(IntegrationName::SESSION),
+            "text/plain",
+            "{}".into(),
+        )
+        .await;
+
+        assert_eq!(status, StatusCode::UNSUPPORTED_MEDIA_TYPE);
+        assert!(logged(&db.pool, request_id).await);
+    }
+
+    #[tokio::test]
+    async fn user_rejections_are_not_logged() {
+        let db = TestDb::create().await;
+        let (status, request_id) = send(&db.pool, None, "application/json", oversized()).await;
+
+        assert_eq!(status, StatusCode::PAYLOAD_TOO_LARGE);
+        assert!(!logged(&db.pool, request_id).await);
+    }
+}
@@ -0,0 +1,116 @@
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use chrono::{Duration, Utc};
+    use sqlx::types::Json;
+
+    use crate::{
+        models::{
+            error::ErrorCode,
+            game_base::Validate,
+            popup_manager::{ClientPopup, MAX_POPUP_PRIORITY, PopupContent, PopupManager},
+        },
+        service::util::parse_accept_language,
+        tests::support::TestDb,
+    };
+
+    #[tokio::test]
+    async fn popup_survives_restart() {
+        let db = TestDb::create().await;
+        let manager = PopupManager::load(&db.pool).await.unwrap();
+
+        let popup = ClientPopup {
+            content: Json(HashMap::from([(
+                "nb".to_string(),
+                PopupContent::new("Vedlikehold", "Appen er nede i kveld"),
+            )])),
+            active: true,
+            ..Default::default()
+        };
+        let popup = manager.update(popup).await.unwrap();
+
+        // Simulate a restart by loading a fresh manager from the same pool
+        let restarted = PopupManager::load(&db.pool).await.unwrap();
+        let loaded = restarted.list().await;
+
+        let loaded = loaded
+            .iter()
+            .find(|p| p.popup_id == popup.popup_id)
+            .unwrap();
+        assert_eq!(loaded.content.0, popup.content.0);
+        restarted.remove(popup.popup_id).await.unwrap();
+    }
+
+    #[test]
+    fn popup_visibility_respects_schedule() {
+        let now = Utc::now();
+        let mut popup = ClientPopup {
+            active: true,
+            ..Default::default()
+        };
+        assert!(popup.is_visible(now));
+
+        popup.active_from = Some(now + Duration::hours(1));
+        assert!(!popup.is_visible(now));
+
+        popup.active_from = Some(now - Duration::hours(2));
+        popup.active_until = Some(now - Duration::hours(1));
+        assert!(!popup.is_visible(now));
+
+        popup.active_until = None;
+        popup.active = false;
+        assert!(!popup.is_visible(now));
+    }
+
+    #[test]
+    fn popup_is_served_in_best_matching_locale() {
+        let popup = ClientPopup::default();
+        let served = |header: &str| popup.localize(&parse_accept_language(header)).locale;
+
+        assert_eq!(served("en-US,en;q=0.9,nb;q=0.8"), "en");
+        assert_eq!(served("de-DE, nb;q=0.5, en;q=0.7"), "en");
+        assert_eq!(served("sv, en;q=0"), "nb");
+        assert_eq!(served(""), "nb");
+
+        let english = popup.localize(&["fr".into(), "EN-gb".into()]);
+        assert_eq!(english.locale, "en");
+        assert_eq!(english.heading, "Welcome");
+    }
+
+    #[test]
+    fn default_locale_must_have_content() {
+        let mut popup = ClientPopup {
+            default_locale: "de".into(),
+            ..Default::default()
+        };
+        assert_eq!(
+            popup.validate().unwrap_err().code(),
+            ErrorCode::ValidationFailed
+        );
+
+        popup.default_locale = "en".into();
+        popup
+            .content
+            .insert("EN_US".into(), PopupContent::new("Hi", "There"));
+        assert!(popup.validate().is_err());
+
+        popup.content.remove("EN_US");
+        assert!(popup.validate().is_ok());
+    }
+
+    #[test]
+    fn priority_must_be_within_range() {
+        let mut popup = ClientPopup {
+            priority: -1,
+            ..Default::default()
+        };
+        assert!(popup.validate().is_err());
+
+        popup.priority = MAX_POPUP_PRIORITY + 1;
+        assert!(popup.validate().is_err());
+
+        popup.priority = MAX_POPUP_PRIORITY;
+        assert!(popup.validate().is_ok());
+    }
+}
@@ -0,0 +1,186 @@
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+    use uuid::Uuid;
+
+    use crate::{
+        db::quiz_game::{get_quiz_session_by_id, tx_clone_quiz_game, tx_persist_quiz_session},
+        models::{
+            error::ErrorCode,
+            game_base::{MAX_NAME_CHARS, Validate},
+            quiz_game::{
+                LEGACY_QUIZ_SCHEMA_VERSION, QUIZ_SCHEMA_VERSION, QuizQuestion, QuizSession,
+            },
+        },
+        tests::support::TestDb,
+    };
+
+    fn payload(questions: serde_json::Value) -> serde_json::Value {
+        json!({
+            "base_id": Uuid::new_v4(),
+            "quiz_id": Uuid::new_v4(),
+            "name": "Vorspiel",
+            "description": null,
+            "category": "Default",
+            "iterations": 2,
+            "current_iteration": 0,
+            "questions": questions,
+            "times_played": 0
+        })
+    }
+
+    #[test]
+    fn legacy_payload_is_upgraded_to_structured_questions() {
+        let session: QuizSession =
+            serde_json::from_value(payload(json!(["Hvem ler mest?", "Hvem sover lengst?"])))
+                .unwrap();
+
+        assert_eq!(session.schema_version, LEGACY_QUIZ_SCHEMA_VERSION);
+        assert_eq!(session.questions.len(), 2);
+        assert_eq!(session.questions[0].text, "Hvem ler mest?");
+        assert!(session.questions[0].options.is_empty());
+        assert_ne!(session.questions[0].id, session.questions[1].id);
+    }
+
+    #[test]
+    fn structured_payload_keeps_question_fields() {
+        let id = Uuid::new_v4();
+        let mut value = payload(json!([{
+            "id": id,
+            "text": "Hva er hovedstaden i Norge?",
+            "options": ["Bergen", "Oslo"],
+            "correct_index": 1,
+            "seconds": 20
+        }]));
+        value["schema_version"] = json!(QUIZ_SCHEMA_VERSION);
+
+        let session: QuizSession = serde_json::from_value(value).unwrap();
+        assert_eq!(session.schema_version, QUIZ_SCHEMA_VERSION);
+        assert_eq!(
+            session.questions[0],
+            QuizQuestion {
+                id,
+                text: "Hva er hovedstaden i Norge?".into(),
+                options: vec!["Bergen".into(), "Oslo".into()],
+                correct_index: Some(1),
+                seconds: Some(20),
+            }
+        );
+    }
+
+    #[tokio::test]
+    async fn questions_round_trip_through_database() {
+        let db = TestDb::create().await;
+        let mut session: QuizSession =
+            serde_json::from_value(payload(json!(["Hvem ler mest?"]))).unwrap();
+        session.questions.push(QuizQuestion {
+            options: vec!["Ja".into(), "Nei".into()],
+            correct_index: Some(0),
+            seconds: Some(10),
+            ..QuizQuestion::from_text("Er dette en test?")
+        });
+
+        let mut tx = db.pool.begin().await.unwrap();
+        tx_persist_quiz_session(&mut tx, &session, Uuid::new_v4())
+            .await
+            .unwrap();
+        tx.commit().await.unwrap();
+
+        let stored = get_quiz_session_by_id(&db.pool, &session.base_id)
+            .await
+            .unwrap();
+        assert_eq!(stored.schema_version, QUIZ_SCHEMA_VERSION);
+        assert_eq!(stored.questions, session.questions);
+    }
+
+    #[tokio::test]
+    async fn clone_copies_quiz_and_leaves_original_untouched() {
+        let db = TestDb::create().await;
+        let session: QuizSession =
+            serde_json::from_value(payload(json!(["Hvem ler mest?", "Hvem sover lengst?"])))
+                .unwrap();
+
+        let mut tx = db.pool.begin().await.unwrap();
+        tx_persist_quiz_session(&mut tx, &session, Uuid::new_v4())
+            .await
+            .unwrap();
+        tx.commit().await.unwrap();
+        let original = get_quiz_session_by_id(&db.pool, &session.base_id)
+            .await
+            .unwrap();
+
+        let mut tx = db.pool.begin().await.unwrap();
+        let clone_id = tx_clone_quiz_game(&mut tx, session.base_id, Uuid::new_v4())
+            .await
+            .unwrap();
+        tx.commit().await.unwrap();
+
+        let clone = get_quiz_session_by_id(&db.pool, &clone_id).await.unwrap();
+        assert_ne!(clone.base_id, original.base_id);
+        assert_ne!(clone.quiz_id, original.quiz_id);
+        assert_eq!(clone.name, "Vorspiel (copy)");
+        assert_eq!(clone.times_played, 0);
+        assert_eq!(clone.questions, original.questions);
+
+        let after = get_quiz_session_by_id(&db.pool, &session.base_id)
+            .await
+            .unwrap();
+        assert_eq!(after.name, original.name);
+        assert_eq!(after.quiz_id, original.quiz_id);
+        assert_eq!(after.times_played, original.times_played);
+        assert_eq!(after.questions, original.questions);
+    }
+
+    #[tokio::test]
+    async fn clone_name_is_truncated_to_the_name_limit() {
+        let db = TestDb::create().await;
+        let mut session: QuizSession =
+            serde_json::from_value(payload(json!(["Hvem ler mest?"]))).unwrap();
+        session.name = "x".repeat(MAX_NAME_CHARS);
+
+        let mut tx = db.pool.begin().await.unwrap();
+        tx_persist_quiz_session(&mut tx, &session, Uuid::new_v4())
+            .await
+            .unwrap();
+        let clone_id = tx_clone_quiz_game(&mut tx, session.base_id, Uuid::new_v4())
+            .await
+            .unwrap();
+        tx.commit().await.unwrap();
+
+        let clone = get_quiz_session_by_id(&db.pool, &clone_id).await.unwrap();
+        assert_eq!(clone.name.chars().count(), MAX_NAME_CHARS);
+        assert!(clone.validate().is_ok());
+    }
+
+    #[tokio::test]
+    async fn padded_name_is_stored_trimmed() {
+        let db = TestDb::create().await;
+        let mut session: QuizSession =
+            serde_json::from_value(payload(json!(["Hvem ler mest?"]))).unwrap();
+        session.name = format!(" {} ", "x".repeat(MAX_NAME_CHARS));
+        assert!(session.validate().is_ok());
+
+        let mut tx = db.pool.begin().await.unwrap();
+        tx_persist_quiz_session(&mut tx, &session, Uuid::new_v4())
+            .await
+            .unwrap();
+        tx.commit().await.unwrap();
+
+        let stored = get_quiz_session_by_id(&db.pool, &session.base_id)
+            .await
+            .unwrap();
+        assert_eq!(stored.name, "x".repeat(MAX_NAME_CHARS));
+    }
+
+    #[tokio::test]
+    async fn clone_of_missing_quiz_is_not_found() {
+        let db = TestDb::create().await;
+
+        let mut tx = db.pool.begin().await.unwrap();
+        let error = tx_clone_quiz_game(&mut tx, Uuid::new_v4(), Uuid::new_v4())
+            .await
+            .unwrap_err();
+
+        assert_eq!(error.code(), ErrorCode::NotFound);
+    }
+}
@@ -0,0 +1,141 @@
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use axum::{
+        Router,
+        body::Body,
+        extract::Request,
+        http::StatusCode,
+        middleware::{Next, from_fn},
+        response::Response,
+        routing::get,
+    };
+    use tower::ServiceExt;
+    use uuid::Uuid;
+
+    use crate::models::{
+        auth::Claims,
+        error::ServerError,
+        integration::IntegrationName,
+        request_context::RequestContext,
+        user::{Permission, SubjectId},
+    };
+
+    fn context(subject: SubjectId, permissions: &[Permission]) -> RequestContext {
+        let mut claims = Claims::empty();
+        claims.permissions = Some(permissions.iter().cloned().collect::<HashSet<_>>());
+        RequestContext::new(subject, claims, Uuid::new_v4())
+    }
+
+    async fn attach_context(mut req: Request<Body>, next: Next) -> Response {
+        req.extensions_mut()
+            .insert(context(SubjectId::BaseUser(Uuid::new_v4()), &[]));
+        next.run(req).await
+    }
+
+    fn router() -> Router {
+        Router::new().route(
+            "/",
+            get(|ctx: RequestContext| async move {
+                ctx.require_base_user().map(|_| StatusCode::OK)
+            }),
+        )
+    }
+
+    async fn status(router: Router) -> StatusCode {
+        router
+            .oneshot(Request::get("/").body(Body::empty()).unwrap())
+            .await
+            .unwrap()
+            .status()
+    }
+
+    #[tokio::test]
+    async fn extractor_rejects_requests_without_context() {
+        assert_eq!(status(router()).await, StatusCode::UNAUTHORIZED);
+    }
+
+    #[tokio::test]
+    async fn extractor_reads_context_attached_by_middleware() {
+        assert_eq!(
+            status(router().layer(from_fn(attach_context))).await,
+            StatusCode::OK
+        );
+    }
+
+    #[test]
+    fn subject_helpers_match_subject_kind() {
+        let id = Uuid::new_v4();
+
+        let pseudo = context(SubjectId::PseudoUser(id), &[]);
+        assert_eq!(pseudo.require_user().unwrap(), id);
+        assert!(matches!(
+            pseudo.require_base_user(),
+            Err(ServerError::AccessDenied)
+        ));
+
+        let base = context(SubjectId::BaseUser(id), &[]);
+        assert_eq!(base.require_user().unwrap(), id);
+        assert_eq!(base.require_base_user().unwrap(), id);
+
+        let integration = context(
+            SubjectId::Integration(IntegrationName::from(IntegrationName::AUTH0)),
+            &[],
+        );
+        assert!(matches!(
+            integration.require_user(),
+            Err(ServerError::AccessDenied)
+        ));
+        assert!(matches!(
+            integration.require_base_user(),
+            Err(ServerError::AccessDenied)
+        ));
+    }
+
+    #[test]
+    fn permission_helpers_check_claims() {
+        let ctx = context(
+            SubjectId::BaseUser(Uuid::new_v4()),
+            &[Permission::ReadAdmin],
+        );
+
+        assert!(ctx.has_permission(Permission::ReadAdmin));
+        assert!(ctx.require_permission(Permission::ReadAdmin).is_ok());
+        assert!(!ctx.has_permission(Permission::WriteAdmin));
+        assert!(matches!(
+            ctx.require_permission(Permission::WriteAdmin),
+            Err(ServerError::Permission(_))
+        ));
+    }
+
+    #[test]
+    fn only_integrations_need_read_game() {
+        let integration = |permissions: &[Permission]| {
+            context(
+                SubjectId::Integration(IntegrationName::from("bff")),
+                permissions,
+            )
+        };
+
+        assert!(
+            context(SubjectId::BaseUser(Uuid::new_v4()), &[])
+                .require_user_or_permission(Permission::ReadGame)
+                .is_ok()
+        );
+        assert!(
+            context(SubjectId::PseudoUser(Uuid::new_v4()), &[])
+                .require_user_or_permission(Permission::ReadGame)
+                .is_ok()
+        );
+        assert!(matches!(
+            integration(&[]).require_user_or_permission(Permission::ReadGame),
+            Err(ServerError::Permission(_))
+        ));
+        assert!(
+            integration(&[Permission::ReadGame])
+                .require_user_or_permission(Permission::ReadGame)
+                .is_ok()
+        );
+    }
+}
@@ -0,0 +1,33 @@
+#[cfg(test)]
+mod tests {
+    use crate::{service::self_check::run_self_check, tests::support::TestApp};
+
+    #[tokio::test]
+    async fn migrated_test_database_passes_the_self_check() {
+        let app = TestApp::spawn().await;
+
+        let report = run_self_check(&app.state).await;
+        assert!(report.passed, "{:?}", report);
+        assert_eq!(report.checks.len(), 5);
+    }
+
+    #[tokio::test]
+    async fn empty_word_table_fails_only_its_own_check() {
+        let app = TestApp::spawn().await;
+        sqlx::query(r#"DELETE FROM "suffix_word""#)
+            .execute(&app.db.pool)
+            .await
+            .unwrap();
+
+        let report = run_self_check(&app.state).await;
+        assert!(!report.passed);
+
+        let failed: Vec<&str> = report
+            .checks
+            .iter()
+            .filter(|c| !c.passed)
+            .map(|c| c.name)
+            .collect();
+        assert_eq!(failed, vec!["word_tables"]);
+    }
+}
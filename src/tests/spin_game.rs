@@ -0,0 +1,233 @@
+#[cfg(test)]
+mod tests {
+    use axum::http::StatusCode;
+    use chrono::Utc;
+    use serde_json::json;
+    use uuid::Uuid;
+
+    use crate::{
+        api::game_base::{persist_standalone_session, standalone_session},
+        client::gs_client::GameSessionDescriptor,
+        config::config::CONFIG,
+        db::spin_game::{get_spin_session_by_game_id, tx_persist_spin_session},
+        models::{
+            error::ServerError,
+            game_base::{
+                CreateGameRequest, ENVELOPE_SCHEMA_VERSION, GameStartResponse, GameType,
+                InteractiveEnvelope, Validate,
+            },
+            integration::IntegrationName,
+            spin_game::{LEGACY_SPIN_SCHEMA_VERSION, SPIN_SCHEMA_VERSION, SpinRound, SpinSession},
+            user::SubjectId,
+        },
+        tests::support::{TestApp, TestDb},
+    };
+
+    fn payload(rounds: serde_json::Value) -> serde_json::Value {
+        json!({
+            "spin_id": Uuid::new_v4(),
+            "base_id": Uuid::new_v4(),
+            "host_id": Uuid::new_v4(),
+            "name": "Snurr flasken",
+            "description": null,
+            "category": "Casual",
+            "iterations": 3,
+            "times_played": 0,
+            "last_played": Utc::now(),
+            "rounds": rounds,
+            "players": []
+        })
+    }
+
+    #[test]
+    fn legacy_and_structured_rounds_deserialize() {
+        let session: SpinSession = serde_json::from_value(payload(json!([
+            "Alle skåler",
+            {"text": "Sjeldent", "weight": 1, "exclude_repeat": true},
+            {"text": "Vanlig", "weight": 5}
+        ])))
+        .unwrap();
+
+        assert_eq!(session.schema_version, LEGACY_SPIN_SCHEMA_VERSION);
+        assert_eq!(session.rounds[0], SpinRound::from_text("Alle skåler"));
+        assert!(session.rounds[1].exclude_repeat);
+        assert_eq!(session.rounds[2].weight, 5);
+        assert!(!session.rounds[2].exclude_repeat);
+    }
+
+    #[test]
+    fn zero_weight_round_is_rejected() {
+        let mut session: SpinSession =
+            serde_json::from_value(payload(json!(["Alle skåler"]))).unwrap();
+        session.rounds[0].weight = 0;
+
+        let fields: Vec<String> = session
+            .field_errors()
+            .into_iter()
+            .map(|e| e.field)
+            .collect();
+        assert_eq!(fields, vec!["rounds[0].weight"]);
+    }
+
+    #[tokio::test]
+    async fn rounds_round_trip_through_database() {
+        let db = TestDb::create().await;
+        let session: SpinSession = serde_json::from_value(payload(json!([
+            "Alle skåler",
+            {"text": "Sjeldent", "weight": 2, "exclude_repeat": true}
+        ])))
+        .unwrap();
+
+        let mut tx = db.pool.begin().await.unwrap();
+        tx_persist_spin_session(&mut tx, &session, session.host_id)
+            .await
+            .unwrap();
+        tx.commit().await.unwrap();
+
+        let host_id = Uuid::new_v4();
+        let stored = get_spin_session_by_game_id(&db.pool, host_id, session.base_id)
+            .await
+            .unwrap();
+
+        assert_eq!(stored.schema_version, SPIN_SCHEMA_VERSION);
+        assert_eq!(stored.host_id, host_id);
+        assert_eq!(stored.rounds, session.rounds);
+    }
+
+    #[tokio::test]
+    async fn spin_games_can_be_started_standalone() {
+        let db = TestDb::create().await;
+        let session: SpinSession =
+            serde_json::from_value(payload(json!(["Alle skåler", "Pekelek"]))).unwrap();
+        let mut tx = db.pool.begin().await.unwrap();
+        tx_persist_spin_session(&mut tx, &session, Uuid::new_v4())
+            .await
+            .unwrap();
+        tx.commit().await.unwrap();
+
+        let host_id = Uuid::new_v4();
+        let value = standalone_session(
+            &db.pool,
+            &SubjectId::PseudoUser(host_id),
+            GameType::Spin,
+            session.base_id,
+            false,
+        )
+        .await
+        .unwrap();
+        let started: SpinSession = serde_json::from_value(value).unwrap();
+        assert_eq!(started.base_id, session.base_id);
+        assert_eq!(started.host_id, host_id);
+        assert_eq!(started.rounds.len(), 2);
+
+        let integration = SubjectId::Integration(IntegrationName::from(IntegrationName::SESSION));
+        let result = standalone_session(
+            &db.pool,
+            &integration,
+            GameType::Spin,
+            session.base_id,
+            false,
+        )
+        .await;
+        assert!(matches!(result, Err(ServerError::AccessDenied)));
+    }
+
+    #[tokio::test]
+    async fn spin_games_can_be_persisted_standalone() {
+        let db = TestDb::create().await;
+        let envelope = |payload| InteractiveEnvelope {
+            game_key: String::new(),
+            host_id: Uuid::new_v4(),
+            game_type: GameType::Spin,
+            schema_version: ENVELOPE_SCHEMA_VERSION,
+            payload,
+        };
+
+        let session = payload(json!(["Alle skåler"]));
+        let base_id: Uuid = serde_json::from_value(session["base_id"].clone()).unwrap();
+        let user_id = Uuid::new_v4();
+        let persisted = persist_standalone_session(&db.pool, envelope(session), user_id).await;
+        assert_eq!(persisted.unwrap(), GameType::Spin);
+
+        let stored = get_spin_session_by_game_id(&db.pool, user_id, base_id)
+            .await
+            .unwrap();
+        assert_eq!(stored.rounds, vec![SpinRound::from_text("Alle skåler")]);
+
+        let malformed = envelope(json!({ "rounds": "Alle skåler" }));
+        let result = persist_standalone_session(&db.pool, malformed, user_id).await;
+        assert!(matches!(result, Err(ServerError::Validation(_))));
+    }
+
+    #[tokio::test]
+    async fn create_initiate_and_join_describe_the_same_hub() {
+        let app = TestApp::spawn().await;
+        let guest = Uuid::new_v4().to_string();
+        let request = || CreateGameRequest {
+            name: "Vorspiel".into(),
+            description: None,
+            category: None,
+            visibility: None,
+        };
+
+        let mut stored = SpinSession::from_create_request(Uuid::new_v4(), request());
+        stored.rounds.push(SpinRound::from_text("Alle skåler"));
+        let mut tx = app.db.pool.begin().await.unwrap();
+        tx_persist_spin_session(&mut tx, &stored, stored.host_id)
+            .await
+            .unwrap();
+        tx.commit().await.unwrap();
+
+        let interactive = |response: GameStartResponse| match response {
+            GameStartResponse::Interactive(descriptor) => descriptor,
+            GameStartResponse::Standalone { .. } => panic!("Expected an interactive game"),
+        };
+
+        let created = app
+            .client
+            .post(app.url("/games/general/Spin/create"))
+            .header("X-Guest-Authentication", &guest)
+            .json(&request())
+            .send()
+            .await
+            .unwrap();
+        let status = created.status();
+        assert_eq!(
+            status,
+            StatusCode::CREATED,
+            "{}",
+            created.text().await.unwrap()
+        );
+        let created = interactive(created.json().await.unwrap());
+
+        let initiated = app
+            .client
+            .post(app.url(&format!("/games/session/Spin/initiate/{}", stored.base_id)))
+            .header("X-Guest-Authentication", &guest)
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(initiated.status(), StatusCode::OK);
+        let initiated = interactive(initiated.json().await.unwrap());
+
+        let joined = app
+            .client
+            .post(app.url(&format!("/games/session/Spin/join/{}", created.key_word)))
+            .header("X-Guest-Authentication", &guest)
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(joined.status(), StatusCode::OK);
+        let joined: GameSessionDescriptor = joined.json().await.unwrap();
+
+        let hub = format!("{}hubs/spin", CONFIG.server.gs_domain);
+        for descriptor in [&created, &initiated, &joined] {
+            assert_eq!(descriptor.hub_address, hub);
+            assert_eq!(descriptor.websocket_scheme, "ws");
+        }
+
+        assert_eq!(joined.key_word, created.key_word);
+        let drift = (joined.expires_at - created.expires_at).num_seconds().abs();
+        assert!(drift <= 1, "Join expiry drifted {}s from create", drift);
+    }
+}
@@ -0,0 +1,250 @@
+use std::{env, net::SocketAddr, str::FromStr, sync::Arc};
+
+use axum::{Router, http::StatusCode};
+use chrono::Utc;
+use dotenv::dotenv;
+use reqwest::Client;
+use sqlx::{
+    Connection, Executor, PgConnection, Pool, Postgres,
+    postgres::{PgConnectOptions, PgPoolOptions},
+};
+use uuid::Uuid;
+
+use crate::{
+    api::router::app_router,
+    client::gs_client::GSClient,
+    db::{
+        quiz_game::tx_persist_quiz_session,
+        user::{create_base_user, tx_create_pseudo_user},
+    },
+    models::{
+        app_state::AppState,
+        auth::{Jwk, Jwks},
+        game_base::{CreateGameRequest, GameCategory, GameVisibility},
+        quiz_game::{QuizQuestion, QuizSession},
+        user::Auth0User,
+    },
+};
+
+/// A database of its own for a single test, created next to the one in
+/// `TERO__DATABASE_URL` and dropped again with the value
+pub struct TestDb {
+    pub pool: Pool<Postgres>,
+    name: String,
+    admin_url: String,
+}
+
+impl TestDb {
+    /// Runs every migration, so the word tables hold the default words
+    pub async fn create() -> Self {
+        dotenv().ok();
+        let admin_url = env::var("TERO__DATABASE_URL").expect("Failed to obtain connection string");
+        let name = format!("tero_test_{}", Uuid::new_v4().simple());
+
+        let mut admin = PgConnection::connect(&admin_url).await.unwrap();
+        admin
+            .execute(
+                format!(
+                    r#"CREATE DATABASE "{}" ENCODING 'UTF8' TEMPLATE template0"#,
+                    name
+                )
+                .as_str(),
+            )
+            .await
+            .unwrap();
+        admin.close().await.unwrap();
+
+        let options = PgConnectOptions::from_str(&admin_url)
+            .unwrap()
+            .database(&name);
+        let pool = PgPoolOptions::new()
+            .max_connections(10)
+            .connect_with(options)
+            .await
+            .unwrap();
+        sqlx::migrate!().run(&pool).await.unwrap();
+
+        Self {
+            pool,
+            name,
+            admin_url,
+        }
+    }
+}
+
+impl Drop for TestDb {
+    fn drop(&mut self) {
+        let name = self.name.clone();
+        let admin_url = self.admin_url.clone();
+
+        // Drop can not await, and the test runtime may already be shutting down
+        let _ = std::thread::spawn(move || {
+            let runtime = tokio::runtime::Builder::new_current_thread()
+                .enable_all()
+                .build()
+                .unwrap();
+
+            runtime.block_on(async move {
+                if let Ok(mut admin) = PgConnection::connect(&admin_url).await {
+                    let query = format!(r#"DROP DATABASE IF EXISTS "{}" WITH (FORCE)"#, name);
+                    let _ = admin.execute(query.as_str()).await;
+                }
+            });
+        })
+        .join();
+    }
+}
+
+/// Replaces the default words, e.g. to get a vault with a small capacity
+pub async fn seed_words(pool: &Pool<Postgres>, prefixes: &[&str], suffixes: &[&str]) {
+    sqlx::query(r#"TRUNCATE "prefix_word", "suffix_word""#)
+        .execute(pool)
+        .await
+        .unwrap();
+
+    for word in prefixes {
+        sqlx::query(r#"INSERT INTO "prefix_word" (word) VALUES ($1)"#)
+            .bind(word)
+            .execute(pool)
+            .await
+            .unwrap();
+    }
+
+    for word in suffixes {
+        sqlx::query(r#"INSERT INTO "suffix_word" (word) VALUES ($1)"#)
+            .bind(word)
+            .execute(pool)
+            .await
+            .unwrap();
+    }
+}
+
+/// Registered users, each with the pseudo user sharing its id
+pub async fn seed_users(pool: &Pool<Postgres>, count: usize) -> Vec<Uuid> {
+    let mut ids = Vec::with_capacity(count);
+    for num in 0..count {
+        let auth0_user = Auth0User {
+            auth0_id: format!("auth0|{}", Uuid::new_v4()),
+            email: Some(format!("bruker{}@tero.no", num)),
+            email_verified: Some(true),
+            username: Some(format!("bruker{}", num)),
+            phone_number: None,
+            phone_verified: None,
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+            name: None,
+            nickname: None,
+            given_name: None,
+            family_name: None,
+        };
+
+        let mut tx = pool.begin().await.unwrap();
+        let id = create_base_user(&mut tx, &auth0_user).await.unwrap();
+        tx_create_pseudo_user(&mut tx, id).await.unwrap();
+        tx.commit().await.unwrap();
+        ids.push(id);
+    }
+
+    ids
+}
+
+/// Public quiz games with a single question, owned by `owner`
+pub async fn seed_games(pool: &Pool<Postgres>, owner: Uuid, count: usize) -> Vec<Uuid> {
+    let mut ids = Vec::with_capacity(count);
+    for num in 0..count {
+        let request = CreateGameRequest {
+            name: format!("Vorspiel {}", num),
+            description: None,
+            category: Some(GameCategory::Casual.into()),
+            visibility: Some(GameVisibility::Public),
+        };
+        let mut session = QuizSession::from_create_request(request);
+        session
+            .questions
+            .push(QuizQuestion::from_text("Hvem ler mest?"));
+
+        let mut tx = pool.begin().await.unwrap();
+        tx_persist_quiz_session(&mut tx, &session, owner)
+            .await
+            .unwrap();
+        tx.commit().await.unwrap();
+        ids.push(session.base_id);
+    }
+
+    ids
+}
+
+/// The api served on a random local port, backed by its own database. Jwt
+/// verification always fails since the keys are fake, so drive the
+/// protected routes as a guest
+pub struct TestApp {
+    pub address: SocketAddr,
+    pub state: Arc<AppState>,
+    pub client: Client,
+    pub db: TestDb,
+}
+
+impl TestApp {
+    pub async fn spawn() -> Self {
+        let db = TestDb::create().await;
+        let client = Client::new();
+        let gs_client = GSClient::new(spawn_game_server().await);
+        let state = AppState::from_parts(
+            db.pool.clone(),
+            db.pool.clone(),
+            client.clone(),
+            gs_client,
+            jwks(),
+        )
+        .await
+        .unwrap();
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap();
+        let app = app_router(state.clone());
+        tokio::spawn(async move {
+            axum::serve(
+                listener,
+                app.into_make_service_with_connect_info::<SocketAddr>(),
+            )
+            .await
+            .unwrap();
+        });
+
+        Self {
+            address,
+            state,
+            client,
+            db,
+        }
+    }
+
+    pub fn url(&self, path: &str) -> String {
+        format!("http://{}{}", self.address, path)
+    }
+}
+
+/// Stands in for tero-session and accepts every call, returns its domain
+async fn spawn_game_server() -> String {
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let address = listener.local_addr().unwrap();
+    let app = Router::new().fallback(|| async { StatusCode::OK });
+    tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+
+    format!("http://{}/", address)
+}
+
+fn jwks() -> Jwks {
+    let jwk = |kid: &str| Jwk {
+        kid: kid.into(),
+        n: String::new(),
+        e: String::new(),
+        kty: "RSA".into(),
+        alg: "RS256".into(),
+        use_: "sig".into(),
+    };
+
+    Jwks {
+        keys: vec![jwk("test-1"), jwk("test-2")],
+    }
+}
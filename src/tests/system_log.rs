@@ -0,0 +1,292 @@
+#[cfg(test)]
+mod tests {
+    use chrono::{Duration, TimeZone, Utc};
+    use uuid::Uuid;
+
+    use crate::{
+        api::system_log::validate_syslog_batch,
+        db::system_log::{
+            create_system_log, create_system_logs, get_log_stats, get_system_log_page,
+        },
+        models::{
+            error::ServerError,
+            system_log::{
+                CreateSyslogRequest, LogAction, LogCeverity, LogStatsQuery, NewSystemLog,
+                SubjectType, SyslogPageQuery,
+            },
+        },
+        service::system_log_builder::SystemLogBuilder,
+        tests::support::TestDb,
+    };
+
+    #[tokio::test]
+    async fn subject_type_is_stored_and_read_back() {
+        let db = TestDb::create().await;
+        let correlation_id = Uuid::new_v4();
+
+        create_system_log(
+            &db.pool,
+            &NewSystemLog {
+                subject_id: "session".into(),
+                subject_type: SubjectType::Integration,
+                action: LogAction::Create,
+                ceverity: LogCeverity::Info,
+                function: "subject_type_test".into(),
+                description: "Stored by an integration".into(),
+                metadata: None,
+                correlation_id: Some(correlation_id),
+            },
+        )
+        .await
+        .unwrap();
+
+        let query = SyslogPageQuery {
+            page_num: 0,
+            subject_type: None,
+            action: None,
+            ceverity: None,
+            from: None,
+            to: None,
+            search: None,
+            correlation_id: Some(correlation_id),
+        };
+
+        let page = get_system_log_page(&db.pool, query).await.unwrap();
+        let items = page.items();
+        assert_eq!(items.len(), 1);
+        assert_eq!(items[0].subject_type, SubjectType::Integration);
+        assert_eq!(items[0].subject_id, "session");
+    }
+
+    #[tokio::test]
+    async fn log_page_filters_on_search_and_date_range() {
+        let db = TestDb::create().await;
+        let marker = Uuid::new_v4().to_string();
+        let correlation_id = Uuid::new_v4();
+
+        create_system_log(
+            &db.pool,
+            &NewSystemLog {
+                subject_id: "[SYSTEM]".into(),
+                subject_type: SubjectType::System,
+                action: LogAction::Other,
+                ceverity: LogCeverity::Warning,
+                function: "log_page_test".into(),
+                description: format!("Searchable {}", marker),
+                metadata: None,
+                correlation_id: Some(correlation_id),
+            },
+        )
+        .await
+        .unwrap();
+
+        let query = SyslogPageQuery {
+            page_num: 0,
+            subject_type: Some(SubjectType::System),
+            action: None,
+            ceverity: Some(LogCeverity::Warning),
+            from: Some(Utc::now() - Duration::minutes(1)),
+            to: None,
+            search: Some(marker.to_uppercase()),
+            correlation_id: None,
+        };
+
+        let page = get_system_log_page(&db.pool, query).await.unwrap();
+        let page = serde_json::to_value(page).unwrap();
+        assert_eq!(page["items"].as_array().unwrap().len(), 1);
+        assert_eq!(page["items"][0]["function"], "log_page_test");
+
+        let query = SyslogPageQuery {
+            page_num: 0,
+            subject_type: None,
+            action: None,
+            ceverity: None,
+            from: None,
+            to: Some(Utc::now() - Duration::days(1)),
+            search: Some(marker),
+            correlation_id: None,
+        };
+
+        let page = get_system_log_page(&db.pool, query).await.unwrap();
+        let page = serde_json::to_value(page).unwrap();
+        assert!(page["items"].as_array().unwrap().is_empty());
+
+        let query = SyslogPageQuery {
+            page_num: 0,
+            subject_type: None,
+            action: None,
+            ceverity: None,
+            from: None,
+            to: None,
+            search: None,
+            correlation_id: Some(correlation_id),
+        };
+
+        let page = get_system_log_page(&db.pool, query).await.unwrap();
+        let page = serde_json::to_value(page).unwrap();
+        assert_eq!(page["items"].as_array().unwrap().len(), 1);
+        assert_eq!(
+            page["items"][0]["correlation_id"],
+            correlation_id.to_string()
+        );
+    }
+
+    #[tokio::test]
+    async fn log_stats_aggregate_within_window() {
+        let db = TestDb::create().await;
+        let from = Utc.with_ymd_and_hms(1999, 1, 1, 0, 0, 0).unwrap();
+        let to = from + Duration::days(1);
+
+        sqlx::query("DELETE FROM system_log WHERE created_at BETWEEN $1 AND $2")
+            .bind(from)
+            .bind(to)
+            .execute(&db.pool)
+            .await
+            .unwrap();
+
+        let seed = [
+            (
+                SubjectType::System,
+                LogAction::Sync,
+                LogCeverity::Info,
+                "sync_words",
+            ),
+            (
+                SubjectType::System,
+                LogAction::Sync,
+                LogCeverity::Info,
+                "sync_words",
+            ),
+            (
+                SubjectType::Integration,
+                LogAction::Create,
+                LogCeverity::Warning,
+                "create_game",
+            ),
+            (
+                SubjectType::RegisteredUser,
+                LogAction::Delete,
+                LogCeverity::Critical,
+                "sync_words",
+            ),
+        ];
+
+        for (idx, (subject_type, action, ceverity, function)) in seed.into_iter().enumerate() {
+            sqlx::query(
+                r#"
+                INSERT INTO system_log (subject_id, subject_type, action, ceverity, file_name, description, created_at)
+                VALUES ('stats_test', $1, $2, $3, $4, 'seeded', $5)
+                "#,
+            )
+            .bind(subject_type)
+            .bind(action)
+            .bind(ceverity)
+            .bind(function)
+            .bind(from + Duration::hours(idx as i64 + 1))
+            .execute(&db.pool)
+            .await
+            .unwrap();
+        }
+
+        let window = LogStatsQuery {
+            from: Some(from),
+            to: Some(to),
+        };
+        let stats = get_log_stats(&db.pool, &window).await.unwrap();
+
+        assert_eq!(stats.ceverity.info, 2);
+        assert_eq!(stats.ceverity.warning, 1);
+        assert_eq!(stats.ceverity.critical, 1);
+        assert_eq!(stats.action.get(&LogAction::Sync), Some(&2));
+        assert_eq!(stats.action.get(&LogAction::Read), None);
+        assert_eq!(stats.subject_type.get(&SubjectType::System), Some(&2));
+        assert_eq!(stats.top_functions[0].function, "sync_words");
+        assert_eq!(stats.top_functions[0].count, 3);
+        assert_eq!(stats.top_functions.len(), 2);
+    }
+
+    fn syslog_request(description: Option<&str>, function: Option<&str>) -> CreateSyslogRequest {
+        CreateSyslogRequest {
+            action: Some(LogAction::Sync),
+            ceverity: None,
+            description: description.map(String::from),
+            function: function.map(String::from),
+            metadata: None,
+            correlation_id: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn log_batch_inserts_only_valid_entries() {
+        let db = TestDb::create().await;
+        let marker = Uuid::new_v4().to_string();
+        let too_long = "x".repeat(513);
+        let correlation_id = Uuid::new_v4();
+
+        let entries = vec![
+            CreateSyslogRequest {
+                correlation_id: Some(correlation_id),
+                ..syslog_request(Some(&marker), Some("batch_test"))
+            },
+            syslog_request(None, Some("batch_test")),
+            syslog_request(Some(&too_long), None),
+            syslog_request(Some(&marker), Some(&"f".repeat(51))),
+            syslog_request(Some(&marker), None),
+        ];
+
+        let (results, valid) = validate_syslog_batch(&entries);
+        let created: Vec<bool> = results.iter().map(|r| r.created).collect();
+        assert_eq!(created, vec![true, false, false, false, true]);
+        assert!(results[1].error.is_some());
+        assert_eq!(results[4].index, 4);
+
+        let inserted = create_system_logs(&db.pool, "session", &SubjectType::Integration, &valid)
+            .await
+            .unwrap();
+        assert_eq!(inserted, 2);
+
+        let count: i64 =
+            sqlx::query_scalar("SELECT COUNT(*) FROM system_log WHERE description = $1")
+                .bind(&marker)
+                .fetch_one(&db.pool)
+                .await
+                .unwrap();
+        assert_eq!(count, 2);
+
+        let correlation_ids: Vec<Option<Uuid>> = sqlx::query_scalar(
+            "SELECT correlation_id FROM system_log WHERE description = $1 ORDER BY correlation_id NULLS LAST",
+        )
+        .bind(&marker)
+        .fetch_all(&db.pool)
+        .await
+        .unwrap();
+        assert_eq!(correlation_ids, vec![Some(correlation_id), None]);
+    }
+
+    #[tokio::test]
+    async fn builder_requires_function_name() {
+        let db = TestDb::create().await;
+        let marker = Uuid::new_v4().to_string();
+
+        let result = SystemLogBuilder::new(&db.pool)
+            .description(&marker)
+            .log()
+            .await;
+        assert!(matches!(result, Err(ServerError::Internal(_))));
+
+        SystemLogBuilder::new_with_function(&db.pool, "builder_test")
+            .description(&marker)
+            .log()
+            .await
+            .unwrap();
+
+        let functions = sqlx::query_scalar::<_, String>(
+            r#"SELECT file_name FROM "system_log" WHERE description = $1"#,
+        )
+        .bind(&marker)
+        .fetch_all(&db.pool)
+        .await
+        .unwrap();
+        assert_eq!(functions, vec!["builder_test".to_string()]);
+    }
+}
@@ -0,0 +1,94 @@
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use crate::{
+        models::auth::Claims,
+        service::token_cache::{TOKEN_CACHE_MAX_TTL_SECS, TokenCache},
+    };
+
+    static NOW: i64 = 1_700_000_000;
+
+    fn claims(sub: &str, exp: i64) -> Claims {
+        serde_json::from_value(json!({
+            "gty": "client-credentials",
+            "aud": ["tero"],
+            "azp": "client",
+            "exp": exp,
+            "iat": NOW,
+            "iss": "https://tero.eu.auth0.com/",
+            "scope": "",
+            "sub": sub,
+            "permissions": ["write:game"],
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn claims_are_never_returned_past_exp() {
+        let cache = TokenCache::with_capacity(10);
+        cache.insert_at("token", &claims("session@clients", NOW + 60), NOW);
+
+        assert!(cache.get_at("token", NOW).is_some());
+        assert!(cache.get_at("token", NOW + 59).is_some());
+        assert!(cache.get_at("token", NOW + 60).is_none());
+        assert_eq!(cache.len(), 0);
+    }
+
+    #[test]
+    fn cache_lifetime_is_capped() {
+        let cache = TokenCache::with_capacity(10);
+        cache.insert_at("token", &claims("session@clients", NOW + 86_400), NOW);
+
+        assert!(
+            cache
+                .get_at("token", NOW + TOKEN_CACHE_MAX_TTL_SECS - 1)
+                .is_some()
+        );
+        assert!(
+            cache
+                .get_at("token", NOW + TOKEN_CACHE_MAX_TTL_SECS)
+                .is_none()
+        );
+    }
+
+    #[test]
+    fn expired_tokens_are_not_cached() {
+        let cache = TokenCache::with_capacity(10);
+        cache.insert_at("expired", &claims("session@clients", NOW), NOW);
+        cache.insert_at("stale", &claims("session@clients", NOW - 1), NOW);
+        assert_eq!(cache.len(), 0);
+    }
+
+    #[test]
+    fn tokens_sharing_a_prefix_do_not_share_claims() {
+        let cache = TokenCache::with_capacity(10);
+        let prefix = "eyJhbGciOiJSUzI1NiIsInR5cCI6IkpXVCJ9.eyJzdWIiOiJzZXNzaW9u";
+        let session_token = format!("{}.signature-a", prefix);
+        let forged_token = format!("{}.signature-b", prefix);
+
+        cache.insert_at(&session_token, &claims("session@clients", NOW + 60), NOW);
+
+        assert!(cache.get_at(&forged_token, NOW).is_none());
+        assert!(cache.get_at(prefix, NOW).is_none());
+        assert_eq!(
+            cache.get_at(&session_token, NOW).unwrap().sub,
+            "session@clients"
+        );
+    }
+
+    #[test]
+    fn cache_is_bounded_and_prunes_expired_entries() {
+        let cache = TokenCache::with_capacity(2);
+        cache.insert_at("a", &claims("a", NOW + 10), NOW);
+        cache.insert_at("b", &claims("b", NOW + 60), NOW);
+        cache.insert_at("c", &claims("c", NOW + 60), NOW);
+        assert_eq!(cache.len(), 2);
+        assert!(cache.get_at("c", NOW).is_none());
+
+        cache.insert_at("c", &claims("c", NOW + 60), NOW + 10);
+        assert_eq!(cache.len(), 2);
+        assert!(cache.get_at("a", NOW + 10).is_none());
+        assert!(cache.get_at("c", NOW + 10).is_some());
+    }
+}
@@ -0,0 +1,112 @@
+#[cfg(test)]
+mod tests {
+    use axum::http::{HeaderMap, HeaderValue};
+    use opentelemetry::trace::TraceContextExt;
+    use opentelemetry_sdk::trace::SdkTracerProvider;
+    use tracing::info_span;
+    use tracing_opentelemetry::OpenTelemetrySpanExt;
+    use tracing_subscriber::{layer::SubscriberExt, registry};
+
+    use crate::service::{
+        telemetry::{otel_layer, tracer_provider},
+        trace_context::{TRACEPARENT_HEADER, TRACESTATE_HEADER, TraceContext},
+    };
+
+    static TRACEPARENT: &str = "00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01";
+
+    fn headers(traceparent: &str) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            TRACEPARENT_HEADER,
+            HeaderValue::from_str(traceparent).unwrap(),
+        );
+        headers.insert(TRACESTATE_HEADER, HeaderValue::from_static("tero=1"));
+        headers
+    }
+
+    #[test]
+    fn incoming_traceparent_is_parsed_and_continued() {
+        let parent = TraceContext::from_headers(&headers(TRACEPARENT)).unwrap();
+        assert_eq!(parent.traceparent(), TRACEPARENT);
+        assert_eq!(parent.tracestate.as_deref(), Some("tero=1"));
+        assert_eq!(
+            parent.correlation_id().simple().to_string(),
+            parent.trace_id
+        );
+
+        let child = parent.child();
+        assert_eq!(child.trace_id, parent.trace_id);
+        assert_ne!(child.span_id, parent.span_id);
+    }
+
+    #[test]
+    fn malformed_traceparent_is_ignored() {
+        for traceparent in [
+            "01-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01",
+            "00-00000000000000000000000000000000-00f067aa0ba902b7-01",
+            "00-4BF92F3577B34DA6A3CE929D0E0E4736-00f067aa0ba902b7-01",
+            "00-4bf92f3577b34da6a3ce929d0e0e4736-01",
+        ] {
+            assert_eq!(TraceContext::from_headers(&headers(traceparent)), None);
+        }
+    }
+
+    #[tokio::test]
+    async fn scoped_context_is_injected_into_outgoing_headers() {
+        assert_eq!(TraceContext::current(), None);
+
+        let cx = TraceContext::from_headers(&headers(TRACEPARENT)).unwrap();
+        let outgoing = cx
+            .clone()
+            .scope(async {
+                let mut outgoing = HeaderMap::new();
+                TraceContext::current().unwrap().inject(&mut outgoing);
+                outgoing
+            })
+            .await;
+
+        assert_eq!(outgoing.get(TRACEPARENT_HEADER).unwrap(), TRACEPARENT);
+        assert_eq!(outgoing.get(TRACESTATE_HEADER).unwrap(), "tero=1");
+    }
+
+    #[test]
+    fn otel_context_names_the_incoming_span_as_remote_parent() {
+        let parent = TraceContext::from_headers(&headers(TRACEPARENT)).unwrap();
+        let cx = parent.otel_context();
+        let span_context = cx.span().span_context().clone();
+
+        assert!(span_context.is_remote());
+        assert!(span_context.is_sampled());
+        assert_eq!(span_context.trace_id().to_string(), parent.trace_id);
+        assert_eq!(span_context.span_id().to_string(), parent.span_id);
+    }
+
+    #[test]
+    fn exported_span_continues_the_incoming_trace() {
+        let parent = TraceContext::from_headers(&headers(TRACEPARENT)).unwrap();
+        let provider = SdkTracerProvider::builder().build();
+        let subscriber = registry().with(otel_layer(&provider));
+
+        let cx = tracing::subscriber::with_default(subscriber, || {
+            let span = info_span!("request");
+            span.set_parent(parent.otel_context()).unwrap();
+            TraceContext::from_span(&span, parent.tracestate.clone()).unwrap()
+        });
+
+        assert_eq!(cx.trace_id, parent.trace_id);
+        assert_ne!(cx.span_id, parent.span_id);
+        assert_eq!(cx.flags, "01");
+        assert_eq!(cx.tracestate.as_deref(), Some("tero=1"));
+    }
+
+    #[test]
+    fn spans_are_not_exported_without_an_endpoint() {
+        assert!(tracer_provider(None).unwrap().is_none());
+
+        let subscriber = registry();
+        let cx = tracing::subscriber::with_default(subscriber, || {
+            TraceContext::from_span(&info_span!("request"), None)
+        });
+        assert_eq!(cx, None);
+    }
+}
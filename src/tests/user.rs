@@ -0,0 +1,344 @@
+#[cfg(test)]
+mod tests {
+    use chrono::{Duration, TimeZone, Utc};
+    use serde_json::json;
+    use sqlx::{Pool, Postgres};
+    use uuid::Uuid;
+
+    use crate::{
+        api::user::patch_user_profile,
+        db::user::{
+            create_base_user, create_pseudo_user, delete_base_user_by_auth0_id,
+            delete_ghost_pseudo_users, get_base_user_by_id, get_user_activity_stats,
+            list_base_users, patch_base_user_by_id, pseudo_user_exists, touch_last_login,
+            tx_create_pseudo_user, username_taken,
+        },
+        models::{
+            error::ErrorCode,
+            game_base::Gender,
+            integration::IntegrationName,
+            system_log::SubjectType,
+            user::{Auth0User, ListUsersQuery, PatchUserRequest, SubjectId},
+        },
+        tests::support::{TestDb, seed_games},
+    };
+
+    fn auth0_user(auth0_id: &str) -> Auth0User {
+        Auth0User {
+            auth0_id: auth0_id.to_string(),
+            email: None,
+            email_verified: None,
+            username: Some("ola.nordmann".into()),
+            phone_number: None,
+            phone_verified: None,
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+            name: None,
+            nickname: None,
+            given_name: None,
+            family_name: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn delete_by_auth0_id_returns_404_for_unknown_user() {
+        let db = TestDb::create().await;
+        let auth0_id = format!("auth0|{}", Uuid::new_v4());
+
+        let error = delete_base_user_by_auth0_id(&db.pool, &auth0_id)
+            .await
+            .unwrap_err();
+
+        assert_eq!(error.code(), ErrorCode::NotFound);
+    }
+
+    #[tokio::test]
+    async fn delete_by_auth0_id_removes_user_pair() {
+        let db = TestDb::create().await;
+        let auth0_id = format!("auth0|{}", Uuid::new_v4());
+        let auth0_user = auth0_user(&auth0_id);
+
+        let mut tx = db.pool.begin().await.unwrap();
+        let id = create_base_user(&mut tx, &auth0_user).await.unwrap();
+        tx_create_pseudo_user(&mut tx, id).await.unwrap();
+        tx.commit().await.unwrap();
+
+        let deleted = delete_base_user_by_auth0_id(&db.pool, &auth0_id)
+            .await
+            .unwrap();
+
+        assert_eq!(deleted, id);
+        assert!(get_base_user_by_id(&db.pool, id).await.unwrap().is_none());
+        assert!(!pseudo_user_exists(&db.pool, id).await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn patch_updates_only_given_fields() {
+        let db = TestDb::create().await;
+        let auth0_id = format!("auth0|{}", Uuid::new_v4());
+
+        let mut tx = db.pool.begin().await.unwrap();
+        let id = create_base_user(&mut tx, &auth0_user(&auth0_id))
+            .await
+            .unwrap();
+        tx.commit().await.unwrap();
+
+        let request = PatchUserRequest {
+            username: Some("kari".into()),
+            gender: Some(Gender::Female),
+            family_name: None,
+            given_name: None,
+            birth_date: None,
+        };
+        let user = patch_base_user_by_id(&db.pool, &id, request).await.unwrap();
+
+        assert_eq!(user.username, "kari");
+        assert!(matches!(user.gender, Gender::Female));
+        assert_eq!(user.given_name.as_deref(), Some("ola"));
+    }
+
+    #[tokio::test]
+    async fn activity_stats_include_log_category_counts() {
+        let db = TestDb::create().await;
+        let stats = get_user_activity_stats(&db.pool).await.unwrap();
+
+        assert!(stats.log_counts.info >= 0);
+        assert!(stats.log_counts.warning >= 0);
+        assert!(stats.log_counts.critical >= 0);
+    }
+
+    #[tokio::test]
+    async fn activity_stats_count_base_and_pseudo_users() {
+        let db = TestDb::create().await;
+        let stats = get_user_activity_stats(&db.pool).await.unwrap();
+
+        assert!(stats.base_user_count >= 0);
+        assert!(stats.pseudo_user_count >= 0);
+        assert_eq!(stats.total_user_count, stats.base_user_count);
+    }
+
+    #[tokio::test]
+    async fn activity_stats_split_anonymous_and_registered_users() {
+        let db = TestDb::create().await;
+        let anonymous = create_pseudo_user(&db.pool).await.unwrap();
+
+        let mut tx = db.pool.begin().await.unwrap();
+        let id = create_base_user(&mut tx, &auth0_user(&format!("auth0|{}", Uuid::new_v4())))
+            .await
+            .unwrap();
+        tx_create_pseudo_user(&mut tx, id).await.unwrap();
+        tx.commit().await.unwrap();
+
+        let base_user_id = |id: Uuid| {
+            sqlx::query_scalar::<_, Option<Uuid>>(
+                r#"SELECT base_user_id FROM "pseudo_user" WHERE id = $1"#,
+            )
+            .bind(id)
+            .fetch_one(&db.pool)
+        };
+        assert_eq!(base_user_id(anonymous).await.unwrap(), None);
+        assert_eq!(base_user_id(id).await.unwrap(), Some(id));
+
+        let stats = get_user_activity_stats(&db.pool).await.unwrap();
+        assert!(stats.recent.this_month_users >= 1);
+        assert!(stats.recent.this_month_registered_users >= 1);
+        assert!(stats.recent.this_week_registered_users >= 1);
+    }
+
+    #[tokio::test]
+    async fn touching_last_login_counts_user_as_recently_logged_in() {
+        let db = TestDb::create().await;
+        let auth0_id = format!("auth0|{}", Uuid::new_v4());
+
+        let mut tx = db.pool.begin().await.unwrap();
+        let id = create_base_user(&mut tx, &auth0_user(&auth0_id))
+            .await
+            .unwrap();
+        tx.commit().await.unwrap();
+
+        let user = get_base_user_by_id(&db.pool, id).await.unwrap().unwrap();
+        assert!(user.last_login_at.is_none());
+        let before = get_user_activity_stats(&db.pool).await.unwrap();
+
+        touch_last_login(&db.pool, id).await.unwrap();
+
+        let user = get_base_user_by_id(&db.pool, id).await.unwrap().unwrap();
+        assert!(user.last_login_at.is_some());
+        let after = get_user_activity_stats(&db.pool).await.unwrap();
+        assert!(after.recently_logged_in_count > before.recently_logged_in_count);
+    }
+
+    #[tokio::test]
+    async fn patching_to_a_taken_username_is_conflict() {
+        let db = TestDb::create().await;
+        let taken = format!("tatt-{}", Uuid::new_v4());
+
+        let mut tx = db.pool.begin().await.unwrap();
+        let owner = create_base_user(&mut tx, &auth0_user(&format!("auth0|{}", Uuid::new_v4())))
+            .await
+            .unwrap();
+        let other = create_base_user(&mut tx, &auth0_user(&format!("auth0|{}", Uuid::new_v4())))
+            .await
+            .unwrap();
+        tx.commit().await.unwrap();
+
+        let rename = |username: &str| PatchUserRequest {
+            username: Some(username.into()),
+            ..Default::default()
+        };
+        patch_user_profile(&db.pool, owner, rename(&taken))
+            .await
+            .unwrap();
+
+        assert!(username_taken(&db.pool, &taken, None).await.unwrap());
+        assert!(!username_taken(&db.pool, &taken, Some(owner)).await.unwrap());
+
+        // Keeping your own name is not a conflict
+        let kept = patch_user_profile(&db.pool, owner, rename(&taken))
+            .await
+            .unwrap();
+        assert_eq!(kept.username, taken);
+
+        let error = patch_user_profile(&db.pool, other, rename(&taken))
+            .await
+            .unwrap_err();
+        assert_eq!(error.code(), ErrorCode::Conflict);
+    }
+
+    #[tokio::test]
+    async fn listing_users_excludes_those_created_outside_range() {
+        let db = TestDb::create().await;
+        let base = Utc.with_ymd_and_hms(2001, 3, 1, 12, 0, 0).unwrap()
+            + Duration::minutes(rand::random_range(0..500_000));
+
+        let mut ids = Vec::new();
+        let mut tx = db.pool.begin().await.unwrap();
+        for offset in [-1, 0, 1] {
+            let mut auth0_user = auth0_user(&format!("auth0|{}", Uuid::new_v4()));
+            auth0_user.created_at = base + Duration::days(offset);
+            ids.push(create_base_user(&mut tx, &auth0_user).await.unwrap());
+        }
+        tx.commit().await.unwrap();
+
+        let query = ListUsersQuery {
+            page_num: 0,
+            created_after: Some(base - Duration::hours(1)),
+            created_before: Some(base + Duration::hours(1)),
+        };
+        let page = list_base_users(&db.pool, query).await.unwrap();
+        let page = serde_json::to_value(page).unwrap();
+        let listed: Vec<String> = page["items"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|user| user["id"].as_str().unwrap().to_string())
+            .collect();
+
+        assert!(listed.contains(&ids[1].to_string()));
+        assert!(!listed.contains(&ids[0].to_string()));
+        assert!(!listed.contains(&ids[2].to_string()));
+    }
+
+    #[test]
+    fn every_subject_maps_to_its_log_type_and_tagged_form() {
+        let id = Uuid::new_v4();
+        let cases = [
+            (
+                SubjectId::PseudoUser(id),
+                SubjectType::GuestUser,
+                id.to_string(),
+                json!({ "type": "pseudo_user", "id": id }),
+            ),
+            (
+                SubjectId::BaseUser(id),
+                SubjectType::RegisteredUser,
+                id.to_string(),
+                json!({ "type": "base_user", "id": id }),
+            ),
+            (
+                SubjectId::Integration(IntegrationName::from("session")),
+                SubjectType::Integration,
+                "session".to_string(),
+                json!({ "type": "integration", "id": "session" }),
+            ),
+        ];
+
+        for (subject, subject_type, id_string, serialized) in cases {
+            assert_eq!(SubjectType::from(&subject), subject_type);
+            assert_eq!(subject.id_string(), id_string);
+            assert_eq!(serde_json::to_value(&subject).unwrap(), serialized);
+
+            let parsed: SubjectId = serde_json::from_value(serialized).unwrap();
+            assert_eq!(parsed, subject);
+        }
+    }
+
+    async fn insert_pseudo_user(
+        pool: &Pool<Postgres>,
+        inactive_days: i64,
+        base_user_id: Option<Uuid>,
+    ) -> Uuid {
+        sqlx::query_scalar(
+            r#"
+            INSERT INTO "pseudo_user" (id, last_active, base_user_id)
+            VALUES (COALESCE($2, uuid_generate_v4()), NOW() - make_interval(days => $1), $2)
+            RETURNING id
+            "#,
+        )
+        .bind(inactive_days as i32)
+        .bind(base_user_id)
+        .fetch_one(pool)
+        .await
+        .unwrap()
+    }
+
+    async fn insert_base_user(pool: &Pool<Postgres>) -> Uuid {
+        sqlx::query_scalar(r#"INSERT INTO "base_user" (username) VALUES ('ola') RETURNING id"#)
+            .fetch_one(pool)
+            .await
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn only_stale_unlinked_pseudo_users_without_games_are_purged() {
+        let db = TestDb::create().await;
+        let pool = &db.pool;
+
+        let ghosts = [
+            insert_pseudo_user(pool, 200, None).await,
+            insert_pseudo_user(pool, 365, None).await,
+            insert_pseudo_user(pool, 181, None).await,
+        ];
+        let active = insert_pseudo_user(pool, 10, None).await;
+        let linked = insert_pseudo_user(pool, 200, Some(insert_base_user(pool).await)).await;
+        let creator = insert_pseudo_user(pool, 200, None).await;
+        seed_games(pool, creator, 1).await;
+
+        // Registered before the link column existed, so only the shared id ties them
+        let legacy = insert_base_user(pool).await;
+        sqlx::query(r#"INSERT INTO "pseudo_user" (id, last_active) VALUES ($1, NOW() - INTERVAL '200 days')"#)
+            .bind(legacy)
+            .execute(pool)
+            .await
+            .unwrap();
+        let saved_game = seed_games(pool, Uuid::new_v4(), 1).await[0];
+        sqlx::query(r#"INSERT INTO "saved_game" (id, user_id, base_id) VALUES ($1, $2, $3)"#)
+            .bind(Uuid::new_v4())
+            .bind(legacy)
+            .bind(saved_game)
+            .execute(pool)
+            .await
+            .unwrap();
+
+        // A batch smaller than the backlog has to loop
+        let purged = delete_ghost_pseudo_users(pool, 180, 2).await.unwrap();
+        assert_eq!(purged, ghosts.len() as u64);
+
+        for id in ghosts {
+            assert!(!pseudo_user_exists(pool, id).await.unwrap());
+        }
+        for id in [active, linked, creator, legacy] {
+            assert!(pseudo_user_exists(pool, id).await.unwrap());
+        }
+    }
+}
@@ -0,0 +1,92 @@
+#[cfg(test)]
+mod tests {
+    use std::{sync::Arc, time::Duration};
+
+    use sqlx::{Pool, Postgres};
+
+    use crate::{
+        db::user::{create_pseudo_user, pseudo_user_exists},
+        models::user::SubjectId,
+        tests::support::{TestApp, seed_users},
+    };
+
+    /// Counts every DELETE statement on pseudo_user, also those matching no
+    /// rows, and holds each one long enough for the other requests to overlap
+    async fn count_pseudo_user_deletes(pool: &Pool<Postgres>) {
+        for statement in [
+            r#"CREATE TABLE "pseudo_user_delete" (id SERIAL PRIMARY KEY)"#,
+            r#"
+            CREATE FUNCTION count_pseudo_user_delete() RETURNS TRIGGER AS $$
+            BEGIN
+                INSERT INTO "pseudo_user_delete" DEFAULT VALUES;
+                PERFORM pg_sleep(0.2);
+                RETURN NULL;
+            END;
+            $$ LANGUAGE plpgsql
+            "#,
+            r#"
+            CREATE TRIGGER count_pseudo_user_delete
+            BEFORE DELETE ON "pseudo_user"
+            FOR EACH STATEMENT EXECUTE FUNCTION count_pseudo_user_delete()
+            "#,
+        ] {
+            sqlx::query(statement).execute(pool).await.unwrap();
+        }
+    }
+
+    async fn wait_for_syncs(app: &TestApp) {
+        for _ in 0..50 {
+            if app.state.get_user_syncs().in_flight_count() == 0 {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(20)).await;
+        }
+    }
+
+    #[tokio::test]
+    async fn concurrent_requests_run_a_single_sync() {
+        let app = TestApp::spawn().await;
+        let pool = &app.db.pool;
+        let base_id = seed_users(pool, 1).await[0];
+        let guest_id = create_pseudo_user(pool).await.unwrap();
+        count_pseudo_user_deletes(pool).await;
+
+        let handles = (0..20).map(|_| {
+            let state = Arc::clone(&app.state);
+            tokio::spawn(async move {
+                let subject = SubjectId::Integration("auth0".into());
+                state.spawn_sync_user(base_id, guest_id, subject);
+            })
+        });
+
+        for result in futures::future::join_all(handles).await {
+            result.unwrap();
+        }
+        wait_for_syncs(&app).await;
+
+        let deletes: i64 = sqlx::query_scalar(r#"SELECT COUNT(*) FROM "pseudo_user_delete""#)
+            .fetch_one(pool)
+            .await
+            .unwrap();
+        assert_eq!(deletes, 1);
+        assert!(!pseudo_user_exists(pool, guest_id).await.unwrap());
+        assert_eq!(app.state.get_user_syncs().in_flight_count(), 0);
+    }
+
+    #[tokio::test]
+    async fn sync_removes_guest_pseudo_user_but_keeps_own() {
+        let app = TestApp::spawn().await;
+        let pool = &app.db.pool;
+        let base_id = seed_users(pool, 1).await[0];
+        let guest_id = create_pseudo_user(pool).await.unwrap();
+        let subject = SubjectId::Integration("auth0".into());
+
+        app.state
+            .spawn_sync_user(base_id, guest_id, subject.clone());
+        app.state.spawn_sync_user(base_id, base_id, subject);
+        wait_for_syncs(&app).await;
+
+        assert!(!pseudo_user_exists(pool, guest_id).await.unwrap());
+        assert!(pseudo_user_exists(pool, base_id).await.unwrap());
+    }
+}
@@ -0,0 +1,36 @@
+#[cfg(test)]
+mod tests {
+    use std::{
+        sync::atomic::{AtomicU8, Ordering},
+        time::Duration,
+    };
+
+    use crate::service::util::try_with_retry;
+
+    #[tokio::test]
+    async fn retry_stops_at_first_success() {
+        let calls = AtomicU8::new(0);
+        let result = try_with_retry(3, Duration::from_millis(1), || async {
+            match calls.fetch_add(1, Ordering::SeqCst) {
+                0 | 1 => Err("Connection reset"),
+                _ => Ok("synced"),
+            }
+        })
+        .await;
+
+        assert_eq!(result, Ok("synced"));
+        assert_eq!(calls.load(Ordering::SeqCst), 3);
+    }
+
+    #[tokio::test]
+    async fn retry_returns_last_error_once_attempts_run_out() {
+        let calls = AtomicU8::new(0);
+        let result: Result<(), u8> = try_with_retry(3, Duration::from_millis(1), || async {
+            Err(calls.fetch_add(1, Ordering::SeqCst))
+        })
+        .await;
+
+        assert_eq!(result, Err(2));
+        assert_eq!(calls.load(Ordering::SeqCst), 3);
+    }
+}
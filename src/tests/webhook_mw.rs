@@ -0,0 +1,37 @@
+#[cfg(test)]
+mod tests {
+    use hmac::{Hmac, Mac};
+    use sha2::Sha256;
+
+    use crate::api::webhook_mw::verify_signature;
+
+    fn sign(body: &[u8], key: &str) -> String {
+        let mut mac = Hmac::<Sha256>::new_from_slice(key.as_bytes()).unwrap();
+        mac.update(body);
+        hex::encode(mac.finalize().into_bytes())
+    }
+
+    #[test]
+    fn accepts_matching_signature() {
+        let body = br#"{"user_id":"auth0|123"}"#;
+        let signature = sign(body, "secret");
+
+        assert!(verify_signature(body, "secret", &signature));
+        assert!(verify_signature(body, "secret", &signature.to_uppercase()));
+    }
+
+    #[test]
+    fn rejects_tampered_body_wrong_key_and_garbage() {
+        let body = br#"{"user_id":"auth0|123"}"#;
+        let signature = sign(body, "secret");
+
+        assert!(!verify_signature(
+            br#"{"user_id":"auth0|666"}"#,
+            "secret",
+            &signature
+        ));
+        assert!(!verify_signature(body, "other", &signature));
+        assert!(!verify_signature(body, "secret", "not-hex"));
+        assert!(!verify_signature(body, "secret", ""));
+    }
+}
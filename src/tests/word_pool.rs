@@ -0,0 +1,72 @@
+#[cfg(test)]
+mod tests {
+    use rand::{Rng, distr::Alphabetic};
+    use uuid::Uuid;
+
+    use crate::{
+        db::key_vault::{delete_word, insert_word, list_words},
+        models::{
+            error::ServerError,
+            game_base::Validate,
+            key_vault::{CreateWordRequest, WordSlot},
+        },
+        tests::support::TestDb,
+    };
+
+    /// The key vault tests expect the seeded pool, so every test removes what it adds
+    fn unique_word() -> String {
+        rand::rng()
+            .sample_iter(Alphabetic)
+            .take(5)
+            .map(|c| (c as char).to_ascii_lowercase())
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn words_can_be_added_listed_and_removed() {
+        let db = TestDb::create().await;
+        let word = unique_word();
+
+        let added = insert_word(&db.pool, WordSlot::Suffix, &word)
+            .await
+            .unwrap();
+        assert_eq!(added.slot, WordSlot::Suffix);
+
+        let duplicate = insert_word(&db.pool, WordSlot::Suffix, &word).await;
+        assert!(matches!(duplicate, Err(ServerError::Api(status, _)) if status == 409));
+
+        let suffixes = list_words(&db.pool, Some(WordSlot::Suffix)).await.unwrap();
+        assert!(suffixes.iter().any(|w| w.id == added.id && w.word == word));
+        assert!(suffixes.iter().all(|w| w.slot == WordSlot::Suffix));
+
+        let prefixes = list_words(&db.pool, Some(WordSlot::Prefix)).await.unwrap();
+        assert!(prefixes.iter().all(|w| w.id != added.id));
+
+        let all = list_words(&db.pool, None).await.unwrap();
+        assert_eq!(all.len(), prefixes.len() + suffixes.len());
+
+        delete_word(&db.pool, added.id).await.unwrap();
+        let suffixes = list_words(&db.pool, Some(WordSlot::Suffix)).await.unwrap();
+        assert!(suffixes.iter().all(|w| w.id != added.id));
+    }
+
+    #[tokio::test]
+    async fn deleting_unknown_word_is_not_found() {
+        let db = TestDb::create().await;
+        let result = delete_word(&db.pool, Uuid::new_v4()).await;
+        assert!(matches!(result, Err(ServerError::NotFound(_))));
+    }
+
+    #[test]
+    fn words_must_be_short_lowercase_letters() {
+        let request = |word: &str| CreateWordRequest {
+            slot: WordSlot::Prefix,
+            word: word.into(),
+        };
+
+        assert!(request("blå").validate().is_ok());
+        for word in ["", "spooky", "Fest", "ab1", "a b"] {
+            assert!(request(word).validate().is_err(), "{}", word);
+        }
+    }
+}